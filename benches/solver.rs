@@ -0,0 +1,152 @@
+//! Benchmarks of parsing, solving and the single techniques
+//!
+//! `SUDOKU_CORPUS=FILE cargo bench` also measures the throughput for the
+//! puzzles of FILE, one per line, like the 49151 puzzles with 17 clues.
+
+use std::{env, fs, hint::black_box};
+
+use criterion::{criterion_group, criterion_main, BatchSize, Criterion, Throughput};
+use sudoku_solver::strategy::{ForcingChains, Neighbourhood, RemotePairs, SoleOption};
+use sudoku_solver::{Board, Difficulty, SolverConfig, Strategy};
+
+// A puzzle of each difficulty
+const PUZZLES: [(Difficulty, &str); 4] = [
+    (
+        Difficulty::Easy,
+        ".69...7.37..3.......4...6.8.2.5....9..8.4....3...2......1..7.2.57...2...9.....14.",
+    ),
+    (
+        Difficulty::Medium,
+        ".69...7..7..3..2....4..96.8.2......9..8.4....3...2......1..7...57...2...9.....14.",
+    ),
+    (
+        Difficulty::Hard,
+        "..58..2.9.......3.......5..2..6..4.1.8...9....57....6.72..4.15.....1..27..8..3...",
+    ),
+    (
+        Difficulty::Expert,
+        ".6...47..7..3..2....4..96.8.2.5....9..8.4....3...2......1..7.2.57.......9.....14.",
+    ),
+];
+
+// A puzzle with 17 clues that needs guessing
+const MINIMAL: &str =
+    ".......1.4.........2...........5.4.7..8...3....1.9....3..4..2...5.1........8.6...";
+
+fn board(puzzle: &str) -> Board {
+    puzzle.parse().expect("valid puzzle")
+}
+
+fn parse(c: &mut Criterion) {
+    c.bench_function("parse", |b| b.iter(|| black_box(PUZZLES[0].1).parse::<Board>()));
+}
+
+fn solve(c: &mut Criterion) {
+    let config = SolverConfig {
+        forcing_chain_depth: 2,
+        backtracking: true,
+        ..SolverConfig::default()
+    };
+
+    let mut group = c.benchmark_group("solve");
+    for (level, puzzle) in PUZZLES {
+        let board = board(puzzle);
+        group.bench_function(level.name(), |b| {
+            b.iter_batched(
+                || board.clone(),
+                |mut board| board.solve_with_config(&config),
+                BatchSize::SmallInput,
+            )
+        });
+    }
+
+    let board = board(MINIMAL);
+    group.bench_function("backtracking", |b| {
+        b.iter_batched(
+            || board.clone(),
+            |mut board| {
+                board.solve_with_config(&SolverConfig {
+                    backtracking: true,
+                    ..SolverConfig::default()
+                })
+            },
+            BatchSize::SmallInput,
+        )
+    });
+    group.bench_function("dlx", |b| {
+        b.iter_batched(
+            || board.clone(),
+            |mut board| board.solve_dlx(),
+            BatchSize::SmallInput,
+        )
+    });
+    group.bench_function("fast", |b| {
+        b.iter_batched(
+            || board.clone(),
+            |mut board| board.solve_fast(),
+            BatchSize::SmallInput,
+        )
+    });
+    group.finish();
+}
+
+fn corpus(c: &mut Criterion) {
+    let path = match env::var("SUDOKU_CORPUS") {
+        Ok(path) => path,
+        Err(_) => return,
+    };
+    let text = fs::read_to_string(&path).expect("readable corpus");
+    let boards = Board::<9>::parse_all(&text)
+        .filter_map(Result::ok)
+        .collect::<Vec<_>>();
+
+    let mut group = c.benchmark_group("corpus");
+    group.throughput(Throughput::Elements(boards.len() as u64));
+    group.sample_size(10);
+    group.bench_function("dlx", |b| {
+        b.iter(|| {
+            for board in &boards {
+                board.clone().solve_dlx();
+            }
+        })
+    });
+    group.bench_function("fast", |b| {
+        b.iter(|| {
+            for board in &boards {
+                board.clone().solve_fast();
+            }
+        })
+    });
+    group.finish();
+}
+
+// One pass of each technique over the board after the easier techniques
+fn techniques(c: &mut Criterion) {
+    let mut board = board(PUZZLES[3].1);
+    board.solve_with_config(&SolverConfig {
+        max_difficulty: Some(Difficulty::Hard),
+        ..SolverConfig::default()
+    });
+
+    let strategies: [&dyn Strategy; 4] = [
+        &SoleOption,
+        &Neighbourhood,
+        &RemotePairs,
+        &ForcingChains { depth: 1 },
+    ];
+
+    let mut group = c.benchmark_group("technique");
+    for strategy in strategies {
+        group.bench_function(strategy.name(), |b| {
+            b.iter_batched(
+                || board.clone(),
+                |mut board| strategy.apply(&mut board),
+                BatchSize::SmallInput,
+            )
+        });
+    }
+    group.finish();
+}
+
+criterion_group!(benches, parse, solve, corpus, techniques);
+criterion_main!(benches);
@@ -0,0 +1,62 @@
+use std::fmt;
+
+/// A move of a chess piece; in anti-knight and anti-king Sudoku cells a move
+/// apart can't hold the same value
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum Move {
+    Knight,
+    King,
+}
+
+impl Move {
+    // Steps of the move by rows and columns
+    fn offsets(self) -> &'static [(isize, isize)] {
+        match self {
+            Move::Knight => &[
+                (-2, -1), (-2, 1), (-1, -2), (-1, 2), (1, -2), (1, 2), (2, -1), (2, 1),
+            ],
+            Move::King => &[
+                (-1, -1), (-1, 0), (-1, 1), (0, -1), (0, 1), (1, -1), (1, 0), (1, 1),
+            ],
+        }
+    }
+
+    /// The cells of a board with `size` rows and columns one move away from
+    /// `(row, column)`
+    pub fn targets(
+        self, pos: (usize, usize), size: usize
+    ) -> impl Iterator<Item = (usize, usize)> {
+        self.offsets().iter().filter_map(move |&(dr, dc)| {
+            let row = pos.0.checked_add_signed(dr).filter(|&r| r < size)?;
+            let col = pos.1.checked_add_signed(dc).filter(|&c| c < size)?;
+            Some((row, col))
+        })
+    }
+}
+
+impl fmt::Display for Move {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Move::Knight => f.write_str("knight's move"),
+            Move::King => f.write_str("king's move"),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn targets() {
+        let knight = Move::Knight.targets((0, 0), 9).collect::<Vec<_>>();
+        assert_eq!(knight, vec![(1, 2), (2, 1)]);
+
+        let king = Move::King.targets((8, 4), 9).collect::<Vec<_>>();
+        assert_eq!(king, vec![(7, 3), (7, 4), (7, 5), (8, 3), (8, 5)]);
+
+        assert_eq!(Move::Knight.targets((4, 4), 9).count(), 8);
+        assert_eq!(Move::King.targets((3, 3), 4).count(), 3);
+    }
+}
@@ -0,0 +1,379 @@
+use std::fmt;
+
+use crate::chess::Move;
+use crate::step::{CellName, Reason};
+use crate::strategy::{Difficulty, Strategy};
+use crate::sudoku::{Board, Field, Parity};
+use crate::unit::Unit;
+
+/// A rule of the board, like the rows, a cage or a thermometer
+///
+/// The rows, columns and boxes are the built-in constraints of every board.
+/// Variants add further ones, which the solver combines: the peers take part
+/// in placing values and in the techniques, the propagation removes
+/// candidates while solving and the validation checks the values.
+pub trait Constraint<const N: usize = 9>: fmt::Debug + Send + Sync {
+    /// The units whose cells must hold every value once
+    fn units(&self, _board: &Board<N>) -> Vec<Unit> {
+        Vec::new()
+    }
+
+    /// The cells that can't hold the same value as the cell at `pos`; the
+    /// other cells of the units by default
+    fn peers(&self, board: &Board<N>, pos: (usize, usize)) -> Vec<(usize, usize)> {
+        let idx = pos.0 * N + pos.1;
+        let mut peers = Vec::new();
+
+        for unit in self.units(board) {
+            if board.unit_cells(unit).any(|i| i == idx) {
+                peers.extend(board.unit_cells(unit).map(|i| (i / N, i % N)));
+            }
+        }
+
+        peers.retain(|&other| other != pos);
+        peers
+    }
+
+    /// Whether the cells at `a` and `b` are peers
+    fn sees(&self, board: &Board<N>, a: (usize, usize), b: (usize, usize)) -> bool {
+        self.peers(board, a).contains(&b)
+    }
+
+    /// Removes the candidates that break the rule and returns whether the
+    /// board changed
+    fn propagate(&self, _board: &mut Board<N>) -> bool {
+        false
+    }
+
+    /// Whether the values of the board keep the rule; by default no peers
+    /// hold the same value
+    fn is_valid(&self, board: &Board<N>) -> bool {
+        (0..N * N).all(|idx| match board.field_at(idx) {
+            Field::Value(val) => self.peers(board, (idx / N, idx % N))
+                .iter()
+                .all(|&pos| board.field(pos) != Field::Value(val)),
+            Field::Options(_) => true,
+        })
+    }
+
+    /// Whether the units and the candidates express the whole rule, so that
+    /// the exact cover solver can take it
+    fn is_exact(&self) -> bool {
+        false
+    }
+
+    /// The rule in words for reporting that it is broken, like `cage 7: r1c1
+    /// r1c2`; `None` for rules that only keep peers apart, whose breaks are
+    /// values that occur twice
+    fn describe(&self) -> Option<String> {
+        None
+    }
+}
+
+// The cells like `r1c1 r1c2`
+pub(crate) fn cell_names(cells: &[(usize, usize)]) -> String {
+    cells.iter().map(|&cell| CellName(cell).to_string()).collect::<Vec<_>>().join(" ")
+}
+
+// Whether no value occurs twice in one of the units
+fn units_valid<const N: usize>(
+    board: &Board<N>, mut units: impl Iterator<Item = Unit>
+) -> bool {
+    units.all(|unit| {
+        let mut seen = [false; 35];
+        board.unit(unit).all(|fld| match fld {
+            Field::Value(v) => !std::mem::replace(&mut seen[v as usize - 1], true),
+            Field::Options(_) => true,
+        })
+    })
+}
+
+/// The rows of the board
+#[derive(Clone, Copy, Debug)]
+pub struct Rows;
+
+/// The columns of the board
+#[derive(Clone, Copy, Debug)]
+pub struct Columns;
+
+/// The boxes of the board or its irregular regions
+#[derive(Clone, Copy, Debug)]
+pub struct Boxes;
+
+impl<const N: usize> Constraint<N> for Rows {
+    fn units(&self, board: &Board<N>) -> Vec<Unit> {
+        board.rows().collect()
+    }
+
+    fn peers(&self, _board: &Board<N>, pos: (usize, usize)) -> Vec<(usize, usize)> {
+        (0..N).filter(|&col| col != pos.1).map(|col| (pos.0, col)).collect()
+    }
+
+    fn sees(&self, _board: &Board<N>, a: (usize, usize), b: (usize, usize)) -> bool {
+        a.0 == b.0 && a != b
+    }
+
+    fn is_valid(&self, board: &Board<N>) -> bool {
+        units_valid(board, board.rows())
+    }
+
+    fn is_exact(&self) -> bool {
+        true
+    }
+}
+
+impl<const N: usize> Constraint<N> for Columns {
+    fn units(&self, board: &Board<N>) -> Vec<Unit> {
+        board.cols().collect()
+    }
+
+    fn peers(&self, _board: &Board<N>, pos: (usize, usize)) -> Vec<(usize, usize)> {
+        (0..N).filter(|&row| row != pos.0).map(|row| (row, pos.1)).collect()
+    }
+
+    fn sees(&self, _board: &Board<N>, a: (usize, usize), b: (usize, usize)) -> bool {
+        a.1 == b.1 && a != b
+    }
+
+    fn is_valid(&self, board: &Board<N>) -> bool {
+        units_valid(board, board.cols())
+    }
+
+    fn is_exact(&self) -> bool {
+        true
+    }
+}
+
+impl<const N: usize> Constraint<N> for Boxes {
+    fn units(&self, board: &Board<N>) -> Vec<Unit> {
+        board.boxes().collect()
+    }
+
+    fn peers(&self, board: &Board<N>, pos: (usize, usize)) -> Vec<(usize, usize)> {
+        let cells = match board.regions() {
+            Some(regions) => {
+                let region = regions[pos.0 * N + pos.1];
+                (0..N * N)
+                    .filter(|&idx| regions[idx] == region)
+                    .map(|idx| (idx / N, idx % N))
+                    .collect::<Vec<_>>()
+            }
+            None => {
+                let (rows, cols) = board.box_size();
+                let (row, col) = (pos.0 / rows * rows, pos.1 / cols * cols);
+                (row..row + rows)
+                    .flat_map(|r| (col..col + cols).map(move |c| (r, c)))
+                    .collect()
+            }
+        };
+
+        cells.into_iter().filter(|&other| other != pos).collect()
+    }
+
+    fn sees(&self, board: &Board<N>, a: (usize, usize), b: (usize, usize)) -> bool {
+        let region = |pos: (usize, usize)| match board.regions() {
+            Some(regions) => regions[pos.0 * N + pos.1],
+            None => {
+                let (rows, cols) = board.box_size();
+                pos.0 / rows * (N / cols) + pos.1 / cols
+            }
+        };
+
+        a != b && region(a) == region(b)
+    }
+
+    fn is_valid(&self, board: &Board<N>) -> bool {
+        units_valid(board, board.boxes())
+    }
+
+    fn is_exact(&self) -> bool {
+        true
+    }
+}
+
+/// A further unit, like a diagonal or a window
+impl<const N: usize> Constraint<N> for Unit {
+    fn units(&self, _board: &Board<N>) -> Vec<Unit> {
+        vec![*self]
+    }
+
+    fn is_valid(&self, board: &Board<N>) -> bool {
+        units_valid(board, std::iter::once(*self))
+    }
+
+    fn is_exact(&self) -> bool {
+        true
+    }
+}
+
+/// The cells a move apart
+impl<const N: usize> Constraint<N> for Move {
+    fn peers(&self, _board: &Board<N>, pos: (usize, usize)) -> Vec<(usize, usize)> {
+        self.targets(pos, N).collect()
+    }
+}
+
+/// The parity marks of the cells, nothing without marks
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(transparent))]
+pub(crate) struct Parities(pub Vec<Option<Parity>>);
+
+impl<const N: usize> Constraint<N> for Parities {
+    fn propagate(&self, board: &mut Board<N>) -> bool {
+        let mut changed = false;
+        for (idx, parity) in self.0.iter().enumerate() {
+            if let Some(parity) = parity {
+                for val in (1..=N as u8).filter(|&v| !parity.allows(v)) {
+                    changed |= board.eliminate(idx, val, Reason::Given);
+                }
+            }
+        }
+
+        changed
+    }
+
+    fn is_valid(&self, board: &Board<N>) -> bool {
+        self.0.iter().zip(board.fields()).all(|(parity, fld)| match (parity, fld) {
+            (Some(parity), Field::Value(val)) => parity.allows(val),
+            _ => true,
+        })
+    }
+
+    fn is_exact(&self) -> bool {
+        true
+    }
+
+    fn describe(&self) -> Option<String> {
+        Some("the parity marks".to_string())
+    }
+}
+
+/// Propagates the constraints added with
+/// [`Board::add_constraint`](crate::Board::add_constraint)
+pub struct CustomConstraints;
+
+impl<const N: usize> Strategy<N> for CustomConstraints {
+    fn name(&self) -> &'static str {
+        "custom constraints"
+    }
+
+    fn difficulty(&self) -> Difficulty {
+        Difficulty::Medium
+    }
+
+    fn cost(&self) -> u32 {
+        5
+    }
+
+    fn apply(&self, board: &mut Board<N>) -> bool {
+        let mut changed = false;
+        for constraint in board.custom_constraints().to_vec() {
+            changed |= constraint.propagate(board);
+        }
+
+        changed
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::killer::Cage;
+    use crate::shape::Shape;
+    use crate::sudoku::SolveResult;
+
+    // Two cells with an odd sum
+    #[derive(Debug)]
+    struct OddSum((usize, usize), (usize, usize));
+
+    impl Constraint for OddSum {
+        fn peers(&self, _board: &Board, pos: (usize, usize)) -> Vec<(usize, usize)> {
+            match pos {
+                pos if pos == self.0 => vec![self.1],
+                pos if pos == self.1 => vec![self.0],
+                _ => Vec::new(),
+            }
+        }
+
+        fn propagate(&self, board: &mut Board) -> bool {
+            let (other, val) = match (board.field(self.0), board.field(self.1)) {
+                (Field::Value(v), _) => (self.1, v),
+                (_, Field::Value(v)) => (self.0, v),
+                _ => return false,
+            };
+
+            let mut changed = false;
+            for same in (1..=9).filter(|v| v % 2 == val % 2) {
+                let reason = Reason::Custom("odd sum".into());
+                changed |= board.eliminate(other.0 * 9 + other.1, same, reason);
+            }
+            changed
+        }
+
+        fn is_valid(&self, board: &Board) -> bool {
+            match (board.field(self.0), board.field(self.1)) {
+                (Field::Value(a), Field::Value(b)) => (a + b) % 2 == 1,
+                _ => true,
+            }
+        }
+    }
+
+    #[test]
+    fn builtin() {
+        let board = Board::<9>::new();
+        let peers = Constraint::<9>::peers(&Boxes, &board, (4, 5));
+        assert_eq!(
+            peers,
+            vec![(3, 3), (3, 4), (3, 5), (4, 3), (4, 4), (5, 3), (5, 4), (5, 5)]
+        );
+        assert_eq!(Constraint::<9>::units(&Rows, &board).len(), 9);
+        assert!(Constraint::<9>::is_exact(&Columns));
+
+        let diagonal = Unit::Diagonal(0);
+        assert_eq!(Constraint::<9>::peers(&diagonal, &board, (2, 2)).len(), 8);
+        assert!(Constraint::<9>::peers(&diagonal, &board, (2, 3)).is_empty());
+        assert!(!Constraint::<9>::is_exact(&Move::King));
+    }
+
+    #[test]
+    fn custom() {
+        let mut board: Board = format!("3{}", ".".repeat(80)).parse().unwrap();
+        board.add_constraint(OddSum((0, 0), (8, 8)));
+
+        assert!(board.sees(0, 80));
+        assert!(!board.candidates((8, 8)).unwrap().contains(&3));
+        assert!(CustomConstraints.apply(&mut board));
+        assert_eq!(board.candidates((8, 8)).as_deref(), Some(&[2, 4, 6, 8][..]));
+        assert_eq!(board.constraints().count(), 5);
+
+        let mut copy = board.clone();
+        assert_eq!(copy.solve_dlx(), SolveResult::Solved);
+        assert!(copy.is_valid());
+
+        let mut board = board.puzzle();
+        assert_eq!(board.custom_constraints().len(), 1);
+        board.set((8, 8), 5);
+        assert!(!board.is_valid());
+        // only the sum breaks the rule, which has no description
+        assert_eq!(board.broken_constraints().count(), 1);
+        assert_eq!(board.broken_constraints().next().unwrap().describe(), None);
+    }
+
+    #[test]
+    fn broken_constraints() {
+        let mut board = Board::<4>::new();
+        board.add_cage(Cage { sum: 7, cells: vec![(0, 0), (0, 1)] }).unwrap();
+        board.add_shape(Shape::Thermometer(vec![(1, 0), (2, 0)])).unwrap();
+        board.set((0, 0), 1);
+        board.set((0, 1), 2);
+        board.set((2, 0), 4);
+        assert_eq!(board.broken_constraints().count(), 1);
+
+        board.set((1, 0), 4);
+        let rules = board.broken_constraints()
+            .filter_map(|rule| rule.describe())
+            .collect::<Vec<_>>();
+        assert_eq!(rules, ["cage 7: r1c1 r1c2", "thermometer: r2c1 r3c1"]);
+    }
+}
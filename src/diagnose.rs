@@ -0,0 +1,149 @@
+use std::fmt;
+
+use crate::step::CellName;
+use crate::strategy::{Solver, SolverConfig};
+use crate::sudoku::Board;
+use crate::unit::Unit;
+
+/// The state of a board the solver got stuck on, see [`Board::diagnose`]
+#[derive(Clone, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+pub struct Diagnosis {
+    /// The number of cells without value
+    pub unsolved: usize,
+    /// The cells `(row, column)` with two candidates left
+    pub bivalue: Vec<(usize, usize)>,
+    /// The candidates of every unit with unsolved cells
+    pub units: Vec<UnitCandidates>,
+    /// The built-in techniques that would make progress, including forcing
+    /// chains of depth 2
+    pub applicable: Vec<&'static str>,
+}
+
+/// The candidates of a unit in [`Diagnosis`]
+#[derive(Clone, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+pub struct UnitCandidates {
+    pub unit: Unit,
+    /// The number of cells without value
+    pub unsolved: usize,
+    /// Every missing value with the number of cells that can take it
+    pub values: Vec<(u8, usize)>,
+}
+
+impl<const N: usize> Board<N> {
+    /// Describes why the board is not solved: the cells left, the bivalue
+    /// cells, the candidates of the units and the techniques that would still
+    /// make progress
+    pub fn diagnose(&self) -> Diagnosis {
+        let unsolved = (0..N * N).filter(|&idx| self.value(idx) == 0).count();
+        let bivalue = (0..N * N)
+            .filter(|&idx| self.mask(idx).count_ones() == 2)
+            .map(|idx| (idx / N, idx % N))
+            .collect();
+
+        let units = self.units()
+            .filter_map(|unit| {
+                // the cells that can take each value and the values placed
+                let (mut count, mut placed, mut unsolved) = ([0; N], 0u64, 0);
+                for idx in self.unit_cells(unit) {
+                    match self.field_at(idx).candidates() {
+                        Some(opts) => {
+                            opts.iter().for_each(|&v| count[v as usize - 1] += 1);
+                            unsolved += 1;
+                        }
+                        None => placed |= 1 << (self.value(idx) - 1),
+                    }
+                }
+
+                (unsolved > 0).then(|| UnitCandidates {
+                    unit,
+                    unsolved,
+                    values: (0..N)
+                        .filter(|&i| placed & 1 << i == 0)
+                        .map(|i| (i as u8 + 1, count[i]))
+                        .collect(),
+                })
+            })
+            .collect();
+
+        let config = SolverConfig {
+            forcing_chain_depth: 2,
+            ..SolverConfig::default()
+        };
+        let applicable = Solver::<N>::with_config(&config)
+            .strategies()
+            .iter()
+            .filter(|strategy| {
+                let mut trial = self.clone();
+                trial.record_steps(false);
+                strategy.apply(&mut trial)
+            })
+            .map(|strategy| strategy.name())
+            .collect();
+
+        Diagnosis { unsolved, bivalue, units, applicable }
+    }
+}
+
+impl fmt::Display for Diagnosis {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(
+            f, "{} cells unsolved, {} of them bivalue",
+            self.unsolved, self.bivalue.len()
+        )?;
+        for (i, &cell) in self.bivalue.iter().enumerate() {
+            write!(f, "{}{}", if i == 0 { ": " } else { " " }, CellName(cell))?;
+        }
+
+        for unit in &self.units {
+            write!(f, "\n{}: {} cells", unit.unit, unit.unsolved)?;
+            for (i, (val, cells)) in unit.values.iter().enumerate() {
+                let sep = if i == 0 { ", value " } else { ", " };
+                write!(f, "{}{} in {}", sep, val, cells)?;
+            }
+        }
+
+        match self.applicable.as_slice() {
+            [] => f.write_str("\nno built-in technique makes progress"),
+            names => write!(f, "\nprogress by: {}", names.join(", ")),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::strategy::Difficulty;
+    use crate::sudoku::SolveResult;
+
+    #[test]
+    fn diagnose() {
+        let mut board: Board = concat!(
+            "..58..2.9.......3.......5..2..6..4.1.8...9....57....6.72..4.15.....1",
+            "..27..8..3..."
+        ).parse().unwrap();
+        let config = SolverConfig {
+            max_difficulty: Some(Difficulty::Medium),
+            ..SolverConfig::default()
+        };
+        assert_eq!(board.solve_with_config(&config), SolveResult::Stuck);
+
+        let diagnosis = board.diagnose();
+        assert_eq!(diagnosis.unsolved, 20);
+        assert_eq!(diagnosis.bivalue.len(), 14);
+        assert_eq!(diagnosis.units[0], UnitCandidates {
+            unit: Unit::Row(0),
+            unsolved: 2,
+            values: vec![(1, 2), (4, 2)],
+        });
+        assert_eq!(diagnosis.applicable, ["remote pairs", "forcing chains"]);
+
+        let text = diagnosis.to_string();
+        assert!(text.starts_with("20 cells unsolved, 14 of them bivalue: r1c6 r1c8 "));
+        assert!(text.contains("\nrow 1: 2 cells, value 1 in 2, 4 in 2\n"));
+        assert!(text.ends_with("\nprogress by: remote pairs, forcing chains"));
+
+        assert_eq!(Board::<4>::new().diagnose().bivalue, []);
+    }
+}
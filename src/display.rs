@@ -0,0 +1,436 @@
+use std::fmt::{self, Write};
+
+use crate::sudoku::{Board, Field};
+use crate::symbols::Symbols;
+
+// The character of `fld` from `symbols` or with the values 10 and above as
+// letters starting with A
+fn symbol(fld: Field, symbols: Option<&Symbols>) -> char {
+    match (fld, symbols) {
+        (Field::Value(v), Some(symbols)) => symbols.symbol(v),
+        (Field::Value(v), None) => std::char::from_digit(u32::from(v), 36)
+            .map_or('?', |c| c.to_ascii_uppercase()),
+        (Field::Options(_), _) => '.',
+    }
+}
+
+/// Formats the board as a line of `N * N` characters with `.` for empty
+/// cells. The alternate form `{:#}` prints a grid of `N` lines with the
+/// squares set apart by spaces and blank lines.
+impl<const N: usize> fmt::Display for Board<N> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        fmt::Display::fmt(&self.with_symbols(None), f)
+    }
+}
+
+/// Displays the board like [`Board`] with other characters for the values,
+/// see [`Board::with_symbols`]
+pub struct WithSymbols<'a, const N: usize = 9> {
+    board: &'a Board<N>,
+    symbols: Option<&'a Symbols>,
+}
+
+impl<const N: usize> Board<N> {
+    /// Wrapper to display the board as line or with `{:#}` as grid with the
+    /// characters of `symbols` for the values
+    pub fn with_symbols<'a>(
+        &'a self, symbols: Option<&'a Symbols>
+    ) -> WithSymbols<'a, N> {
+        WithSymbols { board: self, symbols }
+    }
+}
+
+impl<const N: usize> fmt::Display for WithSymbols<'_, N> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        if !f.alternate() {
+            return self.board.fields()
+                .try_for_each(|fld| write!(f, "{}", symbol(fld, self.symbols)));
+        }
+
+        let (rows, cols) = self.board.box_size();
+        for (i, fld) in self.board.fields().enumerate() {
+            match i {
+                0 => (),
+                _ if i % (rows * N) == 0 => f.write_str("\n\n")?,
+                _ if i % N == 0 => f.write_str("\n")?,
+                _ if i % cols == 0 => f.write_str("   ")?,
+                _ => (),
+            }
+
+            write!(f, "{}", symbol(fld, self.symbols))?;
+        }
+
+        Ok(())
+    }
+}
+
+/// Draws the board with box-drawing characters, see [`Board::pretty`]
+pub struct Pretty<'a, const N: usize = 9> {
+    board: &'a Board<N>,
+    labels: bool,
+    candidates: bool,
+    highlight: &'a [usize],
+    symbols: Option<&'a Symbols>,
+}
+
+impl<const N: usize> Board<N> {
+    /// Wrapper to display the board as grid with box-drawing characters
+    pub fn pretty(&self) -> Pretty<'_, N> {
+        Pretty {
+            board: self,
+            labels: false,
+            candidates: false,
+            highlight: &[],
+            symbols: None,
+        }
+    }
+}
+
+impl<'a, const N: usize> Pretty<'a, N> {
+    /// Numbers the rows and columns at the left and top
+    pub fn labels(mut self, enable: bool) -> Self {
+        self.labels = enable;
+        self
+    }
+
+    /// Shows the candidates of the unsolved cells as small grid of the size of
+    /// a box inside the cell
+    pub fn candidates(mut self, enable: bool) -> Self {
+        self.candidates = enable;
+        self
+    }
+
+    /// Shows the cells with the indices `cells` in reverse video, with the
+    /// escape codes of ANSI terminals
+    pub fn highlight(mut self, cells: &'a [usize]) -> Self {
+        self.highlight = cells;
+        self
+    }
+
+    /// Shows the values and candidates with the characters of `symbols`; the
+    /// labels stay numbers
+    pub fn symbols(mut self, symbols: Option<&'a Symbols>) -> Self {
+        self.symbols = symbols;
+        self
+    }
+}
+
+impl<const N: usize> fmt::Display for Pretty<'_, N> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let (rows, cols) = self.board.box_size();
+        // every cell takes `width` characters in `height` lines
+        let (width, height) = if self.candidates { (cols, rows) } else { (1, 1) };
+        let label = |i: usize| symbol(Field::Value(i as u8 + 1), None);
+        let candidate = |i: usize| symbol(Field::Value(i as u8 + 1), self.symbols);
+        let prefix = if self.labels { "  " } else { "" };
+
+        let segment = "─".repeat((width + 1) * cols + 1);
+        let line = |f: &mut fmt::Formatter, left, middle, right| {
+            f.write_str(prefix)?;
+            f.write_char(left)?;
+            for i in 0..N / cols {
+                if i != 0 {
+                    f.write_char(middle)?;
+                }
+                f.write_str(&segment)?;
+            }
+            f.write_char(right)
+        };
+
+        if self.labels {
+            let mut header = prefix.to_string();
+            for col in 0..N {
+                if col % cols == 0 {
+                    header.push(' ');
+                }
+                write!(header, " {:^width$}", label(col), width = width)?;
+            }
+            writeln!(f, "{}", header.trim_end())?;
+        }
+
+        line(f, '┌', '┬', '┐')?;
+        f.write_char('\n')?;
+
+        let fields = self.board.fields().collect::<Vec<_>>();
+        for (row, cells) in fields.chunks(N).enumerate() {
+            if row != 0 && row % rows == 0 {
+                line(f, '├', '┼', '┤')?;
+                f.write_char('\n')?;
+            } else if row != 0 && height > 1 {
+                f.write_str(prefix)?;
+                for _ in 0..N / cols {
+                    write!(f, "│{:w$}", "", w = (width + 1) * cols + 1)?;
+                }
+                writeln!(f, "│")?;
+            }
+
+            for sub in 0..height {
+                if self.labels && sub == height / 2 {
+                    write!(f, "{} ", label(row))?;
+                } else {
+                    f.write_str(prefix)?;
+                }
+
+                for (col, fld) in cells.iter().enumerate() {
+                    if col % cols == 0 {
+                        f.write_str("│ ")?;
+                    }
+
+                    let marked = self.highlight.contains(&(row * N + col));
+                    if marked {
+                        f.write_str("\x1b[7m")?;
+                    }
+
+                    match fld {
+                        Field::Options(opts) if self.candidates => {
+                            for i in sub * width..(sub + 1) * width {
+                                if opts.contains(&(i as u8 + 1)) {
+                                    f.write_char(candidate(i))?;
+                                } else {
+                                    f.write_char(' ')?;
+                                }
+                            }
+                        }
+                        _ if sub == height / 2 => {
+                            let c = symbol(*fld, self.symbols);
+                            write!(f, "{:^width$}", c, width = width)?;
+                        }
+                        _ => write!(f, "{:width$}", "", width = width)?,
+                    }
+
+                    if marked {
+                        f.write_str("\x1b[0m")?;
+                    }
+                    f.write_char(' ')?;
+                }
+                writeln!(f, "│")?;
+            }
+        }
+
+        line(f, '└', '┴', '┘')
+    }
+}
+
+/// Draws the board in the `.ss` format of Simple Sudoku, see
+/// [`Board::simple_sudoku`]
+pub struct SimpleSudoku<'a, const N: usize = 9> {
+    board: &'a Board<N>,
+    symbols: Option<&'a Symbols>,
+}
+
+impl<const N: usize> Board<N> {
+    /// Wrapper to display the board as grid of Simple Sudoku with `|` between
+    /// the boxes
+    pub fn simple_sudoku(&self) -> SimpleSudoku<'_, N> {
+        SimpleSudoku { board: self, symbols: None }
+    }
+}
+
+impl<'a, const N: usize> SimpleSudoku<'a, N> {
+    /// Shows the values with the characters of `symbols`
+    pub fn symbols(mut self, symbols: Option<&'a Symbols>) -> Self {
+        self.symbols = symbols;
+        self
+    }
+}
+
+impl<const N: usize> fmt::Display for SimpleSudoku<'_, N> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let (rows, cols) = self.board.box_size();
+        let edge = "-".repeat(N + N / cols - 1);
+        let middle = vec!["-".repeat(cols); N / cols].join("+");
+
+        writeln!(f, "*{}*", edge)?;
+        let fields = self.board.fields().collect::<Vec<_>>();
+        for (row, cells) in fields.chunks(N).enumerate() {
+            if row != 0 && row % rows == 0 {
+                writeln!(f, "|{}|", middle)?;
+            }
+
+            for (col, fld) in cells.iter().enumerate() {
+                if col % cols == 0 {
+                    f.write_char('|')?;
+                }
+                f.write_char(symbol(*fld, self.symbols))?;
+            }
+            writeln!(f, "|")?;
+        }
+        write!(f, "*{}*", edge)
+    }
+}
+
+/// Draws the board as TikZ picture for LaTeX, see [`Board::latex`]
+pub struct Latex<'a, const N: usize = 9> {
+    board: &'a Board<N>,
+}
+
+impl<const N: usize> Board<N> {
+    /// Wrapper to display the board as `tikzpicture` for LaTeX documents; the
+    /// document needs `\usepackage{tikz}`
+    pub fn latex(&self) -> Latex<'_, N> {
+        Latex { board: self }
+    }
+}
+
+impl<const N: usize> fmt::Display for Latex<'_, N> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let (rows, cols) = self.board.box_size();
+        writeln!(f, "\\begin{{tikzpicture}}[scale=0.6]")?;
+        writeln!(f, "  \\draw[thin] (0,0) grid ({},{});", N, N)?;
+        writeln!(
+            f, "  \\draw[very thick, xstep={}, ystep={}] (0,0) grid ({},{});",
+            cols, rows, N, N
+        )?;
+
+        for (i, fld) in self.board.fields().enumerate() {
+            if let Field::Value(_) = fld {
+                // TikZ counts the rows from the bottom
+                let (x, y) = (i % N, N - 1 - i / N);
+                let c = symbol(fld, None);
+                writeln!(f, "  \\node at ({}.5,{}.5) {{{}}};", x, y, c)?;
+            }
+        }
+
+        f.write_str("\\end{tikzpicture}")
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const TEST_BOARD : &str =
+        "92.......\
+         5..87....\
+         .38.91...\
+         .5293.16.\
+         .9.....3.\
+         .73.6498.\
+         ...41.25.\
+         ....53..1\
+         .......73";
+
+    #[test]
+    fn line() {
+        let board: Board = TEST_BOARD.parse().unwrap();
+        assert_eq!(board.to_string(), TEST_BOARD);
+    }
+
+    #[test]
+    fn grid() {
+        let board: Board = TEST_BOARD.parse().unwrap();
+        assert_eq!(
+            format!("{:#}", board),
+            "92.   ...   ...\n\
+             5..   87.   ...\n\
+             .38   .91   ...\n\
+             \n\
+             .52   93.   16.\n\
+             .9.   ...   .3.\n\
+             .73   .64   98.\n\
+             \n\
+             ...   41.   25.\n\
+             ...   .53   ..1\n\
+             ...   ...   .73"
+        );
+    }
+
+    #[test]
+    fn pretty() {
+        let board: Board = TEST_BOARD.parse().unwrap();
+        assert_eq!(
+            board.pretty().to_string(),
+            "┌───────┬───────┬───────┐\n\
+             │ 9 2 . │ . . . │ . . . │\n\
+             │ 5 . . │ 8 7 . │ . . . │\n\
+             │ . 3 8 │ . 9 1 │ . . . │\n\
+             ├───────┼───────┼───────┤\n\
+             │ . 5 2 │ 9 3 . │ 1 6 . │\n\
+             │ . 9 . │ . . . │ . 3 . │\n\
+             │ . 7 3 │ . 6 4 │ 9 8 . │\n\
+             ├───────┼───────┼───────┤\n\
+             │ . . . │ 4 1 . │ 2 5 . │\n\
+             │ . . . │ . 5 3 │ . . 1 │\n\
+             │ . . . │ . . . │ . 7 3 │\n\
+             └───────┴───────┴───────┘"
+        );
+    }
+
+    #[test]
+    fn pretty_options() {
+        let mut board: Board<4> = "1... .... .... ...2".parse().unwrap();
+        board.eliminate(1, 3, crate::Reason::Set);
+        assert_eq!(
+            board.pretty().labels(true).to_string(),
+            "    1 2  3 4\n\
+             \x20 ┌─────┬─────┐\n\
+             1 │ 1 . │ . . │\n\
+             2 │ . . │ . . │\n\
+             \x20 ├─────┼─────┤\n\
+             3 │ . . │ . . │\n\
+             4 │ . . │ . 2 │\n\
+             \x20 └─────┴─────┘"
+        );
+        assert_eq!(
+            board.pretty().candidates(true).to_string().lines().nth(2),
+            Some("│ 1   4 │ 34 34 │")
+        );
+        assert_eq!(
+            board.pretty().highlight(&[0, 15]).to_string().lines().nth(1),
+            Some("│ \x1b[7m1\x1b[0m . │ . . │")
+        );
+    }
+
+    #[test]
+    fn simple_sudoku() {
+        let board: Board = TEST_BOARD.parse().unwrap();
+        assert_eq!(
+            board.simple_sudoku().to_string(),
+            "*-----------*\n\
+             |92.|...|...|\n\
+             |5..|87.|...|\n\
+             |.38|.91|...|\n\
+             |---+---+---|\n\
+             |.52|93.|16.|\n\
+             |.9.|...|.3.|\n\
+             |.73|.64|98.|\n\
+             |---+---+---|\n\
+             |...|41.|25.|\n\
+             |...|.53|..1|\n\
+             |...|...|.73|\n\
+             *-----------*"
+        );
+        assert_eq!(board.simple_sudoku().to_string().parse::<Board>(), Ok(board));
+    }
+
+    #[test]
+    fn latex() {
+        let board: Board<4> = "1... .... .... ...2".parse().unwrap();
+        assert_eq!(
+            board.latex().to_string(),
+            "\\begin{tikzpicture}[scale=0.6]\n\
+             \x20 \\draw[thin] (0,0) grid (4,4);\n\
+             \x20 \\draw[very thick, xstep=2, ystep=2] (0,0) grid (4,4);\n\
+             \x20 \\node at (0.5,3.5) {1};\n\
+             \x20 \\node at (3.5,0.5) {2};\n\
+             \\end{tikzpicture}"
+        );
+    }
+
+    #[test]
+    fn other_sizes() {
+        let board: Board<6> = "12.... ...... ...... ...... ...... .....6".parse().unwrap();
+        assert_eq!(
+            format!("{:#}", board),
+            "12.   ...\n...   ...\n\n...   ...\n...   ...\n\n...   ...\n...   ..6"
+        );
+        assert_eq!(
+            board.pretty().to_string().lines().next(),
+            Some("┌───────┬───────┐")
+        );
+
+        let board: Board<16> = format!("9AG{}", ".".repeat(253)).parse().unwrap();
+        assert!(matches!(board.field((0, 1)), Field::Value(10)));
+        assert!(board.to_string().starts_with("9AG."));
+    }
+}
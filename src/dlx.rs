@@ -0,0 +1,189 @@
+use std::sync::atomic::{AtomicBool, Ordering};
+
+// Algorithm X with dancing links, see https://arxiv.org/abs/cs/0011047
+//
+// Node 0 is the root, the nodes 1..=columns are the column headers and all
+// further nodes belong to the rows of the matrix.
+pub struct Dlx {
+    left: Vec<usize>,
+    right: Vec<usize>,
+    up: Vec<usize>,
+    down: Vec<usize>,
+    column: Vec<usize>,
+    row: Vec<usize>,
+    size: Vec<usize>,
+}
+
+impl Dlx {
+    pub fn new(columns: usize) -> Self {
+        let count = columns + 1;
+        let mut dlx = Dlx {
+            left: (0..count).map(|i| (i + count - 1) % count).collect(),
+            right: (0..count).map(|i| (i + 1) % count).collect(),
+            up: (0..count).collect(),
+            down: (0..count).collect(),
+            column: (0..count).collect(),
+            row: vec![usize::MAX; count],
+            size: vec![0; count],
+        };
+        dlx.size[0] = usize::MAX;
+
+        dlx
+    }
+
+    pub fn add_row(&mut self, id: usize, columns: &[usize]) {
+        let first = self.left.len();
+
+        for (i, &col) in columns.iter().enumerate() {
+            let node = first + i;
+            let header = col + 1;
+
+            self.left.push(if i == 0 { first + columns.len() - 1 } else { node - 1 });
+            self.right.push(if i + 1 == columns.len() { first } else { node + 1 });
+            self.up.push(self.up[header]);
+            self.down.push(header);
+            self.column.push(header);
+            self.row.push(id);
+
+            let last = self.up[header];
+            self.down[last] = node;
+            self.up[header] = node;
+            self.size[header] += 1;
+        }
+    }
+
+    fn cover(&mut self, col: usize) {
+        self.right[self.left[col]] = self.right[col];
+        self.left[self.right[col]] = self.left[col];
+
+        let mut i = self.down[col];
+        while i != col {
+            let mut j = self.right[i];
+            while j != i {
+                self.down[self.up[j]] = self.down[j];
+                self.up[self.down[j]] = self.up[j];
+                self.size[self.column[j]] -= 1;
+                j = self.right[j];
+            }
+            i = self.down[i];
+        }
+    }
+
+    fn uncover(&mut self, col: usize) {
+        let mut i = self.up[col];
+        while i != col {
+            let mut j = self.left[i];
+            while j != i {
+                self.size[self.column[j]] += 1;
+                self.down[self.up[j]] = j;
+                self.up[self.down[j]] = j;
+                j = self.left[j];
+            }
+            i = self.up[i];
+        }
+
+        self.right[self.left[col]] = col;
+        self.left[self.right[col]] = col;
+    }
+
+    // Calls `found` with the row ids of every solution until `limit`
+    // solutions were found or `cancel` gets set and returns the number of
+    // solutions.
+    pub fn search(
+        &mut self, limit: usize, cancel: &AtomicBool, found: &mut dyn FnMut(&[usize])
+    ) -> usize {
+        let mut solution = Vec::new();
+        self.search_rec(&mut solution, limit, cancel, found)
+    }
+
+    fn search_rec(
+        &mut self, solution: &mut Vec<usize>, limit: usize, cancel: &AtomicBool,
+        found: &mut dyn FnMut(&[usize])
+    ) -> usize {
+        if self.right[0] == 0 {
+            found(solution);
+            return 1;
+        }
+
+        // choose the column with the fewest rows
+        let mut col = self.right[0];
+        let mut c = self.right[col];
+        while c != 0 {
+            if self.size[c] < self.size[col] {
+                col = c;
+            }
+            c = self.right[c];
+        }
+
+        let mut count = 0;
+        self.cover(col);
+
+        let mut r = self.down[col];
+        while r != col && count < limit && !cancel.load(Ordering::Relaxed) {
+            solution.push(self.row[r]);
+
+            let mut j = self.right[r];
+            while j != r {
+                self.cover(self.column[j]);
+                j = self.right[j];
+            }
+
+            count += self.search_rec(solution, limit - count, cancel, found);
+
+            let mut j = self.left[r];
+            while j != r {
+                self.uncover(self.column[j]);
+                j = self.left[j];
+            }
+
+            solution.pop();
+            r = self.down[r];
+        }
+
+        self.uncover(col);
+
+        count
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn knuth_example() {
+        // the example matrix from Knuth's paper
+        let mut dlx = Dlx::new(7);
+        dlx.add_row(0, &[2, 4, 5]);
+        dlx.add_row(1, &[0, 3, 6]);
+        dlx.add_row(2, &[1, 2, 5]);
+        dlx.add_row(3, &[0, 3]);
+        dlx.add_row(4, &[1, 6]);
+        dlx.add_row(5, &[3, 4, 6]);
+
+        let mut solutions = Vec::new();
+        let count = dlx.search(usize::MAX, &AtomicBool::new(false), &mut |rows| {
+            let mut rows = rows.to_vec();
+            rows.sort_unstable();
+            solutions.push(rows);
+        });
+
+        assert_eq!(count, 1);
+        assert_eq!(solutions, vec![vec![0, 3, 4]]);
+    }
+
+    #[test]
+    fn limit() {
+        let mut dlx = Dlx::new(1);
+        dlx.add_row(0, &[0]);
+        dlx.add_row(1, &[0]);
+        dlx.add_row(2, &[0]);
+
+        let cancel = AtomicBool::new(false);
+        assert_eq!(dlx.search(2, &cancel, &mut |_| ()), 2);
+        assert_eq!(dlx.search(usize::MAX, &cancel, &mut |_| ()), 3);
+
+        cancel.store(true, Ordering::Relaxed);
+        assert_eq!(dlx.search(usize::MAX, &cancel, &mut |_| ()), 0);
+    }
+}
@@ -0,0 +1,97 @@
+use std::{error, fmt};
+
+use crate::parse::ParseError;
+use crate::sudoku::ConflictError;
+
+/// Errors of the operations on a board
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum Error {
+    /// The puzzle could not be parsed
+    Parse(ParseError),
+    /// A cell can only hold the values 1 to 9
+    InvalidValue(u8),
+    /// A neighbour already holds the value
+    Conflict(ConflictError),
+    /// The puzzle has no solution
+    Unsolvable,
+    /// The step with this position doesn't fit the board
+    InvalidStep(usize),
+    /// Only this number of cells was given
+    TooFewCells(usize),
+    /// This number of cells was given, more than the board has
+    TooManyCells(usize),
+    /// The position `(row, column)` lies outside the board
+    InvalidCell((usize, usize)),
+    /// The region with this number has not `N` cells
+    InvalidRegion(usize),
+    /// The symbols aren't this number of different letters or digits
+    InvalidSymbols(usize),
+    /// The field with this name of a puzzle spec is invalid
+    InvalidSpec(&'static str),
+    /// Boards of this size take too much work for the operation
+    TooLarge(usize),
+}
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Error::Parse(err) => err.fmt(f),
+            Error::InvalidValue(v) => write!(f, "invalid value: {}", v),
+            Error::Conflict(err) => err.fmt(f),
+            Error::Unsolvable => f.write_str("the puzzle has no solution"),
+            Error::InvalidStep(i) => write!(f, "step {} doesn't fit the board", i + 1),
+            Error::TooFewCells(n) => write!(f, "too few cells: {}", n),
+            Error::TooManyCells(n) => write!(f, "too many cells: {}", n),
+            Error::InvalidRegion(r) => write!(f, "invalid region: {}", r + 1),
+            Error::InvalidSpec(field) => write!(f, "invalid {} in the spec", field),
+            Error::TooLarge(n) => write!(f, "boards of size {} are too large", n),
+            Error::InvalidSymbols(n) => {
+                write!(f, "the symbols must be {} different letters or digits", n)
+            }
+            Error::InvalidCell((row, col)) => {
+                write!(f, "cell ({}, {}) is outside the board", row + 1, col + 1)
+            }
+        }
+    }
+}
+
+impl error::Error for Error {
+    fn source(&self) -> Option<&(dyn error::Error + 'static)> {
+        match self {
+            Error::Parse(err) => Some(err),
+            Error::Conflict(err) => Some(err),
+            _ => None,
+        }
+    }
+}
+
+impl From<ParseError> for Error {
+    fn from(err: ParseError) -> Self {
+        Error::Parse(err)
+    }
+}
+
+impl From<ConflictError> for Error {
+    fn from(err: ConflictError) -> Self {
+        Error::Conflict(err)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::error::Error as _;
+
+    #[test]
+    fn display() {
+        assert_eq!(Error::InvalidValue(10).to_string(), "invalid value: 10");
+        assert_eq!(
+            Error::from(ParseError::TooFewCells(3)).to_string(),
+            "too few cells: 3"
+        );
+
+        let err = Error::from(ConflictError { cell: (1, 1), value: 4 });
+        assert_eq!(err.to_string(), "4 is already placed at (2, 2)");
+        assert!(err.source().is_some());
+    }
+}
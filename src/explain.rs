@@ -0,0 +1,136 @@
+use crate::step::{Action, Reason, Step};
+use crate::strategy::Solver;
+use crate::sudoku::Board;
+
+impl<const N: usize> Solver<N> {
+    /// The steps of solving `board` that the placement in the cell
+    /// `(row, column)` depends on, ending with that placement
+    ///
+    /// A sole candidate needs the steps that removed the other candidates of
+    /// its cell, a hidden single and pointing candidates the steps that removed
+    /// the value from the other cells of the unit, and a guess nothing; the
+    /// other techniques keep every step before them. Empty if the cell holds a
+    /// value already and `None` if the solver doesn't determine it.
+    pub fn explain_cell(
+        &self, board: &Board<N>, cell: (usize, usize)
+    ) -> Option<Vec<Step>> {
+        if cell.0 >= N || cell.1 >= N {
+            return None;
+        } else if board.value(cell.0 * N + cell.1) != 0 {
+            return Some(Vec::new());
+        }
+
+        let mut solved = board.clone();
+        solved.record_steps(true);
+        self.solve(&mut solved);
+        let steps = solved.steps().as_deref().unwrap_or(&[]);
+        let last = steps.iter()
+            .position(|step| step.action == Action::Place && step.cell == cell)?;
+
+        // the step that removed the candidate `v` of the cell `idx` first, at
+        // `idx * N + v - 1`
+        let mut removed_by = vec![None; N * N * N];
+        let mut replay = board.clone();
+        replay.record_steps(false);
+        let mut masks = (0..N * N).map(|idx| replay.mask(idx)).collect::<Vec<_>>();
+        for (i, step) in steps[..last].iter().enumerate() {
+            let since = replay.generation();
+            let idx = step.cell.0 * N + step.cell.1;
+            match step.action {
+                Action::Place => replay.set_idx(idx, step.value, step.reason.clone()),
+                Action::Eliminate => {
+                    replay.eliminate(idx, step.value, step.reason.clone());
+                }
+            }
+
+            for other in replay.changed_since(since) {
+                let mut gone = masks[other] & !replay.mask(other);
+                masks[other] = replay.mask(other);
+                while gone != 0 {
+                    let v = gone.trailing_zeros() as usize;
+                    gone &= gone - 1;
+                    removed_by[other * N + v].get_or_insert(i);
+                }
+            }
+        }
+
+        let mut needed = vec![false; last + 1];
+        needed[last] = true;
+        for i in (0..=last).rev() {
+            if !needed[i] {
+                continue;
+            }
+
+            let step = &steps[i];
+            let (idx, v) = (step.cell.0 * N + step.cell.1, step.value as usize - 1);
+            // the cells of the line of pointing candidates lose the value later
+            let mut require = |cell: usize, v: usize| match removed_by[cell * N + v] {
+                Some(j) if j < i => needed[j] = true,
+                _ => (),
+            };
+
+            match (step.action, &step.reason) {
+                (Action::Place, Reason::SoleCandidate) => {
+                    (0..N).filter(|&w| w != v).for_each(|w| require(idx, w))
+                }
+                (Action::Place, Reason::HiddenSingle(unit))
+                | (Action::Eliminate, Reason::Pointing(unit)) => {
+                    for other in board.unit_cells(*unit).filter(|&other| other != idx) {
+                        require(other, v);
+                    }
+                }
+                (_, Reason::Guess) => (),
+                _ => needed[..i].fill(true),
+            }
+        }
+
+        Some(steps[..=last].iter()
+            .zip(needed)
+            .filter(|(_, needed)| *needed)
+            .map(|(step, _)| step.clone())
+            .collect())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn explain_cell() {
+        let board: Board = concat!(
+            "..58..2.9.......3.......5..2..6..4.1.8...9....57....6.72..4.15.....1",
+            "..27..8..3..."
+        ).parse().unwrap();
+        let solver = Solver::default();
+
+        // every placement of an explanation holds on the steps before it
+        for idx in (0..81).filter(|&idx| board.value(idx) == 0) {
+            let steps = solver.explain_cell(&board, (idx / 9, idx % 9)).unwrap();
+            let mut replay = board.clone();
+            for step in &steps {
+                let cell = step.cell.0 * 9 + step.cell.1;
+                let bit = 1 << (step.value - 1);
+                match (step.action, &step.reason) {
+                    (Action::Place, Reason::SoleCandidate) => {
+                        assert_eq!(replay.mask(cell), bit, "{:?} {}", steps, step)
+                    }
+                    (Action::Place, Reason::HiddenSingle(unit)) => {
+                        let others = replay.unit_cells(*unit)
+                            .filter(|&other| other != cell)
+                            .any(|other| replay.mask(other) & bit != 0);
+                        assert!(!others);
+                    }
+                    _ => (),
+                }
+                replay.apply_steps(std::slice::from_ref(step)).unwrap();
+            }
+            assert_eq!(steps.last().map(|step| step.cell), Some((idx / 9, idx % 9)));
+        }
+
+        let steps = solver.explain_cell(&board, (4, 4)).unwrap();
+        assert_eq!(steps.len(), 4);
+        assert_eq!(solver.explain_cell(&board, (0, 2)), Some(Vec::new()));
+        assert_eq!(solver.explain_cell(&board, (9, 0)), None);
+    }
+}
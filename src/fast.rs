@@ -0,0 +1,317 @@
+use std::sync::atomic::{AtomicBool, Ordering};
+
+use crate::sudoku::Field;
+
+// Cells of a classic board by band of three rows: the cell `idx` is the bit
+// `idx % 27` of the word `idx / 27`
+type Cells = [u32; 3];
+
+// The cells of the first row, the first column and the first box of a band
+const ROW: u32 = 0x1ff;
+const COL: u32 = 1 | 1 << 9 | 1 << 18;
+const BOX: u32 = 7 * COL;
+
+// The rows, the columns and the boxes
+const UNITS: [Cells; 27] = units();
+// The cells sharing a unit with the cell, without it
+const PEERS: [Cells; 81] = peers();
+
+const fn units() -> [Cells; 27] {
+    let mut units = [[0; 3]; 27];
+    let mut i = 0;
+    while i < 9 {
+        units[i][i / 3] = ROW << (9 * (i % 3));
+        units[9 + i] = [COL << i; 3];
+        units[18 + i][i / 3] = BOX << (3 * (i % 3));
+        i += 1;
+    }
+
+    units
+}
+
+const fn peers() -> [Cells; 81] {
+    let mut peers = [[0; 3]; 81];
+    let mut idx = 0;
+    while idx < 81 {
+        let (row, col) = (idx / 9, idx % 9);
+        let units = [row, 9 + col, 18 + row / 3 * 3 + col / 3];
+        let mut i = 0;
+        while i < 9 {
+            peers[idx][i % 3] |= UNITS[units[i / 3]][i % 3];
+            i += 1;
+        }
+        peers[idx][idx / 27] &= !(1 << (idx % 27));
+        idx += 1;
+    }
+
+    peers
+}
+
+// A classic board as bit boards, small enough to copy it for every guess
+#[derive(Clone, Copy)]
+struct Bands {
+    // the cells without value that can take the value, by value
+    candidates: [Cells; 9],
+    // the cells holding the value, by value
+    placed: [Cells; 9],
+    unsolved: Cells,
+    values: [u8; 81],
+}
+
+impl Bands {
+    fn new(fields: impl Iterator<Item = Field>) -> Self {
+        let mut bands = Bands {
+            candidates: [[0; 3]; 9],
+            placed: [[0; 3]; 9],
+            unsolved: [0; 3],
+            values: [0; 81],
+        };
+
+        // the values get placed as sole candidates by the propagation
+        for (idx, fld) in fields.enumerate() {
+            let (band, bit) = (idx / 27, 1 << (idx % 27));
+            bands.unsolved[band] |= bit;
+            match fld {
+                Field::Value(v) => bands.candidates[v as usize - 1][band] |= bit,
+                Field::Options(opts) => {
+                    for v in opts {
+                        bands.candidates[v as usize - 1][band] |= bit;
+                    }
+                }
+            }
+        }
+
+        bands
+    }
+
+    // Places the value `val`, counted from 0, in the cell `idx`
+    fn place(&mut self, idx: usize, val: usize) {
+        let (band, bit) = (idx / 27, 1 << (idx % 27));
+        for cells in &mut self.candidates {
+            cells[band] &= !bit;
+        }
+        for (word, peers) in self.candidates[val].iter_mut().zip(&PEERS[idx]) {
+            *word &= !peers;
+        }
+
+        self.placed[val][band] |= bit;
+        self.unsolved[band] &= !bit;
+        self.values[idx] = val as u8 + 1;
+    }
+
+    // Places the sole candidates of the cells of all bands at once; false if
+    // a cell has no candidate left
+    fn sole_candidates(&mut self, progress: &mut bool) -> bool {
+        for band in 0..3 {
+            let (mut once, mut twice) = (0, 0);
+            for cells in &self.candidates {
+                twice |= once & cells[band];
+                once |= cells[band];
+            }
+
+            if self.unsolved[band] & !once != 0 {
+                return false;
+            }
+
+            let mut singles = once & !twice & self.unsolved[band];
+            while singles != 0 {
+                let bit = singles & singles.wrapping_neg();
+                singles &= singles - 1;
+
+                // an earlier single may have taken the last candidate
+                match (0..9).find(|&v| self.candidates[v][band] & bit != 0) {
+                    Some(val) => {
+                        self.place(band * 27 + bit.trailing_zeros() as usize, val)
+                    }
+                    None => return false,
+                }
+                *progress = true;
+            }
+        }
+
+        true
+    }
+
+    // Places the values with only one cell left in a unit; false if a unit
+    // has no cell left for a value
+    fn hidden_singles(&mut self, progress: &mut bool) -> bool {
+        for val in 0..9 {
+            for unit in &UNITS {
+                if (0..3).any(|b| self.placed[val][b] & unit[b] != 0) {
+                    continue;
+                }
+
+                let cells = [0, 1, 2].map(|b| self.candidates[val][b] & unit[b]);
+                match cells.iter().map(|c| c.count_ones()).sum::<u32>() {
+                    0 => return false,
+                    1 => {
+                        let band = cells.iter().position(|&c| c != 0).unwrap_or(0);
+                        let idx = band * 27 + cells[band].trailing_zeros() as usize;
+                        self.place(idx, val);
+                        *progress = true;
+                    }
+                    _ => (),
+                }
+            }
+        }
+
+        true
+    }
+
+    // Removes the candidates of a value outside a box if the box has them in
+    // one row only, and outside a row if the row has them in one box only
+    fn locked_candidates(&mut self, progress: &mut bool) {
+        for cells in &mut self.candidates {
+            for word in cells.iter_mut() {
+                let old = *word;
+                for i in 0..9 {
+                    let (row, bx) = (ROW << (9 * (i / 3)), BOX << (3 * (i % 3)));
+                    let line = *word & row & bx;
+                    if line == 0 {
+                        continue;
+                    }
+
+                    if *word & bx & !row == 0 {
+                        *word &= !(row & !bx);
+                    }
+                    if *word & row & !bx == 0 {
+                        *word &= !(bx & !row);
+                    }
+                }
+
+                *progress |= *word != old;
+            }
+        }
+    }
+
+    // Propagates the constraints until nothing changes; false on a
+    // contradiction
+    fn propagate(&mut self) -> bool {
+        loop {
+            let mut progress = false;
+            if !self.sole_candidates(&mut progress)
+                || !self.hidden_singles(&mut progress)
+            {
+                return false;
+            }
+
+            if !progress {
+                self.locked_candidates(&mut progress);
+            }
+
+            if !progress {
+                return true;
+            }
+        }
+    }
+
+    // The unsolved cell with the fewest candidates, preferably one with two
+    fn pick(&self) -> usize {
+        for band in 0..3 {
+            let (mut once, mut twice, mut more) = (0, 0, 0);
+            for cells in &self.candidates {
+                more |= twice & cells[band];
+                twice |= once & cells[band];
+                once |= cells[band];
+            }
+
+            let pairs = twice & !more & self.unsolved[band];
+            if pairs != 0 {
+                return band * 27 + pairs.trailing_zeros() as usize;
+            }
+        }
+
+        (0..81)
+            .filter(|&idx| self.unsolved[idx / 27] & 1 << (idx % 27) != 0)
+            .min_by_key(|&idx| {
+                self.candidates.iter()
+                    .filter(|cells| cells[idx / 27] & 1 << (idx % 27) != 0)
+                    .count()
+            })
+            .unwrap_or(0)
+    }
+
+    fn search(
+        mut self, limit: usize, cancel: &AtomicBool, found: &mut dyn FnMut(&[u8; 81])
+    ) -> usize {
+        if !self.propagate() {
+            return 0;
+        }
+
+        if self.unsolved == [0; 3] {
+            found(&self.values);
+            return 1;
+        }
+
+        let idx = self.pick();
+        let (band, bit) = (idx / 27, 1 << (idx % 27));
+        let mut count = 0;
+        for val in 0..9 {
+            if count >= limit || cancel.load(Ordering::Relaxed) {
+                break;
+            }
+
+            if self.candidates[val][band] & bit != 0 {
+                let mut trial = self;
+                trial.place(idx, val);
+                count += trial.search(limit - count, cancel, found);
+            }
+        }
+
+        count
+    }
+}
+
+// Searches up to `limit` solutions of the 81 fields of a board with only the
+// rows, the columns and the 3x3 boxes as constraints until `cancel` gets set
+// and passes the values of the cells to `found`
+pub(crate) fn search(
+    fields: impl ExactSizeIterator<Item = Field>, limit: usize, cancel: &AtomicBool,
+    found: &mut dyn FnMut(&[u8; 81])
+) -> usize {
+    debug_assert_eq!(fields.len(), 81);
+    Bands::new(fields).search(limit, cancel, found)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::sudoku::Board;
+
+    #[test]
+    fn tables() {
+        assert_eq!(UNITS[4], [0, ROW << 9, 0]);
+        assert_eq!(UNITS[13], [COL << 4; 3]);
+        assert_eq!(UNITS[26], [0, 0, BOX << 6]);
+        for peers in &PEERS {
+            assert_eq!(peers.iter().map(|w| w.count_ones()).sum::<u32>(), 20);
+        }
+        assert_eq!(PEERS[0][0] & 1, 0);
+    }
+
+    #[test]
+    fn solve() {
+        let board: Board = concat!(
+            "8..........36......7..9.2...5...7.......457.....1...3...",
+            "1....68..85...1..9....4.."
+        ).parse().unwrap();
+
+        let cancel = AtomicBool::new(false);
+        let mut solutions = Vec::new();
+        let count = search(board.fields(), 2, &cancel, &mut |values| {
+            solutions.push(*values)
+        });
+        assert_eq!(count, 1);
+        let text = solutions[0].iter()
+            .map(|v| char::from(b'0' + v))
+            .collect::<String>();
+        assert_eq!(text, concat!(
+            "812753649943682175675491283154237896369845721287169534",
+            "521974368438526917796318452"
+        ));
+
+        assert_eq!(search(Board::<9>::new().fields(), 5, &cancel, &mut |_| ()), 5);
+        let board: Board = format!("11{}", ".".repeat(79)).parse().unwrap();
+        assert_eq!(search(board.fields(), 1, &cancel, &mut |_| ()), 0);
+    }
+}
@@ -0,0 +1,450 @@
+use std::{
+    fmt,
+    time::{SystemTime, UNIX_EPOCH},
+};
+
+use crate::step::Reason;
+use crate::strategy::Difficulty;
+use crate::sudoku::{Board, Field};
+
+// How many puzzles `generate_rated` tries
+const GENERATE_ATTEMPTS: usize = 100;
+
+/// Pseudo random numbers by xorshift64*, good enough to shuffle candidates
+#[derive(Clone, Debug)]
+pub struct Rng(u64);
+
+impl Rng {
+    /// The numbers of `seed`, always the same ones for the same seed
+    pub fn new(seed: u64) -> Self {
+        // the state must not be zero, xorshift gives only zeros then; the one
+        // seed that would end up there takes the state of seed 0
+        const MIX: u64 = 0x9e37_79b9_7f4a_7c15;
+        match seed ^ MIX {
+            0 => Rng(MIX),
+            state => Rng(state),
+        }
+    }
+
+    /// The numbers of a seed from the current time
+    pub fn from_time() -> Self {
+        let nanos = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map_or(0, |d| d.as_nanos() as u64);
+        Self::new(nanos)
+    }
+
+    /// The next number
+    pub fn next_u64(&mut self) -> u64 {
+        self.0 ^= self.0 >> 12;
+        self.0 ^= self.0 << 25;
+        self.0 ^= self.0 >> 27;
+        self.0.wrapping_mul(0x2545_f491_4f6c_dd1d)
+    }
+
+    /// A number in the range `0..n`
+    pub fn below(&mut self, n: usize) -> usize {
+        (self.next_u64() % n as u64) as usize
+    }
+
+    /// Puts `items` in random order
+    pub fn shuffle<T>(&mut self, items: &mut [T]) {
+        for i in (1..items.len()).rev() {
+            items.swap(i, self.below(i + 1));
+        }
+    }
+}
+
+/// How the clues of a generated puzzle are arranged
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash)]
+pub enum Symmetry {
+    /// Anywhere
+    #[default]
+    None,
+    /// The same after a half turn around the center
+    Rotational,
+    /// The same after flipping the left and right half
+    Mirror,
+    /// The same after flipping at the diagonal from the top left
+    Diagonal,
+}
+
+impl Symmetry {
+    /// All kinds
+    pub const ALL: [Symmetry; 4] =
+        [Symmetry::None, Symmetry::Rotational, Symmetry::Mirror, Symmetry::Diagonal];
+
+    /// Lower case name of the kind
+    pub fn name(self) -> &'static str {
+        match self {
+            Symmetry::None => "none",
+            Symmetry::Rotational => "rotational",
+            Symmetry::Mirror => "mirror",
+            Symmetry::Diagonal => "diagonal",
+        }
+    }
+
+    /// The kind called `name`
+    pub fn from_name(name: &str) -> Option<Self> {
+        Self::ALL.iter().copied().find(|s| s.name() == name)
+    }
+
+    // The cell that must be empty if the cell `(row, col)` of a board with
+    // `size` rows is
+    fn partner(self, (row, col): (usize, usize), size: usize) -> (usize, usize) {
+        match self {
+            Symmetry::None => (row, col),
+            Symmetry::Rotational => (size - 1 - row, size - 1 - col),
+            Symmetry::Mirror => (row, size - 1 - col),
+            Symmetry::Diagonal => (col, row),
+        }
+    }
+}
+
+impl fmt::Display for Symmetry {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str(self.name())
+    }
+}
+
+/// What [`Board::generate_with_config`] generates
+#[derive(Clone, Debug, Default)]
+pub struct GeneratorConfig {
+    /// Keep at least this many clues
+    pub clues: usize,
+    /// Generate the same puzzle for the same seed
+    pub seed: Option<u64>,
+    /// Generate only puzzles that [`rate`](Board::rate) as this level
+    pub difficulty: Option<Difficulty>,
+    /// Arrangement of the clues
+    pub symmetry: Symmetry,
+}
+
+impl<const N: usize> Board<N> {
+    /// A random puzzle with a unique solution
+    ///
+    /// Clues are removed from a random solution as long as the solution stays
+    /// unique, but not below `clues`.
+    pub fn generate(clues: usize) -> Self {
+        Self::generate_with(&mut Rng::from_time(), clues)
+    }
+
+    /// Like [`generate`](Self::generate), but always the same puzzle for the
+    /// same `seed`
+    pub fn generate_seeded(seed: u64, clues: usize) -> Self {
+        Self::generate_with(&mut Rng::new(seed), clues)
+    }
+
+    /// Like [`generate`](Self::generate), but only a puzzle that
+    /// [`rate`](Self::rate)s as `level`; `seed` makes it reproducible
+    ///
+    /// Returns `None` if no such puzzle turned up after some attempts, e.g. for
+    /// `Easy` with few clues.
+    pub fn generate_rated(
+        level: Difficulty, clues: usize, seed: Option<u64>
+    ) -> Option<Self> {
+        Self::generate_with_config(&GeneratorConfig {
+            clues,
+            seed,
+            difficulty: Some(level),
+            ..GeneratorConfig::default()
+        })
+    }
+
+    /// A random puzzle as described by `config`
+    ///
+    /// Returns `None` if no puzzle of the difficulty turned up after some
+    /// attempts.
+    pub fn generate_with_config(config: &GeneratorConfig) -> Option<Self> {
+        let mut rng = config.seed.map_or_else(Rng::from_time, Rng::new);
+
+        let level = match config.difficulty {
+            Some(level) => level,
+            None => return Some(Self::generate_where(&mut rng, config, |_| true)),
+        };
+
+        // removing a clue never makes a puzzle easier, hence the removal stops
+        // at puzzles that are too hard
+        (0..GENERATE_ATTEMPTS)
+            .map(|_| {
+                Self::generate_where(&mut rng, config, |board| {
+                    board.rate().is_some_and(|rating| rating <= level)
+                })
+            })
+            .find(|board| board.rate() == Some(level))
+    }
+
+    /// A random solved board, found by guessing the values in random order
+    pub fn random_solution(rng: &mut Rng) -> Self {
+        let mut board = Self::new();
+        fill_random(&mut board, rng);
+        board
+    }
+
+    /// A minimal puzzle with the solution of this board: starting with its
+    /// values, every clue gets removed whose puzzle keeps a unique solution
+    ///
+    /// A board with several solutions starts with the values of one of them.
+    /// Returns `None` if it has no solution.
+    pub fn minimal_puzzle(&self) -> Option<Self> {
+        let board = match self.count_solutions(2) {
+            0 => return None,
+            1 => self.clone(),
+            _ => self.solutions(1).swap_remove(0),
+        };
+
+        let mut values = board.fields()
+            .map(|fld| match fld {
+                Field::Value(v) => Some(v),
+                Field::Options(_) => None,
+            })
+            .collect::<Vec<_>>();
+
+        // a clue needed now stays needed with fewer clues
+        for idx in 0..values.len() {
+            let val = values[idx].take();
+            let puzzle = self.with_clues(values.iter().copied());
+            if val.is_some() && puzzle.count_solutions(2) != 1 {
+                values[idx] = val;
+            }
+        }
+
+        Some(self.with_clues(values.into_iter()))
+    }
+
+    /// The givens that could be removed one at a time while the puzzle keeps a
+    /// unique solution; none if the solution isn't unique
+    pub fn redundant_clues(&self) -> Vec<(usize, usize)> {
+        let puzzle = self.puzzle();
+        if puzzle.count_solutions(2) != 1 {
+            return Vec::new();
+        }
+
+        let clues = puzzle.fields()
+            .map(|fld| match fld {
+                Field::Value(v) => Some(v),
+                Field::Options(_) => None,
+            })
+            .collect::<Vec<_>>();
+
+        (0..clues.len())
+            .filter(|&idx| clues[idx].is_some())
+            .filter(|&idx| {
+                let fewer = clues.iter()
+                    .enumerate()
+                    .map(|(i, &val)| if i == idx { None } else { val });
+                self.with_clues(fewer).count_solutions(2) == 1
+            })
+            .map(|idx| (idx / N, idx % N))
+            .collect()
+    }
+
+    pub(crate) fn generate_with(rng: &mut Rng, clues: usize) -> Self {
+        let config = GeneratorConfig { clues, ..GeneratorConfig::default() };
+        Self::generate_where(rng, &config, |_| true)
+    }
+
+    // Removes clues, or pairs of them for the symmetry, while the solution stays
+    // unique and `keep` accepts the puzzle
+    fn generate_where(
+        rng: &mut Rng, config: &GeneratorConfig, keep: impl Fn(&Self) -> bool
+    ) -> Self {
+        let solution = Self::random_solution(rng);
+
+        let mut values = solution.fields()
+            .map(|fld| match fld {
+                Field::Value(v) => Some(v),
+                Field::Options(_) => None,
+            })
+            .collect::<Vec<_>>();
+        let (rows, cols) = solution.box_size();
+        let puzzle = |values: &[Option<u8>]| {
+            let mut board = Self::with_boxes(rows, cols);
+            board.fill(values.iter().copied()).expect("values of a board are valid");
+            board
+        };
+
+        let size = rows * cols;
+        let mut order = (0..values.len()).collect::<Vec<_>>();
+        rng.shuffle(&mut order);
+
+        let mut count = values.len();
+        for idx in order {
+            let (row, col) = config.symmetry.partner((idx / size, idx % size), size);
+            let partner = row * size + col;
+            let cells = if partner == idx { 1 } else { 2 };
+            if values[idx].is_none() || count < config.clues + cells {
+                continue;
+            }
+
+            let val = values[idx].take();
+            let partner_val = values[partner].take();
+            let board = puzzle(&values);
+            if board.count_solutions(2) == 1 && keep(&board) {
+                count -= cells;
+            } else {
+                values[partner] = partner_val;
+                values[idx] = val;
+            }
+        }
+
+        puzzle(&values)
+    }
+}
+
+// Completes the board by guessing in random order; returns false if the board
+// has no solution
+fn fill_random<const N: usize>(board: &mut Board<N>, rng: &mut Rng) -> bool {
+    let cell = board.fields()
+        .enumerate()
+        .filter_map(|(idx, fld)| match fld {
+            Field::Options(opts) => Some((idx, opts)),
+            Field::Value(_) => None,
+        })
+        .min_by_key(|(_, opts)| opts.len())
+        .map(|(idx, opts)| (idx, opts.to_vec()));
+
+    let (idx, mut opts) = match cell {
+        Some(cell) => cell,
+        None => return board.is_valid(),
+    };
+
+    rng.shuffle(&mut opts);
+    let snapshot = board.snapshot();
+    for val in opts {
+        board.set_idx(idx, val, Reason::Guess);
+        if board.contradiction().is_none() && fill_random(board, rng) {
+            return true;
+        }
+
+        board.restore(&snapshot);
+    }
+
+    false
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn shuffle() {
+        let mut rng = Rng::new(1);
+        let mut items = (0..20).collect::<Vec<_>>();
+        rng.shuffle(&mut items);
+
+        assert_ne!(items, (0..20).collect::<Vec<_>>());
+        items.sort_unstable();
+        assert_eq!(items, (0..20).collect::<Vec<_>>());
+        assert!((0..100).all(|_| rng.below(3) < 3));
+
+        let mut rng = Rng::new(11_400_714_819_323_198_485);
+        assert_ne!((rng.next_u64(), rng.next_u64()), (0, 0));
+    }
+
+    #[test]
+    fn random_solution() {
+        let board = Board::<9>::random_solution(&mut Rng::new(5));
+        assert!(board.fields().all(|fld| fld.candidates().is_none()));
+        assert!(board.is_valid());
+
+        let other = Board::<9>::random_solution(&mut Rng::new(6));
+        assert_ne!(board.to_string(), other.to_string());
+        assert_eq!(board, Board::random_solution(&mut Rng::new(5)));
+        assert!(Board::<16>::random_solution(&mut Rng::new(5)).is_valid());
+    }
+
+    #[test]
+    fn minimal_puzzle() {
+        let solution = Board::<9>::random_solution(&mut Rng::new(3));
+        let puzzle = solution.minimal_puzzle().unwrap();
+        assert_eq!(puzzle.count_solutions(2), 1);
+        assert_eq!(puzzle.solutions(1)[0].to_string(), solution.to_string());
+
+        // no clue can go
+        let values = puzzle.fields().map(|fld| match fld {
+            Field::Value(v) => Some(v),
+            Field::Options(_) => None,
+        }).collect::<Vec<_>>();
+        for idx in (0..81).filter(|&idx| values[idx].is_some()) {
+            let mut fewer = values.clone();
+            fewer[idx] = None;
+            assert!(puzzle.with_clues(fewer.into_iter()).count_solutions(2) > 1);
+        }
+
+        // the empty board has many solutions
+        let puzzle = Board::<4>::new().minimal_puzzle().unwrap();
+        assert_eq!(puzzle.count_solutions(2), 1);
+        let mut board = Board::<4>::new();
+        board.set((0, 0), 1);
+        board.set((0, 1), 1);
+        assert_eq!(board.minimal_puzzle(), None);
+    }
+
+    #[test]
+    fn redundant_clues() {
+        let solution = Board::<9>::random_solution(&mut Rng::new(3));
+        let puzzle = solution.minimal_puzzle().unwrap();
+        assert!(puzzle.redundant_clues().is_empty());
+
+        // a value of the solution added to the minimal puzzle
+        let idx = puzzle.fields().position(|fld| fld.candidates().is_some());
+        let idx = idx.unwrap();
+        let value = match solution.field_at(idx) {
+            Field::Value(v) => v,
+            Field::Options(_) => unreachable!(),
+        };
+        let mut line = puzzle.to_string();
+        line.replace_range(idx..=idx, &value.to_string());
+        let board: Board = line.parse().unwrap();
+        assert!(board.redundant_clues().contains(&(idx / 9, idx % 9)));
+
+        assert!(Board::<4>::new().redundant_clues().is_empty());
+    }
+
+    #[test]
+    fn generate() {
+        let board = Board::<9>::generate_with(&mut Rng::new(7), 30);
+        let clues = board.fields().filter(|f| f.candidates().is_none()).count();
+
+        assert!(clues >= 30);
+        assert!(clues < 81);
+        assert!(board.is_valid());
+        assert!(board.is_given((0, 0)) || board.candidates((0, 0)).is_some());
+        assert_eq!(board.count_solutions(2), 1);
+
+        let board = Board::<4>::generate_with(&mut Rng::new(7), 0);
+        assert_eq!(board.count_solutions(2), 1);
+
+        assert_eq!(Board::<9>::generate_seeded(3, 0), Board::generate_seeded(3, 0));
+
+        let board = Board::<9>::generate_rated(Difficulty::Easy, 0, Some(7));
+        let board = board.expect("easy puzzle");
+        assert_eq!(board.rate(), Some(Difficulty::Easy));
+
+        for symmetry in Symmetry::ALL {
+            let config = GeneratorConfig {
+                seed: Some(7),
+                symmetry,
+                ..GeneratorConfig::default()
+            };
+            let board = Board::<9>::generate_with_config(&config).unwrap();
+            assert_eq!(board.count_solutions(2), 1);
+            for row in 0..9 {
+                for col in 0..9 {
+                    let partner = symmetry.partner((row, col), 9);
+                    assert_eq!(board.is_given((row, col)), board.is_given(partner));
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn generate_large() {
+        // `generate --count` tells the puzzles apart by their fingerprints
+        let fingerprints = (1..=2)
+            .map(|seed| Board::<16>::generate_seeded(seed, 180).fingerprint())
+            .collect::<Vec<_>>();
+        assert_ne!(fingerprints[0], fingerprints[1]);
+    }
+}
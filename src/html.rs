@@ -0,0 +1,149 @@
+use std::fmt::Write;
+
+use sudoku_solver::{Action, Board, Field, Hint, SolveResult, Step};
+
+const STYLE: &str = "\
+body { font-family: sans-serif; max-width: 40em; margin: auto; }
+table.grid { border-collapse: collapse; margin: 1em 0; }
+.grid td { width: 2em; height: 2em; text-align: center; border: 1px solid #aaa; }
+.grid td.given { font-weight: bold; }
+.grid td.solved { color: #1c4fa0; }
+.grid td.place { background: #ffe58a; }
+.grid td.eliminate { background: #f6c6c6; }
+.grid td.right { border-right: 2px solid #000; }
+.grid td.bottom { border-bottom: 2px solid #000; }
+.grid td.left { border-left: 2px solid #000; }
+.grid td.top { border-top: 2px solid #000; }
+";
+
+/// A standalone HTML page with the puzzle and a grid with the explanation for
+/// every step of the solved `board`
+pub fn walkthrough<const N: usize>(board: &Board<N>, result: SolveResult) -> String {
+    let puzzle = board.puzzle();
+    let mut values = puzzle.fields()
+        .map(|fld| match fld {
+            Field::Value(v) => Some(v),
+            Field::Options(_) => None,
+        })
+        .collect::<Vec<_>>();
+
+    let mut page = format!(
+        "<!DOCTYPE html>\n<html>\n<head>\n<meta charset=\"utf-8\">\n\
+         <title>Sudoku walkthrough</title>\n<style>\n{}</style>\n</head>\n<body>\n\
+         <h1>Sudoku walkthrough</h1>\n<h2>Puzzle</h2>\n{}",
+        STYLE, grid(&puzzle, &values, &[])
+    );
+
+    let steps = board.steps().as_deref().unwrap_or(&[]);
+    if !steps.is_empty() {
+        page += "<h2>Steps</h2>\n<ol>\n";
+    }
+
+    let mut done = 0;
+    for group in groups(steps) {
+        done += group.len();
+        // the snapshot of the cells after the group, if recorded, spares the
+        // replay
+        if let Some(state) = board.snapshots().and_then(|s| s.get(done - 1)) {
+            values = (0..N * N).map(|idx| state.value(idx)).collect();
+        }
+
+        let hint = match group {
+            [step] if step.action == Action::Place => {
+                values[step.cell.0 * N + step.cell.1] = Some(step.value);
+                Hint::Place(step.clone())
+            }
+            _ => Hint::Eliminate {
+                reason: group[0].reason.clone(),
+                candidates: group.iter().map(|step| (step.cell, step.value)).collect(),
+            },
+        };
+
+        let _ = write!(
+            page, "<li>\n<p>{}</p>\n{}</li>\n",
+            escape(&hint.explain()), grid(&puzzle, &values, group)
+        );
+    }
+
+    if !steps.is_empty() {
+        page += "</ol>\n";
+    }
+
+    let outcome = match result {
+        SolveResult::Solved => "The puzzle is solved.".to_string(),
+        SolveResult::Stuck => "The solver got stuck here.".to_string(),
+        SolveResult::TimeLimit => "The solver ran out of time here.".to_string(),
+        SolveResult::IterationLimit => {
+            "The solver used up its rounds here.".to_string()
+        }
+        SolveResult::Contradiction => match board.contradiction() {
+            Some(reason) => format!("The puzzle has no solution: {}", reason),
+            None => "The puzzle has no solution.".to_string(),
+        },
+    };
+
+    page + &format!("<p>{}</p>\n</body>\n</html>", escape(&outcome))
+}
+
+// Splits the steps into single placements and runs of eliminations for the
+// same reason
+fn groups(steps: &[Step]) -> Vec<&[Step]> {
+    let mut groups = Vec::new();
+    let mut start = 0;
+    for i in 1..=steps.len() {
+        let split = i == steps.len()
+            || steps[i].action == Action::Place
+            || steps[start].action == Action::Place
+            || steps[i].reason != steps[start].reason;
+        if split {
+            groups.push(&steps[start..i]);
+            start = i;
+        }
+    }
+
+    groups
+}
+
+// The values as table with the cells of `marked` highlighted
+fn grid<const N: usize>(
+    puzzle: &Board<N>, values: &[Option<u8>], marked: &[Step]
+) -> String {
+    let (rows, cols) = puzzle.box_size();
+    let mut table = String::from("<table class=\"grid\">\n");
+
+    for row in 0..N {
+        table += "<tr>";
+        for col in 0..N {
+            let mut class = Vec::new();
+            if puzzle.is_given((row, col)) {
+                class.push("given");
+            } else if values[row * N + col].is_some() {
+                class.push("solved");
+            }
+
+            if let Some(step) = marked.iter().find(|step| step.cell == (row, col)) {
+                class.push(match step.action {
+                    Action::Place => "place",
+                    Action::Eliminate => "eliminate",
+                });
+            }
+
+            class.extend([
+                (col % cols == 0, "left"),
+                (col == N - 1, "right"),
+                (row % rows == 0, "top"),
+                (row == N - 1, "bottom"),
+            ].iter().filter(|(on, _)| *on).map(|(_, name)| *name));
+
+            let val = values[row * N + col].map_or(String::new(), |v| v.to_string());
+            let _ = write!(table, "<td class=\"{}\">{}</td>", class.join(" "), val);
+        }
+        table += "</tr>\n";
+    }
+
+    table + "</table>\n"
+}
+
+fn escape(text: &str) -> String {
+    text.replace('&', "&amp;").replace('<', "&lt;").replace('>', "&gt;")
+}
@@ -0,0 +1,255 @@
+use std::collections::HashMap;
+
+use crate::constraint::{cell_names, Constraint};
+use crate::parse::ParseError;
+use crate::step::Reason;
+use crate::strategy::{Difficulty, Strategy};
+use crate::sudoku::{Board, Field};
+
+/// A cage of Killer Sudoku: its cells hold different values that add up to
+/// `sum`
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Cage {
+    pub sum: usize,
+    /// Positions `(row, column)` of the cells
+    pub cells: Vec<(usize, usize)>,
+}
+
+impl Cage {
+    /// Parses a cage from each line like `15: r1c1 r1c2 r2c1` with the sum
+    /// and the cells counted from 1; empty lines and lines starting with `#`
+    /// are skipped
+    pub fn parse_all(data: &str) -> Result<Vec<Cage>, ParseError> {
+        data.lines()
+            .enumerate()
+            .map(|(i, line)| (i, line.trim()))
+            .filter(|(_, line)| !line.is_empty() && !line.starts_with('#'))
+            .map(|(i, line)| parse_cage(line).ok_or(ParseError::InvalidCage(i + 1)))
+            .collect()
+    }
+}
+
+fn parse_cage(line: &str) -> Option<Cage> {
+    let (sum, cells) = line.split_once(':')?;
+    let cells = cells.split_whitespace().map(parse_cell).collect::<Option<Vec<_>>>()?;
+
+    if cells.is_empty() {
+        return None;
+    }
+
+    Some(Cage { sum: sum.trim().parse().ok()?, cells })
+}
+
+// A cell like `r3c5`
+pub(crate) fn parse_cell(cell: &str) -> Option<(usize, usize)> {
+    let cell = cell.to_lowercase();
+    let (row, col) = cell.strip_prefix('r')?.split_once('c')?;
+    let (row, col) = (row.parse::<usize>().ok()?, col.parse::<usize>().ok()?);
+
+    if row == 0 || col == 0 {
+        return None;
+    }
+
+    Some((row - 1, col - 1))
+}
+
+/// Removes the candidates of cage cells that are in no combination of
+/// different values adding up to the sum of the cage
+pub struct CageSums;
+
+impl<const N: usize> Strategy<N> for CageSums {
+    fn name(&self) -> &'static str {
+        "cage sums"
+    }
+
+    fn difficulty(&self) -> Difficulty {
+        Difficulty::Medium
+    }
+
+    fn cost(&self) -> u32 {
+        3
+    }
+
+    fn apply(&self, board: &mut Board<N>) -> bool {
+        self.apply_changed(board, 0)
+    }
+
+    fn apply_changed(&self, board: &mut Board<N>, since: u32) -> bool {
+        let mut changed = false;
+        for cage in board.cages().to_vec() {
+            let touched = cage.cells
+                .iter()
+                .any(|&(row, col)| board.is_changed_since(row * N + col, since));
+            if touched {
+                changed |= cage.propagate(board);
+            }
+        }
+
+        changed
+    }
+}
+
+/// The cells of a cage hold different values and the cage sums propagate it
+impl<const N: usize> Constraint<N> for Cage {
+    fn peers(&self, _board: &Board<N>, pos: (usize, usize)) -> Vec<(usize, usize)> {
+        if !self.cells.contains(&pos) {
+            return Vec::new();
+        }
+
+        self.cells.iter().copied().filter(|&other| other != pos).collect()
+    }
+
+    fn propagate(&self, board: &mut Board<N>) -> bool {
+        let candidates = self.cells
+            .iter()
+            .map(|&pos| match board.field(pos) {
+                Field::Value(v) => vec![v],
+                Field::Options(opts) => opts.to_vec(),
+            })
+            .collect::<Vec<_>>();
+        let options = Combinations::new(&candidates, self.sum).options();
+
+        let mut changed = false;
+        for (i, &(row, col)) in self.cells.iter().enumerate() {
+            for val in &candidates[i] {
+                if !options[i].contains(val) {
+                    changed |= board.eliminate(row * N + col, *val, Reason::CageSum);
+                }
+            }
+        }
+
+        changed
+    }
+
+    fn is_valid(&self, board: &Board<N>) -> bool {
+        let values = self.cells
+            .iter()
+            .filter_map(|&pos| match board.field(pos) {
+                Field::Value(v) => Some(v as usize),
+                Field::Options(_) => None,
+            })
+            .collect::<Vec<_>>();
+        let total = values.iter().sum::<usize>();
+        let distinct = values.iter().enumerate().all(|(i, v)| !values[..i].contains(v));
+
+        distinct
+            && total <= self.sum
+            && (values.len() < self.cells.len() || total == self.sum)
+    }
+
+    fn describe(&self) -> Option<String> {
+        Some(format!("cage {}: {}", self.sum, cell_names(&self.cells)))
+    }
+}
+
+// The combinations of different values of the candidates of the cells, one
+// cell after the other, that add up to the sum; a state is the set of the
+// values of the first cells as bit mask
+struct Combinations<'a> {
+    candidates: &'a [Vec<u8>],
+    sum: usize,
+    feasible: HashMap<u64, bool>,
+}
+
+impl<'a> Combinations<'a> {
+    fn new(candidates: &'a [Vec<u8>], sum: usize) -> Self {
+        Combinations { candidates, sum, feasible: HashMap::new() }
+    }
+
+    fn total(used: u64) -> usize {
+        (0..64).filter(|&v| used & (1 << v) != 0).sum()
+    }
+
+    // Whether the remaining cells can complete the values of `used`
+    fn is_feasible(&mut self, used: u64) -> bool {
+        if let Some(&known) = self.feasible.get(&used) {
+            return known;
+        }
+
+        let total = Self::total(used);
+        let ret = match self.candidates.get(used.count_ones() as usize) {
+            None => total == self.sum,
+            Some(cands) => cands.clone().into_iter().any(|val| {
+                used & (1 << val) == 0
+                    && total + val as usize <= self.sum
+                    && self.is_feasible(used | 1 << val)
+            }),
+        };
+
+        self.feasible.insert(used, ret);
+        ret
+    }
+
+    // The candidates of each cell that are part of a combination
+    fn options(mut self) -> Vec<Vec<u8>> {
+        let mut options = vec![Vec::new(); self.candidates.len()];
+        let mut states = vec![0];
+
+        let candidates = self.candidates;
+        for (i, cands) in candidates.iter().enumerate() {
+            let mut next = Vec::new();
+            for &used in &states {
+                for &val in cands {
+                    let state = used | 1 << val;
+                    if used & (1 << val) == 0 && self.is_feasible(state) {
+                        if !options[i].contains(&val) {
+                            options[i].push(val);
+                        }
+                        if !next.contains(&state) {
+                            next.push(state);
+                        }
+                    }
+                }
+            }
+            states = next;
+        }
+
+        options.iter_mut().for_each(|opts| opts.sort_unstable());
+        options
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parse() {
+        let cages = Cage::parse_all("# killer\n3: r1c1 R1C2\n\n17: r9c9 r9c8\n");
+        let cages = cages.unwrap();
+        assert_eq!(cages, vec![
+            Cage { sum: 3, cells: vec![(0, 0), (0, 1)] },
+            Cage { sum: 17, cells: vec![(8, 8), (8, 7)] },
+        ]);
+
+        assert_eq!(Cage::parse_all("3: r1c1\n4 r1c2"), Err(ParseError::InvalidCage(2)));
+        assert_eq!(Cage::parse_all("3: r0c1"), Err(ParseError::InvalidCage(1)));
+        assert_eq!(Cage::parse_all("3:"), Err(ParseError::InvalidCage(1)));
+    }
+
+    #[test]
+    fn combinations() {
+        let all = (1..=9).collect::<Vec<u8>>();
+        let options = Combinations::new(&[all.clone(), all.clone()], 3).options();
+        assert_eq!(options, vec![vec![1, 2], vec![1, 2]]);
+
+        let options = Combinations::new(&[vec![7], all.clone(), all], 10).options();
+        assert_eq!(options, vec![vec![7], vec![1, 2], vec![1, 2]]);
+
+        let options = Combinations::new(&[vec![1, 2], vec![1, 2]], 4).options();
+        assert_eq!(options, vec![Vec::<u8>::new(), Vec::new()]);
+    }
+
+    #[test]
+    fn cage_sums() {
+        let mut board = Board::<9>::new();
+        board.add_cage(Cage { sum: 17, cells: vec![(0, 0), (0, 1)] }).unwrap();
+        board.add_cage(Cage { sum: 3, cells: vec![(1, 0), (2, 0)] }).unwrap();
+
+        assert!(CageSums.apply(&mut board));
+        assert_eq!(board.candidates((0, 0)).as_deref(), Some(&[8, 9][..]));
+        assert_eq!(board.candidates((2, 0)).as_deref(), Some(&[1, 2][..]));
+        assert!(!CageSums.apply(&mut board));
+    }
+}
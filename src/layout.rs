@@ -0,0 +1,168 @@
+use std::ops::Range;
+
+use crate::sudoku::{Candidates, Field};
+#[cfg(feature = "serde")]
+use crate::sudoku::deserialize_cells;
+
+// The cells of a board split into an array of the values and one of the
+// candidates as bit masks: the scans of the techniques go through a few bytes
+// per cell and the `Field`s of the API are made of them when read
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub(crate) struct Cells<const N: usize> {
+    // the value of every cell or 0
+    values: [[u8; N]; N],
+    // the bit `v - 1` for every candidate `v` of the cell, 0 if it has a value
+    masks: [[u64; N]; N],
+}
+
+impl<const N: usize> Cells<N> {
+    // Every cell as `fld`
+    pub fn new(fld: Field) -> Self {
+        let (value, mask) = split(&fld);
+        Cells { values: [[value; N]; N], masks: [[mask; N]; N] }
+    }
+
+    // Cells made of the fields in reading order
+    #[cfg(any(test, feature = "serde"))]
+    pub fn from_fields(fields: [[Field; N]; N]) -> Self {
+        let mut cells = Cells { values: [[0; N]; N], masks: [[0; N]; N] };
+        for (idx, fld) in fields.as_flattened().iter().enumerate() {
+            cells.put(idx, fld);
+        }
+
+        cells
+    }
+
+    pub fn fields(&self) -> Fields<'_, N> {
+        Fields { cells: self, range: 0..N * N }
+    }
+
+    pub fn get(&self, idx: usize) -> Field {
+        match self.value(idx) {
+            0 => Field::Options(Candidates::from_mask(self.mask(idx))),
+            v => Field::Value(v),
+        }
+    }
+
+    // Changes the field of the cell `idx` with `change`
+    pub fn update<R>(
+        &mut self, idx: usize, change: impl FnOnce(&mut Field) -> R
+    ) -> R {
+        let mut fld = self.get(idx);
+        let ret = change(&mut fld);
+        self.put(idx, &fld);
+        ret
+    }
+
+    // Places `val` in the cell `idx`
+    pub fn place(&mut self, idx: usize, val: u8) {
+        self.values[idx / N][idx % N] = val;
+        self.masks[idx / N][idx % N] = 0;
+    }
+
+    // Removes the candidate `val` from the cell `idx`
+    pub fn remove(&mut self, idx: usize, val: u8) {
+        self.masks[idx / N][idx % N] &= !(1 << (val - 1));
+    }
+
+    // The value of the cell `idx` or 0 if it has none
+    pub fn value(&self, idx: usize) -> u8 {
+        self.values[idx / N][idx % N]
+    }
+
+    // The candidates of the cell `idx` as bit mask, 0 if it has a value
+    pub fn mask(&self, idx: usize) -> u64 {
+        self.masks[idx / N][idx % N]
+    }
+
+    fn put(&mut self, idx: usize, fld: &Field) {
+        let (value, mask) = split(fld);
+        self.values[idx / N][idx % N] = value;
+        self.masks[idx / N][idx % N] = mask;
+    }
+}
+
+/// The cells of a board row by row, see [`Board::fields`](crate::Board::fields)
+#[derive(Clone, Debug)]
+pub struct Fields<'a, const N: usize = 9> {
+    cells: &'a Cells<N>,
+    range: Range<usize>,
+}
+
+impl<const N: usize> Iterator for Fields<'_, N> {
+    type Item = Field;
+
+    fn next(&mut self) -> Option<Field> {
+        self.range.next().map(|idx| self.cells.get(idx))
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        self.range.size_hint()
+    }
+}
+
+impl<const N: usize> DoubleEndedIterator for Fields<'_, N> {
+    fn next_back(&mut self) -> Option<Field> {
+        self.range.next_back().map(|idx| self.cells.get(idx))
+    }
+}
+
+impl<const N: usize> ExactSizeIterator for Fields<'_, N> {}
+
+// The value of `fld` or 0 and its candidates as bit mask
+fn split(fld: &Field) -> (u8, u64) {
+    match fld {
+        Field::Value(v) => (*v, 0),
+        Field::Options(opts) => (0, opts.iter().fold(0, |mask, v| mask | 1 << (v - 1))),
+    }
+}
+
+#[cfg(feature = "serde")]
+impl<const N: usize> serde::Serialize for Cells<N> {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: serde::Serializer,
+    {
+        serializer.collect_seq(self.fields())
+    }
+}
+
+#[cfg(feature = "serde")]
+impl<'de, const N: usize> serde::Deserialize<'de> for Cells<N> {
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: serde::Deserializer<'de>,
+    {
+        deserialize_cells(deserializer).map(Cells::from_fields)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn update() {
+        let mut cells = Cells::<4>::new(Field::with_all_options(4));
+        assert_eq!(cells.mask(15), 0b1111);
+
+        cells.update(5, |fld| fld.remove_option(2));
+        assert_eq!(cells.mask(5), 0b1101);
+        assert_eq!(cells.value(5), 0);
+
+        assert!(cells.update(5, |fld| fld.set(3, 9)).is_ok());
+        assert_eq!((cells.value(5), cells.mask(5)), (3, 0));
+        assert_eq!(cells.get(5), Field::Value(3));
+
+        cells.remove(7, 4);
+        assert_eq!(cells.get(7), Field::Options((1..=3).collect()));
+        cells.place(7, 2);
+        assert_eq!((cells.value(7), cells.mask(7)), (2, 0));
+        assert_eq!(cells.get(6), Field::with_all_options(4));
+
+        let fields = std::array::from_fn(|row| std::array::from_fn(|col| {
+            cells.get(row * 4 + col)
+        }));
+        assert_eq!(cells, Cells::from_fields(fields));
+    }
+}
@@ -0,0 +1,75 @@
+//! Solver for sudoku puzzles
+//!
+//! The solver fills in the cells by logical techniques the way humans do. The
+//! techniques implement [`Strategy`] and a [`Solver`] applies them one after
+//! another. If logic is not sufficient, the solver can fall back to guessing,
+//! [`Board::solve_dlx`] offers an exact cover solver and [`Board::solve_fast`]
+//! a bit parallel one for classic boards.
+//!
+//! ```
+//! use sudoku_solver::{Board, SolveResult};
+//!
+//! let mut board: Board =
+//!     "..4..5.2..52.3684..16.82...2...5.4......1.73.641.....8...8....712......47.....1.9"
+//!     .parse()
+//!     .unwrap();
+//! assert_eq!(board.solve(), SolveResult::Solved);
+//! ```
+
+mod chess;
+mod constraint;
+mod diagnose;
+mod display;
+mod dlx;
+mod error;
+mod explain;
+mod fast;
+mod generate;
+mod killer;
+mod layout;
+mod links;
+mod parse;
+mod samurai;
+mod shape;
+#[cfg(feature = "serde")]
+mod spec;
+mod step;
+pub mod strategy;
+mod sudoku;
+mod symbols;
+mod tables;
+#[cfg(feature = "serde")]
+mod trace;
+mod transform;
+mod unit;
+#[cfg(feature = "wasm")]
+mod wasm;
+
+pub use chess::Move;
+pub use constraint::{Boxes, Columns, Constraint, Rows};
+pub use diagnose::{Diagnosis, UnitCandidates};
+pub use display::{Latex, Pretty, SimpleSudoku, WithSymbols};
+pub use error::Error;
+pub use generate::{GeneratorConfig, Rng, Symmetry};
+pub use killer::Cage;
+pub use layout::Fields;
+pub use links::{Link, LinkGraph};
+pub use parse::{detect_size, ParseError, Puzzles, Sdk};
+pub use samurai::{Samurai, SAMURAI_SIZE};
+pub use shape::Shape;
+#[cfg(feature = "serde")]
+pub use spec::{Spec, SpecArrow, SpecCage};
+pub use step::{Action, CellName, Event, GridState, Hint, LogEntry, Reason, Step};
+pub use strategy::{
+    CellOrder, Difficulty, Solver, SolverConfig, Stats, Strategy, TechniqueStats,
+    ValueOrder,
+};
+pub use sudoku::{
+    Board, Candidates, ConflictError, Contradiction, Field, Parity, Snapshot,
+    SolveResult,
+};
+pub use symbols::Symbols;
+#[cfg(feature = "serde")]
+pub use trace::{technique_id, Trace, TraceStep, TraceUnit, TRACE_VERSION};
+pub use transform::Transform;
+pub use unit::Unit;
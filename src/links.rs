@@ -0,0 +1,141 @@
+use std::fmt;
+
+use crate::step::CellName;
+use crate::sudoku::Board;
+
+/// The links between the candidates of a board that chains are made of, see
+/// [`Board::links`]
+///
+/// Displays as undirected graph in the DOT format of Graphviz with a node for
+/// every candidate like `r1c6 = 4`, solid edges for strong links and dashed
+/// ones for weak links.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct LinkGraph {
+    /// The candidates `((row, column), value)` with a strong link
+    pub nodes: Vec<((usize, usize), u8)>,
+    pub links: Vec<Link>,
+}
+
+/// An edge of a [`LinkGraph`]
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub struct Link {
+    /// The positions of the candidates in the nodes of the graph
+    pub nodes: (usize, usize),
+    /// Whether one of the candidates holds if the other doesn't: the only two
+    /// cells of a unit that can take the value or the two candidates of a
+    /// cell; otherwise at most one of them holds, as the candidates of one
+    /// value in cells that see each other or of one cell
+    pub strong: bool,
+}
+
+impl<const N: usize> Board<N> {
+    /// The graph of the strong links between the candidates and the weak
+    /// links between those candidates, of `value` only or of every value
+    pub fn links(&self, value: Option<u8>) -> LinkGraph {
+        let wanted = |v: usize| value.is_none_or(|value| value as usize == v + 1);
+        let mut pairs = Vec::new();
+        let mut strong = |a: (usize, usize), b: (usize, usize)| {
+            let pair = if a < b { (a, b) } else { (b, a) };
+            if !pairs.contains(&pair) {
+                pairs.push(pair);
+            }
+        };
+
+        // the cells of a unit that can take a value only twice
+        for unit in self.units() {
+            for v in (0..N).filter(|&v| wanted(v)) {
+                let mut cells = self.unit_cells(unit)
+                    .filter(|&idx| self.mask(idx) & 1 << v != 0);
+                let (a, b) = (cells.next(), cells.next());
+                if let (Some(a), Some(b), None) = (a, b, cells.next()) {
+                    strong((a, v), (b, v));
+                }
+            }
+        }
+
+        // the bivalue cells
+        if value.is_none() {
+            for idx in (0..N * N).filter(|&idx| self.mask(idx).count_ones() == 2) {
+                let mask = self.mask(idx);
+                let low = mask.trailing_zeros() as usize;
+                strong((idx, low), (idx, 63 - mask.leading_zeros() as usize));
+            }
+        }
+
+        let mut candidates = pairs.iter()
+            .flat_map(|&(a, b)| [a, b])
+            .collect::<Vec<_>>();
+        candidates.sort_unstable();
+        candidates.dedup();
+
+        let node = |c: (usize, usize)| candidates.binary_search(&c).unwrap();
+        let mut links = pairs.iter()
+            .map(|&(a, b)| Link { nodes: (node(a), node(b)), strong: true })
+            .collect::<Vec<_>>();
+        for (i, &(a, v)) in candidates.iter().enumerate() {
+            for (j, &(b, w)) in candidates.iter().enumerate().skip(i + 1) {
+                let weak = (v == w && self.sees(a, b)) || (a == b && value.is_none());
+                if weak && !pairs.contains(&((a, v), (b, w))) {
+                    links.push(Link { nodes: (i, j), strong: false });
+                }
+            }
+        }
+
+        LinkGraph {
+            nodes: candidates.iter()
+                .map(|&(idx, v)| ((idx / N, idx % N), v as u8 + 1))
+                .collect(),
+            links,
+        }
+    }
+}
+
+impl fmt::Display for LinkGraph {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        writeln!(f, "graph links {{")?;
+        writeln!(f, "    node [shape=box];")?;
+        for (i, &(cell, value)) in self.nodes.iter().enumerate() {
+            writeln!(f, "    n{} [label=\"{} = {}\"];", i, CellName(cell), value)?;
+        }
+        for link in &self.links {
+            let style = if link.strong { "" } else { " [style=dashed]" };
+            writeln!(f, "    n{} -- n{}{};", link.nodes.0, link.nodes.1, style)?;
+        }
+        f.write_str("}")
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn links() {
+        let mut board: Board<4> = Board::new();
+        board.set((0, 0), 1);
+        board.set((1, 2), 1);
+        board.set((0, 1), 2);
+
+        // 1 fits in the rows 3 and 4 of the columns 2 and 4 only
+        let graph = board.links(Some(1));
+        assert_eq!(graph.nodes, [((2, 1), 1), ((2, 3), 1), ((3, 1), 1), ((3, 3), 1)]);
+        assert_eq!(graph.links.iter().filter(|link| link.strong).count(), 4);
+        assert!(graph.links.iter().all(|link| link.strong));
+
+        let text = graph.to_string();
+        assert!(text.starts_with("graph links {\n    node [shape=box];\n"));
+        assert!(text.contains("    n0 [label=\"r3c2 = 1\"];\n"));
+        assert!(text.contains("    n0 -- n1;\n"));
+        assert!(text.ends_with("}"));
+
+        // the cell r1c3 holds 3 or 4 and r1c4 the other one
+        let graph = board.links(None);
+        let r1c3 = |v| graph.nodes.iter().position(|&n| n == ((0, 2), v)).unwrap();
+        let r1c4 = |v| graph.nodes.iter().position(|&n| n == ((0, 3), v)).unwrap();
+        let strong = |nodes| graph.links.contains(&Link { nodes, strong: true });
+        assert!(strong((r1c3(3), r1c3(4))));
+        assert!(strong((r1c3(3), r1c4(3))));
+        assert!(!graph.links.iter().any(|link| link.nodes == (r1c3(4), r1c4(3))));
+        assert_eq!(Board::<4>::new().links(None), LinkGraph::default());
+    }
+}
@@ -1,228 +1,2120 @@
 use std::{
-    io::{self, Read},
-    process,
+    cell::RefCell,
+    collections::HashSet,
+    fmt,
+    fs::{self, File},
+    io::{self, BufRead, IsTerminal, LineWriter, Read, Write},
+    iter, process, str,
+    sync::Mutex,
+    time::{Duration, Instant},
 };
 
-mod sudoku;
-use sudoku::{Board, Field};
+mod html;
+mod rpc;
+#[cfg(feature = "server")]
+mod server;
+#[cfg(feature = "tui")]
+mod tui;
+
+// Like `println!`, but writes to the file of `--output-file` if given
+macro_rules! outln {
+    () => {
+        write_output(format_args!(""))
+    };
+    ($($arg:tt)*) => {
+        write_output(format_args!($($arg)*))
+    };
+}
+
+// Calls the function for boards of the size given by `--size` or the input
+macro_rules! sized {
+    ($f:ident($args:expr)) => {
+        match size($args) {
+            4 => $f::<4>($args),
+            6 => $f::<6>($args),
+            16 => $f::<16>($args),
+            _ => $f::<9>($args),
+        }
+    };
+}
+
+use sudoku_solver::{
+    detect_size, Action, Board, Cage, Candidates, CellName, CellOrder, Difficulty,
+    Error, Event as SolveEvent, Field, GeneratorConfig, LogEntry, Move, ParseError,
+    Samurai, Shape, SolveResult, Solver, Spec, SolverConfig, Stats, Step, Symbols,
+    Symmetry, TechniqueStats, ValueOrder,
+};
 
 fn main() {
-    let args = clap::Command::new(clap::crate_name!())
+    let args = cli().get_matches();
+
+    let code = match args.subcommand() {
+        Some(("completions", args)) => {
+            let shell = *args.get_one::<clap_complete::Shell>("shell").unwrap();
+            let name = clap::crate_name!();
+            clap_complete::generate(shell, &mut cli(), name, &mut io::stdout());
+            EXIT_SUCCESS
+        }
+        Some(("man", _)) => {
+            exit_code(clap_mangen::Man::new(cli()).render(&mut io::stdout()).is_ok())
+        }
+        Some(("generate", args)) => {
+            open_output(args);
+            exit_code(sized!(generate(args)))
+        }
+        Some(("rate", args)) => sized!(rate_puzzles(args)),
+        Some(("check", args)) => exit_code(sized!(check(args))),
+        Some(("hint", args)) => sized!(hint_puzzles(args)),
+        Some(("explain", args)) => sized!(explain_puzzles(args)),
+        Some(("links", args)) => sized!(links_puzzles(args)),
+        #[cfg(feature = "tui")]
+        Some(("play", args)) => exit_code(play(args)),
+        #[cfg(feature = "server")]
+        Some(("serve", args)) => {
+            let port = *args.get_one("port").unwrap();
+            match server::serve(port, &config(args)) {
+                Ok(()) => EXIT_SUCCESS,
+                Err(err) => {
+                    eprintln!("Failed to start the server: {}", err);
+                    EXIT_FAILURE
+                }
+            }
+        }
+        Some(("daemon", args)) => match rpc::daemon(&config(args)) {
+            Ok(()) => EXIT_SUCCESS,
+            Err(err) => {
+                eprintln!("Failed to answer a request: {}", err);
+                EXIT_FAILURE
+            }
+        },
+        Some(("solve", args)) => sized!(run_solve(args)),
+        _ => sized!(run_solve(&args)),
+    };
+
+    if code != EXIT_SUCCESS {
+        process::exit(code);
+    }
+}
+
+fn cli() -> clap::Command {
+    let solve_cmd = clap::Command::new("solve")
+        .about("Solve the puzzles (default)")
+        .args(solve_args());
+
+    let cmd = clap::Command::new(clap::crate_name!())
         .version(clap::crate_version!())
         .author(clap::crate_authors!("\n"))
         .about(clap::crate_description!())
-        .arg(
-            clap::Arg::new("quiet")
-                .short('q')
-                .help("Don't print infomational messages")
-        ).arg(
-            clap::Arg::new("steps")
-                .short('s')
-                .help("Print the steps for solution")
-        ).arg(
-            clap::Arg::new("unsolved")
-                .short('u')
-                .help("Print possible values for unsolved positions")
-        ).get_matches();
-
-    let quiet = args.contains_id("quiet");
+        .args(solve_args())
+        .args_conflicts_with_subcommands(true)
+        .subcommand(solve_cmd)
+        .subcommand(
+            clap::Command::new("generate")
+                .about("Generate a puzzle with a unique solution")
+                .arg(
+                    clap::Arg::new("clues")
+                        .short('c')
+                        .long("clues")
+                        .value_name("COUNT")
+                        .value_parser(clap::value_parser!(usize))
+                        .default_value("0")
+                        .help("Keep at least COUNT values of the solution")
+                ).arg(
+                    clap::Arg::new("seed")
+                        .long("seed")
+                        .value_name("NUMBER")
+                        .value_parser(clap::value_parser!(u64))
+                        .help("Generate the same puzzle for the same NUMBER")
+                ).arg(
+                    clap::Arg::new("difficulty")
+                        .short('d')
+                        .long("difficulty")
+                        .value_name("LEVEL")
+                        .value_parser(Difficulty::ALL.map(Difficulty::name))
+                        .help("Generate only puzzles that rate as LEVEL")
+                ).arg(
+                    clap::Arg::new("symmetry")
+                        .long("symmetry")
+                        .value_name("KIND")
+                        .value_parser(Symmetry::ALL.map(Symmetry::name))
+                        .default_value("none")
+                        .help("Arrange the clues symmetrically")
+                ).arg(
+                    clap::Arg::new("minimal")
+                        .long("minimal")
+                        .action(clap::ArgAction::SetTrue)
+                        .conflicts_with_all(["clues", "difficulty", "symmetry"])
+                        .help("Remove every clue that isn't needed for a unique \
+                               solution")
+                ).arg(
+                    clap::Arg::new("book")
+                        .long("book")
+                        .value_name("COUNT")
+                        .value_parser(clap::value_parser!(usize))
+                        .conflicts_with("output")
+                        .help("Print a LaTeX document with COUNT puzzles and their \
+                               solutions")
+                ).arg(
+                    clap::Arg::new("count")
+                        .short('n')
+                        .long("count")
+                        .value_name("COUNT")
+                        .value_parser(clap::value_parser!(usize))
+                        .conflicts_with("book")
+                        .help("Generate COUNT different puzzles, also after turning \
+                               and relabeling, with their ratings in the headers \
+                               of SDK, the field difficulty of JSON and comments \
+                               of LaTeX")
+                ).arg(
+                    clap::Arg::new("ratings")
+                        .long("ratings")
+                        .value_name("FILE")
+                        .requires("count")
+                        .help("Write the rating of each puzzle of --count as line \
+                               to FILE")
+                ).arg(
+                    output_file_arg()
+                        .help("Write the puzzles to FILE instead of stdout")
+                ).arg(append_arg())
+                .arg(
+                    output_arg()
+                        .value_parser([
+                            "grid", "line", "pretty", "json", "csv", "latex", "sdm",
+                            "sdk", "ss",
+                        ])
+                        .default_value("line")
+                ).arg(size_arg())
+                .arg(symbols_arg())
+        ).subcommand(
+            clap::Command::new("rate")
+                .about("Print the difficulty of the hardest technique needed")
+                .args(variant_args())
+                .arg(jobs_arg())
+                .arg(quiet_arg())
+                .arg(file_arg())
+        ).subcommand(
+            clap::Command::new("check")
+                .about("Check that SOLUTION solves PUZZLE or that PUZZLE is solved")
+                .args(variant_args())
+                .arg(quiet_arg())
+                .arg(
+                    clap::Arg::new("puzzle")
+                        .value_name("PUZZLE")
+                        .required(true)
+                        .help("File with the puzzle, or the solution with --spec; \
+                               - is stdin")
+                ).arg(
+                    clap::Arg::new("solution")
+                        .value_name("SOLUTION")
+                        .conflicts_with("spec")
+                        .help("File with the solved board")
+                )
+        ).subcommand(
+            clap::Command::new("daemon")
+                .about("Answer JSON-RPC requests, one per line, from stdin")
+                .args(solver_args())
+        ).subcommand(
+            clap::Command::new("hint")
+                .about("Print only the next move instead of solving the puzzle")
+                .args(solver_args())
+                .mut_arg("max-difficulty", |arg| arg.short('l').visible_alias("level"))
+                .arg(output_arg().value_parser(["text", "json"]).default_value("text"))
+                .args(variant_args())
+                .arg(quiet_arg())
+                .arg(file_arg())
+        ).subcommand(
+            clap::Command::new("explain")
+                .about("Print only the steps that the value of a cell depends on")
+                .arg(
+                    clap::Arg::new("cell")
+                        .value_name("CELL")
+                        .required(true)
+                        .value_parser(clap::value_parser!(CellName))
+                        .help("The cell like r4c7 for row 4 and column 7")
+                )
+                .args(solver_args())
+                .args(variant_args())
+                .arg(quiet_arg())
+                .arg(file_arg())
+        ).subcommand(
+            clap::Command::new("links")
+                .about("Print the strong and weak links between the candidates left \
+                        by the solver as graph in the DOT format of Graphviz")
+                .arg(
+                    clap::Arg::new("value")
+                        .long("value")
+                        .value_name("VALUE")
+                        .value_parser(clap::value_parser!(u8).range(1..))
+                        .help("Only the links of the candidates VALUE")
+                )
+                .args(solver_args())
+                .args(variant_args())
+                .arg(quiet_arg())
+                .arg(file_arg())
+        );
 
-    if !quiet {
-        println!("Input initial board setting (space and newline are ignored, \
-                  non-digit charaters define empty fields)\n");
+    #[cfg(feature = "tui")]
+    let cmd = cmd.subcommand(
+        clap::Command::new("play")
+            .about("Solve the puzzle yourself in the terminal")
+            .arg(
+                clap::Arg::new("file")
+                    .value_name("FILE")
+                    .help("Read the puzzle from FILE instead of generating one")
+            )
+    );
+
+    #[cfg(feature = "server")]
+    let cmd = cmd.subcommand(
+        clap::Command::new("serve")
+            .about("Answer POST /solve, /hint, /validate, /rate or /generate")
+            .args(solver_args())
+            .arg(
+                clap::Arg::new("port")
+                    .short('p')
+                    .long("port")
+                    .value_name("PORT")
+                    .value_parser(clap::value_parser!(u16))
+                    .default_value("8080")
+                    .help("Listen on PORT")
+            )
+    );
+
+    cmd.subcommand(
+        clap::Command::new("completions")
+            .about("Print the tab completion script for SHELL")
+            .hide(true)
+            .arg(
+                clap::Arg::new("shell")
+                    .value_name("SHELL")
+                    .required(true)
+                    .value_parser(clap::value_parser!(clap_complete::Shell))
+            )
+    ).subcommand(
+        clap::Command::new("man")
+            .about("Print the man page")
+            .hide(true)
+    )
+}
+
+// The exit codes; with several puzzles the highest one wins
+const EXIT_SUCCESS: i32 = 0;
+const EXIT_FAILURE: i32 = 1;
+/// The puzzle is only partially solved
+const EXIT_STUCK: i32 = 2;
+/// The puzzle has no solution
+const EXIT_CONTRADICTION: i32 = 3;
+/// The input is no valid puzzle
+const EXIT_INVALID: i32 = 4;
+/// The solver hit `--timeout` or `--max-iterations`
+const EXIT_LIMIT: i32 = 5;
+
+static OUTPUT: Mutex<Option<LineWriter<File>>> = Mutex::new(None);
+
+thread_local! {
+    // The output of the puzzle a thread of `--jobs` works on, written in the
+    // order of the input afterwards
+    static BUFFER: RefCell<Option<String>> = const { RefCell::new(None) };
+}
+
+fn write_output(line: fmt::Arguments) {
+    let buffered = BUFFER.with(|buffer| match buffer.borrow_mut().as_mut() {
+        Some(text) => {
+            text.push_str(&line.to_string());
+            text.push('\n');
+            true
+        }
+        None => false,
+    });
+    if buffered {
+        return;
+    }
+
+    let mut file = OUTPUT.lock().unwrap_or_else(|err| err.into_inner());
+    let result = match file.as_mut() {
+        Some(file) => writeln!(file, "{}", line),
+        None => writeln!(io::stdout(), "{}", line),
+    };
+
+    if let Err(err) = result {
+        eprintln!("Failed to write the output: {}", err);
+        process::exit(EXIT_FAILURE);
+    }
+}
+
+// Sends the output to the file of `--output-file` and exits if that fails
+fn open_output(args: &clap::ArgMatches) {
+    let path = match args.get_one::<String>("output-file") {
+        Some(path) if path != "-" => path,
+        _ => return,
+    };
+
+    let file = fs::OpenOptions::new()
+        .write(true)
+        .create(true)
+        .append(args.get_flag("append"))
+        .truncate(!args.get_flag("append"))
+        .open(path);
+
+    match file {
+        Ok(file) => {
+            *OUTPUT.lock().unwrap_or_else(|err| err.into_inner()) =
+                Some(LineWriter::new(file))
+        }
+        Err(err) => {
+            eprintln!("Failed to open {}: {}", path, err);
+            process::exit(EXIT_FAILURE);
+        }
+    }
+}
+
+fn exit_code(success: bool) -> i32 {
+    if success {
+        EXIT_SUCCESS
+    } else {
+        EXIT_FAILURE
+    }
+}
+
+fn solver_args() -> Vec<clap::Arg> {
+    vec![
+        clap::Arg::new("backtracking")
+            .short('b')
+            .long("backtracking")
+            .action(clap::ArgAction::SetTrue)
+            .help("Guess values if the puzzle can't be solved by logic"),
+        clap::Arg::new("cell-order")
+            .long("cell-order")
+            .value_name("ORDER")
+            .value_parser(CellOrder::ALL.map(CellOrder::name))
+            .default_value("fewest")
+            .help("Guess the first cell or the one with the fewest candidates"),
+        clap::Arg::new("value-order")
+            .long("value-order")
+            .value_name("ORDER")
+            .value_parser(ValueOrder::ALL.map(ValueOrder::name))
+            .default_value("least-constraining")
+            .help("Guess the values ascending or that of the fewest peers first"),
+        clap::Arg::new("disable")
+            .short('x')
+            .long("disable")
+            .value_name("TECHNIQUE")
+            .action(clap::ArgAction::Append)
+            .value_parser(techniques())
+            .help("Don't use this technique; can be given multiple times"),
+        clap::Arg::new("techniques")
+            .short('t')
+            .long("techniques")
+            .value_name("LIST")
+            .value_delimiter(',')
+            .value_parser(techniques())
+            .help("Use only these comma separated techniques"),
+        clap::Arg::new("forcing-chains")
+            .short('f')
+            .long("forcing-chains")
+            .value_name("DEPTH")
+            .value_parser(clap::value_parser!(usize))
+            .help("Try forcing chains with up to DEPTH nested assumptions"),
+        clap::Arg::new("max-difficulty")
+            .long("max-difficulty")
+            .value_name("LEVEL")
+            .value_parser(Difficulty::ALL.map(Difficulty::name))
+            .help("Use only techniques up to this difficulty"),
+        clap::Arg::new("max-iterations")
+            .long("max-iterations")
+            .value_name("COUNT")
+            .value_parser(clap::value_parser!(usize))
+            .help("Give up after COUNT rounds of the techniques and guesses"),
+        clap::Arg::new("timeout")
+            .long("timeout")
+            .value_name("SECONDS")
+            .value_parser(|secs: &str| {
+                secs.parse::<f64>()
+                    .ok()
+                    .and_then(|secs| Duration::try_from_secs_f64(secs).ok())
+                    .ok_or("expected a number of seconds")
+            })
+            .help("Give up after SECONDS and print the partial solution"),
+    ]
+}
+
+// The names of the built-in techniques, also with dashes for spaces
+fn techniques() -> clap::builder::PossibleValuesParser {
+    let names = [
+        ("sole option", "sole-option"),
+        ("sole position by neighbours", "sole-position-by-neighbours"),
+        ("cage sums", "cage-sums"),
+        ("thermometers", "thermometers"),
+        ("arrow sums", "arrow-sums"),
+        ("custom constraints", "custom-constraints"),
+        ("remote pairs", "remote-pairs"),
+        ("forcing chains", "forcing-chains"),
+    ];
+
+    names.iter()
+        .map(|(name, alias)| clap::builder::PossibleValue::new(name).alias(alias))
+        .collect::<Vec<_>>()
+        .into()
+}
+
+fn solve_args() -> Vec<clap::Arg> {
+    let mut args = solver_args();
+    args.extend([
+        clap::Arg::new("count-solutions")
+            .long("count-solutions")
+            .value_name("LIMIT")
+            .num_args(0..=1)
+            .require_equals(true)
+            .default_missing_value("1000")
+            .value_parser(clap::value_parser!(usize))
+            .conflicts_with("all-solutions")
+            .help("Count the solutions, but stop at LIMIT [default: 1000]"),
+        clap::Arg::new("all-solutions")
+            .long("all-solutions")
+            .value_name("LIMIT")
+            .num_args(0..=1)
+            .require_equals(true)
+            .default_missing_value("0")
+            .value_parser(clap::value_parser!(usize))
+            .help("Print every solution, but at most LIMIT of them; 0 is no limit"),
+        clap::Arg::new("dlx")
+            .short('d')
+            .long("dlx")
+            .action(clap::ArgAction::SetTrue)
+            .help("Solve as exact cover problem with dancing links"),
+        clap::Arg::new("fast")
+            .short('F')
+            .long("fast")
+            .action(clap::ArgAction::SetTrue)
+            .conflicts_with("dlx")
+            .help("Solve classic puzzles with bit boards, without steps to follow"),
+        output_arg().default_value("grid"),
+        clap::Arg::new("labels")
+            .long("labels")
+            .action(clap::ArgAction::SetTrue)
+            .help("Number the rows and columns of the pretty grid"),
+        clap::Arg::new("marks")
+            .long("marks")
+            .action(clap::ArgAction::SetTrue)
+            .help("Show the candidates of unsolved cells in the pretty grid or \
+                   CSV with a line for each cell"),
+        clap::Arg::new("color")
+            .long("color")
+            .value_name("WHEN")
+            .value_parser(["auto", "always", "never"])
+            .default_value("auto")
+            .help("Color the givens and solved cells of grids; auto does so for \
+                   terminals"),
+        clap::Arg::new("hint")
+            .short('H')
+            .long("hint")
+            .action(clap::ArgAction::SetTrue)
+            .help("Print only the next move instead of solving the puzzle"),
+        quiet_arg(),
+        clap::Arg::new("stats")
+            .long("stats")
+            .action(clap::ArgAction::SetTrue)
+            .help("Print the time and the work of the techniques"),
+        clap::Arg::new("summary")
+            .long("summary")
+            .action(clap::ArgAction::SetTrue)
+            .help("Print a table of the placements and eliminations of each \
+                   technique and the hardest one needed"),
+        jobs_arg(),
+        output_file_arg()
+            .help("Write the solutions, steps and reports to FILE instead of stdout"),
+        append_arg(),
+        clap::Arg::new("samurai")
+            .long("samurai")
+            .action(clap::ArgAction::SetTrue)
+            .help("Solve Samurai Sudoku: five overlapping grids given as square \
+                   of 21 rows and columns or one grid after another"),
+        clap::Arg::new("stdin-lines")
+            .long("stdin-lines")
+            .action(clap::ArgAction::SetTrue)
+            .help("Read a puzzle from each line of stdin and print each solution \
+                   as line right away"),
+        clap::Arg::new("steps")
+            .short('s')
+            .action(clap::ArgAction::SetTrue)
+            .help("Print the steps for solution"),
+        clap::Arg::new("step")
+            .long("step")
+            .action(clap::ArgAction::SetTrue)
+            .conflicts_with_all([
+                "dlx", "fast", "hint", "stdin-lines", "samurai", "stats", "summary",
+            ])
+            .help("Pause after every step with the grid and the changed cell \
+                   highlighted until Enter; q skips to the result"),
+        clap::Arg::new("explain-unsolved")
+            .long("explain-unsolved")
+            .action(clap::ArgAction::SetTrue)
+            .help("Describe the cells and units left if the solver gets stuck and \
+                   the techniques that would make progress"),
+        clap::Arg::new("unsolved")
+            .short('u')
+            .action(clap::ArgAction::SetTrue)
+            .help("Print possible values for unsolved positions; --output \
+                   candidates shows them in the grid"),
+        file_arg(),
+    ]);
+    args.extend(variant_args());
+    args
+}
+
+fn output_file_arg() -> clap::Arg {
+    clap::Arg::new("output-file")
+        .short('o')
+        .long("output-file")
+        .visible_alias("out")
+        .value_name("FILE")
+}
+
+fn append_arg() -> clap::Arg {
+    clap::Arg::new("append")
+        .long("append")
+        .action(clap::ArgAction::SetTrue)
+        .requires("output-file")
+        .help("Append to the file of --output-file instead of replacing it")
+}
+
+fn output_arg() -> clap::Arg {
+    clap::Arg::new("output")
+        .long("output")
+        .visible_alias("format")
+        .value_name("FORMAT")
+        .value_parser([
+            "grid", "line", "pretty", "candidates", "json", "trace", "csv", "latex",
+            "html", "sdm", "sdk", "ss",
+        ])
+        .help("Print the result as grid, line of 81 characters, grid with \
+               box-drawing characters, such a grid with the candidates of every \
+               unsolved cell as small grid, JSON document, JSON document of every \
+               step in a versioned schema, CSV, TikZ picture, HTML page with \
+               every step, in the SDM or SDK format of SudoCue or in the format \
+               of Simple Sudoku")
+}
+
+fn size_arg() -> clap::Arg {
+    clap::Arg::new("size")
+        .long("size")
+        .value_name("N")
+        .value_parser(["4", "6", "9", "16"])
+        .help("Read and write boards with N rows and columns, e.g. 16 with the \
+               values 1 to 9 and A to G; the default is the size of the input")
+}
+
+fn symbols_arg() -> clap::Arg {
+    clap::Arg::new("symbols")
+        .long("symbols")
+        .value_name("CHARS")
+        .help("Read and write the values 1, 2, ... as these characters, e.g. \
+               ABCDEFGHI for Wordoku")
+}
+
+fn variant_arg() -> clap::Arg {
+    clap::Arg::new("variant")
+        .long("variant")
+        .value_name("NAME")
+        .value_parser(["x", "windoku", "disjoint"])
+        .value_delimiter(',')
+        .help("Apply the rules of variants: x needs every value once on both \
+               main diagonals, too, windoku in the windows between the boxes \
+               and disjoint in the cells at the same position of each box")
+}
+
+fn cages_arg() -> clap::Arg {
+    clap::Arg::new("cages")
+        .long("cages")
+        .value_name("FILE")
+        .help("Solve Killer Sudoku with the cages of FILE, one per line like \
+               `15: r1c1 r1c2 r2c1`")
+}
+
+fn regions_arg() -> clap::Arg {
+    clap::Arg::new("regions")
+        .long("regions")
+        .value_name("FILE")
+        .help("Solve Jigsaw Sudoku with the regions of FILE instead of boxes, \
+               with a letter naming the region of each cell")
+}
+
+fn constraints_arg() -> clap::Arg {
+    clap::Arg::new("constraints")
+        .long("constraints")
+        .value_name("FILE")
+        .help("Apply the thermometers and arrows of FILE, one per line like \
+               `thermo: r1c1 r1c2` from the bulb or `arrow: r5c5 r4c4` from the circle")
+}
+
+fn parity_arg() -> clap::Arg {
+    clap::Arg::new("parity")
+        .long("parity")
+        .value_name("FILE")
+        .help("Mark the cells of FILE with e as even and with o as odd, in a grid \
+               like the puzzle")
+}
+
+fn anti_knight_arg() -> clap::Arg {
+    clap::Arg::new("anti-knight")
+        .long("anti-knight")
+        .action(clap::ArgAction::SetTrue)
+        .help("Forbid the same value in cells a knight's move apart")
+}
+
+fn anti_king_arg() -> clap::Arg {
+    clap::Arg::new("anti-king")
+        .long("anti-king")
+        .action(clap::ArgAction::SetTrue)
+        .help("Forbid the same value in cells a king's move apart")
+}
+
+fn jobs_arg() -> clap::Arg {
+    clap::Arg::new("jobs")
+        .short('j')
+        .long("jobs")
+        .value_name("COUNT")
+        .value_parser(clap::value_parser!(usize))
+        .help("Work on COUNT puzzles at once; 0 is one per CPU core")
+}
+
+fn quiet_arg() -> clap::Arg {
+    clap::Arg::new("quiet")
+        .short('q')
+        .action(clap::ArgAction::SetTrue)
+        .help("Don't print infomational messages")
+}
+
+// The size, the symbols and the rules of the variants of the puzzles
+fn variant_args() -> Vec<clap::Arg> {
+    vec![
+        size_arg(),
+        symbols_arg(),
+        variant_arg(),
+        cages_arg(),
+        regions_arg(),
+        constraints_arg(),
+        parity_arg(),
+        anti_knight_arg(),
+        anti_king_arg(),
+        spec_arg(),
+    ]
+}
+
+fn spec_arg() -> clap::Arg {
+    clap::Arg::new("spec")
+        .long("spec")
+        .value_name("FILE")
+        .help("Read the puzzle and its variants from the JSON document of FILE \
+               with the fields grid, variant, cages, extra-regions, inequalities \
+               and more")
+}
+
+fn file_arg() -> clap::Arg {
+    clap::Arg::new("file")
+        .value_name("FILE")
+        .default_value("-")
+        .conflicts_with("spec")
+        .help("Read the puzzle from FILE; - is stdin")
+}
+
+fn config(args: &clap::ArgMatches) -> SolverConfig {
+    // the parser keeps the dashes of the aliases
+    let names = |id| {
+        args.get_many::<String>(id)
+            .map(|names| names.map(|name| name.replace('-', " ")).collect::<Vec<_>>())
+    };
+    let enabled = names("techniques");
+    // forcing chains selected by name need some depth
+    let chains = enabled.as_ref()
+        .is_some_and(|names| names.iter().any(|name| name == "forcing chains"));
+
+    SolverConfig {
+        forcing_chain_depth: args.get_one("forcing-chains")
+            .copied()
+            .unwrap_or(if chains { 1 } else { 0 }),
+        backtracking: args.get_flag("backtracking"),
+        cell_order: args.get_one::<String>("cell-order")
+            .and_then(|name| CellOrder::from_name(name))
+            .unwrap_or_default(),
+        value_order: args.get_one::<String>("value-order")
+            .and_then(|name| ValueOrder::from_name(name))
+            .unwrap_or_default(),
+        enabled,
+        disabled: names("disable").unwrap_or_default(),
+        max_difficulty: args.get_one::<String>("max-difficulty")
+            .and_then(|name| Difficulty::from_name(name)),
+        max_iterations: args.get_one("max-iterations").copied(),
+        timeout: args.get_one("timeout").copied(),
+    }
+}
+
+// The size of the boards by `--size` or else of the input, 9 if unknown
+fn size(args: &clap::ArgMatches) -> usize {
+    if let Ok(Some(size)) = args.try_get_one::<String>("size") {
+        return size.parse().expect("valid size");
+    }
+
+    if let Ok(Some(file)) = args.try_get_one::<String>("spec") {
+        return read_spec(args, file).size().unwrap_or(9);
+    }
+
+    // the lines of stdin get solved before the end of the input is known
+    let streaming = matches!(args.try_get_one::<bool>("stdin-lines"), Ok(Some(true)));
+    let file = ["file", "puzzle"]
+        .iter()
+        .find_map(|id| args.try_get_one::<String>(id).ok().flatten());
+
+    match file {
+        Some(file) if !streaming => {
+            let quiet = matches!(args.try_get_one::<bool>("quiet"), Ok(Some(true)));
+            let input = Input::new(file, !quiet && !machine_output(args));
+            detect_size(input.text()).unwrap_or(9)
+        }
+        _ => 9,
+    }
+}
+
+// The symbols of `--symbols`; exits if they don't fit the size
+fn symbols<const N: usize>(args: &clap::ArgMatches) -> Option<Symbols> {
+    let chars = args.try_get_one::<String>("symbols").ok().flatten()?;
+    match Symbols::new(chars, N) {
+        Ok(symbols) => Some(symbols),
+        Err(err) => {
+            eprintln!("Invalid symbols: {}", err);
+            process::exit(EXIT_INVALID);
+        }
+    }
+}
+
+// Adds the regions of `--regions`, the units of the variants of `--variant` and
+// the cages of `--cages` to `board` and exits if they are invalid
+fn apply_variant<const N: usize>(args: &clap::ArgMatches, board: &mut Board<N>) {
+    if let Ok(Some(file)) = args.try_get_one::<String>("regions") {
+        let set = Board::<N>::parse_regions(&read_input(file, false))
+            .map_err(Error::from)
+            .and_then(|regions| board.set_regions(&regions));
+        if let Err(err) = set {
+            eprintln!("Invalid regions: {}", err);
+            process::exit(EXIT_INVALID);
+        }
+    }
+
+    if let Ok(Some(file)) = args.try_get_one::<String>("parity") {
+        let set = Board::<N>::parse_parities(&read_input(file, false))
+            .map_err(Error::from)
+            .and_then(|marks| board.set_parities(&marks));
+        if let Err(err) = set {
+            eprintln!("Invalid parity marks: {}", err);
+            process::exit(EXIT_INVALID);
+        }
+    }
+
+    let variants = args.try_get_many::<String>("variant").ok().flatten();
+    for name in variants.into_iter().flatten() {
+        let units = match name.as_str() {
+            "x" => board.diagonals().collect::<Vec<_>>(),
+            "disjoint" => board.groups().collect(),
+            _ => board.windows().collect(),
+        };
+        for unit in units {
+            board.add_unit(unit);
+        }
+    }
+
+    for (name, mv) in [("anti-knight", Move::Knight), ("anti-king", Move::King)] {
+        if matches!(args.try_get_one::<bool>(name), Ok(Some(true))) {
+            board.add_move(mv);
+        }
+    }
+
+    if let Ok(Some(file)) = args.try_get_one::<String>("cages") {
+        let added = Cage::parse_all(&read_input(file, false))
+            .map_err(Error::from)
+            .and_then(|cages| {
+                cages.into_iter().try_for_each(|cage| board.add_cage(cage))
+            });
+        if let Err(err) = added {
+            eprintln!("Invalid cages: {}", err);
+            process::exit(EXIT_INVALID);
+        }
+    }
+
+    if let Ok(Some(file)) = args.try_get_one::<String>("constraints") {
+        let added = Shape::parse_all(&read_input(file, false))
+            .map_err(Error::from)
+            .and_then(|shapes| {
+                shapes.into_iter().try_for_each(|shape| board.add_shape(shape))
+            });
+        if let Err(err) = added {
+            eprintln!("Invalid constraints: {}", err);
+            process::exit(EXIT_INVALID);
+        }
+    }
+}
+
+// Reads `file`, or stdin for `-` only once, and exits if that fails
+fn read_input(file: &str, prompt: bool) -> String {
+    static STDIN: Mutex<Option<String>> = Mutex::new(None);
+
+    if file != "-" {
+        return fs::read_to_string(file).unwrap_or_else(|err| {
+            eprintln!("Failed to read {}: {}", file, err);
+            process::exit(1);
+        });
     }
 
-    let mut buffer = String::new();
-    {
-        let stdin = io::stdin();
-        let mut handle = stdin.lock();
-        if let Err(err) = handle.read_to_string(&mut buffer) {
-            eprintln!("Failed to read data: {}", err);
+    let mut stdin = STDIN.lock().unwrap_or_else(|err| err.into_inner());
+    stdin.get_or_insert_with(|| {
+        if prompt {
+            println!("Input initial board setting (space and newline are ignored, \
+                      non-digit charaters define empty fields)\n");
+        }
+
+        let mut buffer = String::new();
+        if let Err(err) = io::stdin().lock().read_to_string(&mut buffer) {
+            eprintln!("Failed to read {}: {}", file, err);
             process::exit(1);
         }
+        buffer
+    }).clone()
+}
+
+// The text of the input; a file gets mapped into memory instead of read, such
+// that huge batches don't need to fit into it
+enum Input {
+    Mapped(memmap2::Mmap),
+    Read(String),
+}
+
+impl Input {
+    // Maps `file` or reads stdin for `-` like `read_input`
+    fn new(file: &str, prompt: bool) -> Self {
+        if file == "-" {
+            return Input::Read(read_input(file, prompt));
+        }
+
+        // SAFETY: the mapping gets only read and changes of the file by other
+        // processes are as unsupported as while reading it
+        match File::open(file).and_then(|file| unsafe { memmap2::Mmap::map(&file) }) {
+            Ok(map) => Input::Mapped(map),
+            // e.g. for pipes; reading reports the error
+            Err(_) => Input::Read(read_input(file, prompt)),
+        }
+    }
+
+    // The text, or exits if it isn't UTF-8
+    fn text(&self) -> &str {
+        match self {
+            Input::Mapped(map) => str::from_utf8(map).unwrap_or_else(|err| {
+                eprintln!("Failed to read the input: {}", err);
+                process::exit(1);
+            }),
+            Input::Read(text) => text,
+        }
+    }
+}
+
+// Reads the puzzle spec of `--spec` from `file` and exits if that fails
+fn read_spec(args: &clap::ArgMatches, file: &str) -> Spec {
+    let mut spec = serde_json::from_str::<Spec>(&read_input(file, false))
+        .unwrap_or_else(|err| {
+            eprintln!("Invalid spec: {}", err);
+            process::exit(EXIT_INVALID);
+        });
+
+    if let Ok(Some(chars)) = args.try_get_one::<String>("symbols") {
+        if let Ok(symbols) = Symbols::new(chars, chars.chars().count()) {
+            spec.grid = symbols.decode(&spec.grid);
+        }
+    }
+
+    spec
+}
+
+// Reads the puzzles from `file`, written with `symbols` if given, and exits if
+// that fails
+fn read_puzzles<const N: usize>(
+    file: &str, prompt: bool, symbols: Option<&Symbols>
+) -> Vec<Result<Board<N>, ParseError>> {
+    let mut buffer = read_input(file, prompt);
+    if let Some(symbols) = symbols {
+        buffer = symbols.decode(&buffer);
+    }
+    let mut puzzles = Board::parse_all(&buffer).collect::<Vec<_>>();
+    if puzzles.is_empty() {
+        puzzles.push(buffer.parse());
+    }
+
+    puzzles
+}
+
+// Puzzles parsed from the input on demand
+type Parsed<'a, const N: usize> =
+    Box<dyn Iterator<Item = Result<Board<N>, Error>> + 'a>;
+
+// Why the invalid `board` breaks the rules, like `cage 3: r1c1 r1c2`
+fn invalid_reason<const N: usize>(board: &Board<N>) -> String {
+    let mut reasons = Vec::new();
+    if !board.conflicts().is_empty() {
+        reasons.push("a value occurs twice in a row, column or square".to_string());
+    }
+    reasons.extend(board.broken_constraints().filter_map(|rule| rule.describe()));
+    if reasons.is_empty() {
+        reasons.push("a value breaks a rule of the puzzle".to_string());
+    }
+
+    reasons.join("; ")
+}
+
+// Passes every valid puzzle of the input to `f` and returns the highest exit
+// code; with `--jobs` several puzzles at once
+fn each_puzzle<const N: usize>(
+    args: &clap::ArgMatches,
+    f: impl Fn(usize, Board<N>) -> i32 + Sync,
+) -> i32 {
+    let quiet = args.get_flag("quiet") || machine_output(args);
+    let spec = args.try_get_one::<String>("spec").ok().flatten();
+    let input = match spec {
+        Some(_) => Input::Read(String::new()),
+        None => {
+            let input = Input::new(args.get_one::<String>("file").unwrap(), !quiet);
+            match symbols::<N>(args) {
+                Some(symbols) => Input::Read(symbols.decode(input.text())),
+                None => input,
+            }
+        }
+    };
+
+    // the puzzles get parsed one after another, not all in advance
+    let text = input.text();
+    let (puzzles, batch): (Parsed<N>, bool) = match spec {
+        Some(file) => (Box::new(iter::once(read_spec(args, file).board())), false),
+        None => {
+            let mut ahead = Board::<N>::parse_all(text);
+            match (ahead.next(), ahead.next()) {
+                (None, _) => {
+                    (Box::new(iter::once(text.parse().map_err(Error::from))), false)
+                }
+                (_, second) => {
+                    let puzzles = Board::parse_all(text)
+                        .map(|puzzle| puzzle.map_err(Error::from));
+                    (Box::new(puzzles), second.is_some())
+                }
+            }
+        }
+    };
+
+    let check = |i: usize, puzzle: Result<Board<N>, Error>| {
+        let name = if batch {
+            format!("board {}", i + 1)
+        } else {
+            "board".to_string()
+        };
+
+        match puzzle.map(|mut board| {
+            apply_variant(args, &mut board);
+            board
+        }) {
+            Ok(board) if !board.is_valid() => {
+                eprintln!("Invalid {}: {}", name, invalid_reason(&board));
+                EXIT_INVALID
+            }
+            Ok(board) => f(i, board),
+            Err(err) => {
+                eprintln!("Invalid {}: {}", name, err);
+                EXIT_INVALID
+            }
+        }
+    };
+
+    match args.try_get_one::<usize>("jobs") {
+        Ok(Some(&jobs)) if jobs != 1 && batch => in_parallel(jobs, puzzles, check),
+        _ => puzzles
+            .enumerate()
+            .map(|(i, puzzle)| check(i, puzzle))
+            .fold(EXIT_SUCCESS, i32::max),
+    }
+}
+
+// Passes the items to `f` on `jobs` threads, one per CPU core for 0, writes
+// the output in the order of the items and returns the highest exit code
+fn in_parallel<T: Send>(
+    jobs: usize, items: impl Iterator<Item = T>, f: impl Fn(usize, T) -> i32 + Sync
+) -> i32 {
+    use rayon::prelude::*;
+
+    let pool = match rayon::ThreadPoolBuilder::new().num_threads(jobs).build() {
+        Ok(pool) => pool,
+        Err(err) => {
+            eprintln!("Failed to start the threads: {}", err);
+            process::exit(EXIT_FAILURE);
+        }
+    };
+
+    // the output of a chunk waits for its slowest item, not for all items
+    let chunk = pool.current_num_threads() * 64;
+    let mut items = items.enumerate().peekable();
+    let mut code = EXIT_SUCCESS;
+    while items.peek().is_some() {
+        let next = items.by_ref().take(chunk).collect::<Vec<_>>();
+        let results = pool.install(|| {
+            next.into_par_iter()
+                .map(|(i, item)| {
+                    // nested work of rayon may run on this thread in between
+                    let outer = BUFFER.with(|b| b.replace(Some(String::new())));
+                    let result = f(i, item);
+                    let text = BUFFER.with(|b| b.replace(outer)).unwrap_or_default();
+                    (text, result)
+                })
+                .collect::<Vec<_>>()
+        });
+
+        for (text, result) in results {
+            text.lines().for_each(|line| outln!("{}", line));
+            code = code.max(result);
+        }
+    }
+
+    code
+}
+
+fn run_solve<const N: usize>(args: &clap::ArgMatches) -> i32 {
+    open_output(args);
+    let config = config(args);
+    if args.get_flag("stdin-lines") {
+        return solve_lines::<N>(args, &config);
+    } else if args.get_flag("samurai") {
+        return solve_samurai(args, &config);
     }
+    each_puzzle(args, |i, board: Board<N>| {
+        if args.get_flag("hint") {
+            return exit_code(hint(args, &config, board));
+        }
 
-    let mut board = board_from_string(&buffer);
-    drop(buffer);
+        if i != 0 && !machine_output(args) {
+            outln!();
+        }
+
+        if args.contains_id("count-solutions") || args.contains_id("all-solutions") {
+            solutions(args, board)
+        } else {
+            solve(args, &config, board)
+        }
+    })
+}
 
-    if args.contains_id("steps") {
+// Solves a puzzle of each line of stdin and prints the solution or an error as
+// one line as soon as it is known
+fn solve_lines<const N: usize>(args: &clap::ArgMatches, config: &SolverConfig) -> i32 {
+    let solver = Solver::with_config(config);
+    let symbols = symbols::<N>(args);
+    let mut code = EXIT_SUCCESS;
+
+    for line in io::stdin().lock().lines() {
+        let line = match line {
+            Ok(line) => line,
+            Err(err) => {
+                eprintln!("Failed to read stdin: {}", err);
+                return code.max(EXIT_FAILURE);
+            }
+        };
+
+        let line = match &symbols {
+            Some(symbols) => symbols.decode(&line),
+            None => line,
+        };
+        let puzzle = line.trim().parse::<Board<N>>().map(|mut board| {
+            apply_variant(args, &mut board);
+            board
+        });
+        let (text, result) = match puzzle {
+            Ok(board) if !board.is_valid() => {
+                (format!("error: {}", invalid_reason(&board)), EXIT_INVALID)
+            }
+            Ok(mut board) => {
+                let result = if args.get_flag("dlx") {
+                    board.solve_dlx()
+                } else if args.get_flag("fast") {
+                    board.solve_fast()
+                } else {
+                    solver.solve(&mut board)
+                };
+
+                match result {
+                    SolveResult::Solved => {
+                        let text = board.with_symbols(symbols.as_ref()).to_string();
+                        (text, EXIT_SUCCESS)
+                    }
+                    SolveResult::Stuck => ("error: stuck".to_string(), EXIT_STUCK),
+                    SolveResult::TimeLimit => {
+                        ("error: time limit reached".to_string(), EXIT_LIMIT)
+                    }
+                    SolveResult::IterationLimit => {
+                        ("error: iteration limit reached".to_string(), EXIT_LIMIT)
+                    }
+                    SolveResult::Contradiction => {
+                        ("error: no solution".to_string(), EXIT_CONTRADICTION)
+                    }
+                }
+            }
+            Err(err) => (format!("error: {}", err), EXIT_INVALID),
+        };
+
+        code = code.max(result);
+        outln!("{}", text);
+    }
+
+    code
+}
+
+// Solves the five grids of the input jointly and prints the square
+fn solve_samurai(args: &clap::ArgMatches, config: &SolverConfig) -> i32 {
+    let quiet = args.get_flag("quiet") || machine_output(args);
+    let file = args.get_one::<String>("file").unwrap();
+    let mut samurai = match read_input(file, !quiet).parse::<Samurai>() {
+        Ok(samurai) if samurai.grids().iter().all(Board::is_valid) => samurai,
+        Ok(_) => {
+            eprintln!("Invalid board: a value occurs twice in a row, column or square");
+            return EXIT_INVALID;
+        }
+        Err(err) => {
+            eprintln!("Invalid board: {}", err);
+            return EXIT_INVALID;
+        }
+    };
+
+    let result = samurai.solve_with_config(config);
+    outln!("{}", samurai.to_string().trim_end());
+
+    match result {
+        SolveResult::Solved => EXIT_SUCCESS,
+        SolveResult::Stuck => EXIT_STUCK,
+        SolveResult::Contradiction => EXIT_CONTRADICTION,
+        SolveResult::TimeLimit | SolveResult::IterationLimit => EXIT_LIMIT,
+    }
+}
+
+fn generate<const N: usize>(args: &clap::ArgMatches) -> bool {
+    let mut config = GeneratorConfig {
+        clues: *args.get_one("clues").unwrap(),
+        seed: args.get_one("seed").copied(),
+        difficulty: args.get_one::<String>("difficulty")
+            .and_then(|name| Difficulty::from_name(name)),
+        symmetry: Symmetry::from_name(args.get_one::<String>("symmetry").unwrap())
+            .unwrap_or_default(),
+    };
+    let level = config.difficulty;
+    let generate = |config: &GeneratorConfig| {
+        let board = Board::<N>::generate_with_config(config);
+        if board.is_none() {
+            eprintln!(
+                "Found no {} puzzle with at least {} clues",
+                level.map_or("", Difficulty::name), config.clues
+            );
+        }
+        board
+    };
+
+    if let Some(&count) = args.get_one::<usize>("book") {
+        let seed = config.seed;
+        let mut puzzles = Vec::new();
+        for i in 0..count {
+            config.seed = seed.map(|seed| seed.wrapping_add(i as u64));
+            match generate(&config) {
+                Some(board) => puzzles.push(board),
+                None => return false,
+            }
+        }
+
+        outln!("{}", latex_book(&puzzles));
+        return true;
+    }
+
+    let minimal = |board: Board<N>| match args.get_flag("minimal") {
+        true => board.minimal_puzzle().expect("a generated puzzle has a solution"),
+        false => board,
+    };
+
+    if let Some(&count) = args.get_one::<usize>("count") {
+        // the seeds of the puzzles that turned out the same as others are lost
+        const DUPLICATES: usize = 1000;
+
+        let mut ratings = match args.get_one::<String>("ratings") {
+            Some(path) => match fs::File::create(path) {
+                Ok(file) => Some((path, LineWriter::new(file))),
+                Err(err) => {
+                    eprintln!("Failed to open {}: {}", path, err);
+                    return false;
+                }
+            },
+            None => None,
+        };
+
+        let seed = config.seed;
+        let mut seen = HashSet::new();
+        let (mut found, mut duplicates) = (0, 0);
+        for i in 0.. {
+            if found == count || duplicates == DUPLICATES {
+                break;
+            }
+
+            config.seed = seed.map(|seed| seed.wrapping_add(i as u64));
+            let board = match generate(&config) {
+                Some(board) => minimal(board),
+                None => return false,
+            };
+            if !seen.insert(board.fingerprint()) {
+                duplicates += 1;
+                continue;
+            }
+
+            let rating = board.rate().map_or("backtracking", Difficulty::name);
+            if let Some((path, file)) = &mut ratings {
+                if let Err(err) = writeln!(file, "{}", rating) {
+                    eprintln!("Failed to write {}: {}", path, err);
+                    return false;
+                }
+            }
+            print_generated(args, &board, Some(rating));
+            found += 1;
+            duplicates = 0;
+        }
+
+        if found < count {
+            eprintln!("Found only {} different puzzles", found);
+        }
+        return found == count;
+    }
+
+    let board = match generate(&config) {
+        Some(board) => minimal(board),
+        None => return false,
+    };
+
+    // keep the rating out of the puzzle for pipes
+    if let (Some(level), false) = (level, output(args) == "json") {
+        eprintln!("Difficulty: {}", level);
+    }
+
+    print_generated(args, &board, level.map(Difficulty::name));
+    true
+}
+
+// Prints a generated puzzle in the format of `--output` with the rating `level`
+// for JSON, LaTeX and SDK
+fn print_generated<const N: usize>(
+    args: &clap::ArgMatches, board: &Board<N>, level: Option<&'static str>
+) {
+    let symbols = symbols::<N>(args);
+    let symbols = symbols.as_ref();
+    match output(args) {
+        "json" => {
+            let mut output = json_output(args, board, board.status(), None);
+            output.difficulty = level;
+            outln!("{}", serde_json::to_string(&output).expect("serializable"));
+        }
+        "latex" => {
+            let comment = level.map(|level| format!("% {}\n", level));
+            outln!("{}{}", comment.unwrap_or_default(), board.latex());
+        }
+        "sdk" => {
+            let headers = level.map(|level| format!("#L {}\n", level));
+            outln!("{}{}", headers.unwrap_or_default(), sdk(board, symbols));
+        }
+        "csv" => outln!("{}", csv(board, symbols)),
+        "sdm" => outln!("{}", sdm(board, symbols)),
+        "ss" => outln!("{}", board.simple_sudoku().symbols(symbols)),
+        "pretty" => outln!("{}", board.pretty().symbols(symbols)),
+        "grid" => outln!("{:#}", board.with_symbols(symbols)),
+        _ => outln!("{}", board.with_symbols(symbols)),
+    }
+}
+
+#[cfg(feature = "tui")]
+fn play(args: &clap::ArgMatches) -> bool {
+    let board = match args.get_one::<String>("file") {
+        Some(file) => match read_puzzles(file, false, None).swap_remove(0) {
+            Ok(board) if board.is_valid() => board,
+            Ok(board) => {
+                eprintln!("Invalid board: {}", invalid_reason(&board));
+                return false;
+            }
+            Err(err) => {
+                eprintln!("Invalid board: {}", err);
+                return false;
+            }
+        },
+        None => Board::generate(30),
+    };
+
+    if let Err(err) = tui::play(board) {
+        eprintln!("Terminal failed: {}", err);
+        return false;
+    }
+
+    true
+}
+
+fn rate_puzzles<const N: usize>(args: &clap::ArgMatches) -> i32 {
+    each_puzzle(args, |_, board: Board<N>| exit_code(rate(board)))
+}
+
+fn rate<const N: usize>(board: Board<N>) -> bool {
+    match board.rate() {
+        Some(level) => {
+            outln!("{}", level);
+            true
+        }
+        None => {
+            outln!("not solvable by logic");
+            false
+        }
+    }
+}
+
+fn check<const N: usize>(args: &clap::ArgMatches) -> bool {
+    let quiet = args.get_flag("quiet");
+    // the solution takes on the variants of the spec
+    let spec = args.get_one::<String>("spec").map(|file| read_spec(args, file));
+    let read = |arg| {
+        let file = args.get_one::<String>(arg)?;
+        let board = match &spec {
+            Some(spec) => {
+                let mut grid = read_input(file, false);
+                if let Some(symbols) = symbols::<N>(args) {
+                    grid = symbols.decode(&grid);
+                }
+                Spec { grid, ..spec.clone() }.board()
+            }
+            None => {
+                let symbols = symbols::<N>(args);
+                let mut puzzles = read_puzzles::<N>(file, false, symbols.as_ref());
+                puzzles.swap_remove(0).map_err(Error::from)
+            }
+        };
+        match board {
+            Ok(mut board) => {
+                apply_variant(args, &mut board);
+                Some(Ok(board))
+            }
+            Err(err) => {
+                eprintln!("Invalid {}: {}", arg, err);
+                Some(Err(()))
+            }
+        }
+    };
+
+    // a filled grid alone is checked on its own
+    let (puzzle, solution) = match (&spec, read("puzzle"), read("solution")) {
+        (Some(spec), Some(Ok(solution)), None) => match spec.board() {
+            Ok(puzzle) => (Some(puzzle), solution),
+            Err(err) => {
+                eprintln!("Invalid spec: {}", err);
+                return false;
+            }
+        },
+        (None, Some(Ok(puzzle)), Some(Ok(solution))) => (Some(puzzle), solution),
+        (None, Some(Ok(solution)), None) => (None, solution),
+        _ => return false,
+    };
+
+    let conflicts = solution.conflicts();
+    let mut mistakes = Vec::new();
+    for (idx, fld) in solution.fields().enumerate() {
+        let pos = (idx / N, idx % N);
+        let given = puzzle.as_ref().map(|puzzle| puzzle.field(pos));
+        let cell = CellName(pos);
+
+        match (fld, given) {
+            (Field::Options(_), _) => mistakes.push(format!("{} is empty", cell)),
+            (Field::Value(v), Some(Field::Value(g))) if v != g => {
+                mistakes.push(format!("{} = {} but the puzzle gives {}", cell, v, g))
+            }
+            (Field::Value(v), _) if conflicts.contains(&pos) => {
+                mistakes.push(format!("{} = {} occurs twice", cell, v))
+            }
+            _ => (),
+        }
+    }
+
+    // the cages, shapes and parity marks
+    let broken = solution.broken_constraints().filter_map(|rule| rule.describe());
+    mistakes.extend(broken.map(|rule| format!("broken {}", rule)));
+    if mistakes.is_empty() && !solution.is_valid() {
+        mistakes.push("broken constraint".to_string());
+    }
+
+    if !quiet {
+        if mistakes.is_empty() {
+            outln!("The solution is correct");
+        } else {
+            outln!("The solution is wrong:");
+        }
+    }
+
+    for mistake in &mistakes {
+        outln!("  {}", mistake);
+    }
+
+    mistakes.is_empty()
+}
+
+fn hint_puzzles<const N: usize>(args: &clap::ArgMatches) -> i32 {
+    let config = config(args);
+    each_puzzle(args, |_, board: Board<N>| exit_code(hint(args, &config, board)))
+}
+
+// Prints the next move and returns false if none was found
+fn hint<const N: usize>(
+    args: &clap::ArgMatches, config: &SolverConfig, board: Board<N>
+) -> bool {
+    match Solver::with_config(config).next_hint(&board) {
+        Some(hint) if output(args) == "json" => {
+            let output = serde_json::to_string(&hint).expect("serializable");
+            outln!("{}", output);
+        }
+        Some(hint) => outln!("{}", hint.explain()),
+        None => {
+            eprintln!("No hint found");
+            return false;
+        }
+    }
+
+    true
+}
+
+fn explain_puzzles<const N: usize>(args: &clap::ArgMatches) -> i32 {
+    let config = config(args);
+    each_puzzle(args, |_, board: Board<N>| explain(args, &config, board))
+}
+
+// Prints the steps the value of the cell of `explain` depends on and returns
+// the exit code
+fn explain<const N: usize>(
+    args: &clap::ArgMatches, config: &SolverConfig, board: Board<N>
+) -> i32 {
+    let cell = *args.get_one::<CellName>("cell").unwrap();
+    let CellName(pos) = cell;
+    if pos.0 >= N || pos.1 >= N {
+        eprintln!("{}", Error::InvalidCell(pos));
+        return EXIT_INVALID;
+    }
+
+    let steps = match Solver::with_config(config).explain_cell(&board, pos) {
+        Some(steps) => steps,
+        None => {
+            eprintln!("The solver doesn't determine {}", cell);
+            return EXIT_STUCK;
+        }
+    };
+
+    if let Field::Value(v) = board.field(pos) {
+        outln!("{} = {} already", cell, v);
+        return EXIT_SUCCESS;
+    }
+
+    if !args.get_flag("quiet") {
+        let value = steps.last().map_or(0, |step| step.value);
+        outln!("{} = {} after {} steps:", cell, value, steps.len());
+    }
+    for (i, step) in steps.iter().enumerate() {
+        outln!("  {:2}. {}", i + 1, step);
+    }
+
+    EXIT_SUCCESS
+}
+
+fn links_puzzles<const N: usize>(args: &clap::ArgMatches) -> i32 {
+    let config = config(args);
+    each_puzzle(args, |_, board: Board<N>| links(args, &config, board))
+}
+
+// Prints the link graph of the board solved as far as the solver gets and
+// returns the exit code
+fn links<const N: usize>(
+    args: &clap::ArgMatches, config: &SolverConfig, mut board: Board<N>
+) -> i32 {
+    let value = args.get_one::<u8>("value").copied();
+    if let Some(value) = value.filter(|&value| value as usize > N) {
+        eprintln!("{}", Error::InvalidValue(value));
+        return EXIT_INVALID;
+    }
+
+    board.solve_with_config(config);
+    outln!("{}", board.links(value));
+    EXIT_SUCCESS
+}
+
+fn output(args: &clap::ArgMatches) -> &str {
+    args.try_get_one::<String>("output")
+        .ok()
+        .flatten()
+        .map_or("grid", String::as_str)
+}
+
+// Whether the output is meant for other programs and hence must not contain
+// informational messages
+fn machine_output(args: &clap::ArgMatches) -> bool {
+    matches!(
+        output(args),
+        "line" | "json" | "trace" | "csv" | "latex" | "html" | "sdm" | "sdk" | "ss"
+    )
+}
+
+/// The result of solving a puzzle for `--output json`
+#[derive(serde::Serialize)]
+struct JsonOutput<'a> {
+    status: SolveResult,
+    /// The cells as line with `.` for unsolved ones
+    grid: String,
+    /// The candidates of the unsolved cells
+    candidates: Vec<JsonCandidates>,
+    contradiction: Option<String>,
+    steps: &'a [Step],
+    /// The steps with the wrong guesses of backtracking
+    #[serde(skip_serializing_if = "Option::is_none")]
+    log: Option<&'a [LogEntry]>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    stats: Option<JsonStats<'a>>,
+    /// The rating of `generate --difficulty`
+    #[serde(skip_serializing_if = "Option::is_none")]
+    difficulty: Option<&'static str>,
+}
+
+/// The numbers of `--stats`
+#[derive(serde::Serialize)]
+struct JsonStats<'a> {
+    seconds: f64,
+    #[serde(flatten)]
+    solver: Option<&'a Stats>,
+}
+
+#[derive(serde::Serialize)]
+struct JsonCandidates {
+    cell: (usize, usize),
+    values: Candidates,
+}
+
+fn indent(text: &str) -> String {
+    text.lines()
+        .map(|line| if line.is_empty() { String::new() } else { format!("  {}", line) })
+        .collect::<Vec<_>>()
+        .join("\n")
+}
+
+// A LaTeX document with the puzzles, two in a row, and a page with their
+// solutions
+fn latex_book<const N: usize>(puzzles: &[Board<N>]) -> String {
+    let mut doc = String::from(
+        "\\documentclass{article}\n\\usepackage{tikz}\n\\begin{document}\n"
+    );
+
+    let solutions = puzzles.iter()
+        .map(|puzzle| puzzle.solutions(1).swap_remove(0))
+        .collect::<Vec<_>>();
+    for (title, boards) in [("Puzzles", puzzles), ("Solutions", &solutions)] {
+        doc += &format!("\\section*{{{}}}\n", title);
+        for (i, board) in boards.iter().enumerate() {
+            doc += &format!(
+                "\\begin{{minipage}}{{0.48\\textwidth}}\n\\centering\n\
+                 \\textbf{{{}}}\\par\\smallskip\n{}\n\\end{{minipage}}\n",
+                i + 1, board.latex()
+            );
+            doc += if i % 2 == 0 { "\\hfill\n" } else { "\\par\\bigskip\n" };
+        }
+        doc += "\\newpage\n";
+    }
+
+    doc + "\\end{document}"
+}
+
+// The line with 0 for unsolved cells as in the SDM collections of SudoCue
+fn sdm<const N: usize>(board: &Board<N>, symbols: Option<&Symbols>) -> String {
+    board.with_symbols(symbols).to_string().replace('.', "0")
+}
+
+// The grid without headers of the SDK format
+fn sdk<const N: usize>(board: &Board<N>, symbols: Option<&Symbols>) -> String {
+    let line = board.with_symbols(symbols).to_string().chars().collect::<Vec<_>>();
+    line.chunks(N)
+        .map(|row| row.iter().collect::<String>())
+        .collect::<Vec<_>>()
+        .join("\n")
+}
+
+// The value `v` as number or as symbol of `symbols`
+fn value_text(v: u8, symbols: Option<&Symbols>) -> String {
+    match symbols {
+        Some(symbols) => symbols.symbol(v).to_string(),
+        None => v.to_string(),
+    }
+}
+
+// A line for each row with the values separated by commas and nothing for
+// unsolved cells
+fn csv<const N: usize>(board: &Board<N>, symbols: Option<&Symbols>) -> String {
+    let fields = board.fields().collect::<Vec<_>>();
+    fields.chunks(N)
+        .map(|row| {
+            row.iter()
+                .map(|fld| match fld {
+                    Field::Value(v) => value_text(*v, symbols),
+                    Field::Options(_) => String::new(),
+                })
+                .collect::<Vec<_>>()
+                .join(",")
+        })
+        .collect::<Vec<_>>()
+        .join("\n")
+}
+
+// A line for each cell with its position, value and candidates, e.g.
+// `1,3,,147`, for spreadsheets
+fn csv_candidates<const N: usize>(
+    board: &Board<N>, symbols: Option<&Symbols>
+) -> String {
+    let mut text = String::from("row,column,value,candidates");
+    for (i, fld) in board.fields().enumerate() {
+        let (value, candidates) = match fld {
+            Field::Value(v) => (value_text(v, symbols), String::new()),
+            Field::Options(opts) => {
+                let candidates = opts.iter().map(|&v| value_text(v, symbols));
+                (String::new(), candidates.collect())
+            }
+        };
+        text += &format!("\n{},{},{},{}", i / N + 1, i % N + 1, value, candidates);
+    }
+
+    text
+}
+
+// Whether to color the output by `--color`
+fn color(args: &clap::ArgMatches) -> bool {
+    match args.get_one::<String>("color").map(String::as_str) {
+        Some("always") => true,
+        Some("never") => false,
+        _ => io::stdout().is_terminal(),
+    }
+}
+
+// Sets the givens in bold, the solved cells in blue and dims the empty ones;
+// `text` must show the cells in order as the only alphanumeric characters or
+// dots
+fn colorize<const N: usize>(
+    args: &clap::ArgMatches, board: &Board<N>, text: String
+) -> String {
+    if !color(args) {
+        return text;
+    }
+
+    let mut cells = board.fields().enumerate();
+    let mut ret = String::with_capacity(text.len() * 4);
+    for c in text.chars() {
+        let cell = if c.is_alphanumeric() || c == '.' { cells.next() } else { None };
+        let style = match cell {
+            Some((_, Field::Options(_))) => "2",
+            Some((idx, _)) if board.is_given((idx / N, idx % N)) => "1",
+            Some(_) => "34",
+            None => {
+                ret.push(c);
+                continue;
+            }
+        };
+
+        ret.push_str(&format!("\x1b[{}m{}\x1b[0m", style, c));
+    }
+
+    ret
+}
+
+fn print_json<const N: usize>(
+    args: &clap::ArgMatches, board: &Board<N>, status: SolveResult,
+    stats: Option<JsonStats>
+) {
+    let output = json_output(args, board, status, stats);
+    outln!("{}", serde_json::to_string(&output).expect("serializable"));
+}
+
+fn json_output<'a, const N: usize>(
+    args: &clap::ArgMatches, board: &'a Board<N>, status: SolveResult,
+    stats: Option<JsonStats<'a>>
+) -> JsonOutput<'a> {
+    JsonOutput {
+        status,
+        grid: board.with_symbols(symbols::<N>(args).as_ref()).to_string(),
+        candidates: board.fields()
+            .enumerate()
+            .filter_map(|(i, fld)| match fld {
+                Field::Options(values) => {
+                    Some(JsonCandidates { cell: (i / N, i % N), values })
+                }
+                Field::Value(_) => None,
+            })
+            .collect(),
+        contradiction: board.contradiction().map(|c| c.to_string()),
+        steps: board.steps().as_deref().unwrap_or(&[]),
+        log: board.log(),
+        stats,
+        difficulty: None,
+    }
+}
+
+// The board in the text format selected by `--output`
+fn board_text<const N: usize>(args: &clap::ArgMatches, board: &Board<N>) -> String {
+    let symbols = symbols::<N>(args);
+    let symbols = symbols.as_ref();
+    match output(args) {
+        "line" => board.with_symbols(symbols).to_string(),
+        "csv" if args.get_flag("marks") => csv_candidates(board, symbols),
+        "csv" => csv(board, symbols),
+        "latex" => board.latex().to_string(),
+        "sdm" => sdm(board, symbols),
+        "ss" => board.simple_sudoku().symbols(symbols).to_string(),
+        "sdk" => sdk(board, symbols),
+        "candidates" => {
+            let pretty = board.pretty()
+                .labels(args.get_flag("labels"))
+                .candidates(true)
+                .symbols(symbols);
+            indent(&pretty.to_string())
+        }
+        "pretty" if args.get_flag("labels") || args.get_flag("marks") => {
+            let pretty = board.pretty()
+                .labels(args.get_flag("labels"))
+                .candidates(args.get_flag("marks"))
+                .symbols(symbols);
+            indent(&pretty.to_string())
+        }
+        "pretty" => {
+            let pretty = board.pretty().symbols(symbols).to_string();
+            indent(&colorize(args, board, pretty))
+        }
+        _ => {
+            let grid = format!("{:#}", board.with_symbols(symbols));
+            indent(&colorize(args, board, grid))
+        }
+    }
+}
+
+// Counts or prints the solutions for `--count-solutions` and `--all-solutions`
+fn solutions<const N: usize>(args: &clap::ArgMatches, board: Board<N>) -> i32 {
+    let quiet = args.get_flag("quiet") || machine_output(args);
+
+    let solved = if let Some(&limit) = args.get_one::<usize>("count-solutions") {
+        let count = match args.try_get_one::<usize>("jobs") {
+            Ok(Some(&jobs)) if jobs != 1 => board.count_solutions_parallel(limit),
+            _ => board.count_solutions(limit),
+        };
+        match count {
+            _ if quiet => outln!("{}", count),
+            1 => outln!("The puzzle has 1 solution"),
+            _ if count == limit => {
+                outln!("The puzzle has at least {} solutions", count)
+            }
+            _ => outln!("The puzzle has {} solutions", count),
+        }
+        count > 0
+    } else {
+        let limit = args.get_one::<usize>("all-solutions").copied().unwrap_or(0);
+        let solutions = board.solutions(if limit == 0 { usize::MAX } else { limit });
+        for (i, solution) in solutions.iter().enumerate() {
+            if output(args) == "json" {
+                print_json(args, solution, SolveResult::Solved, None);
+                continue;
+            }
+
+            if !quiet {
+                outln!("\nSolution {}:\n", i + 1);
+            }
+            outln!("{}", board_text(args, solution));
+        }
+
+        if solutions.is_empty() && !quiet {
+            outln!("The puzzle has no solution");
+        }
+        !solutions.is_empty()
+    };
+
+    if solved {
+        EXIT_SUCCESS
+    } else {
+        EXIT_CONTRADICTION
+    }
+}
+
+// What `--step` shows: a step of the solver or the board reverted after a
+// wrong guess
+enum Frame<const N: usize> {
+    Step(Step),
+    Restore(Box<Board<N>>),
+}
+
+// Replays the frames on `board` for `--step` and prints the grid with the
+// changed cell highlighted after each step until Enter; `q` ends it
+fn step_through<const N: usize>(
+    args: &clap::ArgMatches, mut board: Board<N>, frames: Vec<Frame<N>>
+) {
+    // the answers come from the terminal if the puzzle came from stdin
+    let file = args.get_one::<String>("file").unwrap();
+    let mut answers: Box<dyn BufRead> = if file == "-" {
+        match File::open("/dev/tty") {
+            Ok(tty) => Box::new(io::BufReader::new(tty)),
+            Err(err) => {
+                eprintln!("Failed to open the terminal for --step: {}", err);
+                return;
+            }
+        }
+    } else {
+        Box::new(io::stdin().lock())
+    };
+
+    board.record_steps(false);
+    let mut count = 0;
+    for frame in frames {
+        let changed = match frame {
+            Frame::Step(step) => {
+                count += 1;
+                println!("\nStep {}: {}\n", count, step);
+
+                let idx = step.cell.0 * N + step.cell.1;
+                match step.action {
+                    Action::Place => board.set_idx(idx, step.value, step.reason),
+                    Action::Eliminate => {
+                        board.eliminate(idx, step.value, step.reason);
+                    }
+                }
+                Some(idx)
+            }
+            Frame::Restore(restored) => {
+                board = *restored;
+                board.record_steps(false);
+                println!("\nThe guess was wrong, back to:\n");
+                None
+            }
+        };
+
+        let cells = changed.filter(|_| color(args)).into_iter().collect::<Vec<_>>();
+        let grid = board.pretty().candidates(true).highlight(&cells).to_string();
+        println!("{}", indent(&grid));
+        print!("\nEnter for the next step, q to skip to the result: ");
+        let _ = io::stdout().flush();
+
+        let mut answer = String::new();
+        let read = answers.read_line(&mut answer).unwrap_or(0);
+        if read == 0 || answer.trim() == "q" {
+            break;
+        }
+    }
+}
+
+// Solves and prints one puzzle and returns the exit code for the result
+fn solve<const N: usize>(
+    args: &clap::ArgMatches, config: &SolverConfig, mut board: Board<N>
+) -> i32 {
+    let json = output(args) == "json";
+    let quiet = args.get_flag("quiet") || machine_output(args);
+
+    let html = output(args) == "html";
+    let trace = output(args) == "trace";
+    let walk = args.get_flag("step");
+    if html {
+        board.record_snapshots(true);
+    } else if args.get_flag("steps") || json || trace {
         board.record_steps(true);
     }
+    // the steps of wrong guesses vanish from the steps but not from the log
+    if config.backtracking && (args.get_flag("steps") || json) {
+        board.record_log(true);
+    }
 
-    board.solve();
+    let before = walk.then(|| board.clone());
+    let mut frames = Vec::new();
+    let start = Instant::now();
+    let (result, stats) = if args.get_flag("dlx") {
+        (board.solve_dlx(), None)
+    } else if args.get_flag("fast") {
+        (board.solve_fast(), None)
+    } else if args.get_flag("stats") || args.get_flag("summary") {
+        let (result, stats) = Solver::with_config(config).solve_with_stats(&mut board);
+        (result, Some(stats))
+    } else if walk {
+        let solver = Solver::with_config(config);
+        let result = solver.solve_with(&mut board, |event| match event {
+            SolveEvent::Step(step) => frames.push(Frame::Step(step.clone())),
+            SolveEvent::Restore(board) => {
+                frames.push(Frame::Restore(Box::new(board.clone())))
+            }
+        });
+        (result, None)
+    } else {
+        (board.solve_with_config(config), None)
+    };
+    let elapsed = start.elapsed();
 
-    let mut unsolved = if args.contains_id("unsolved") {
+    if let Some(before) = before {
+        step_through(args, before, frames);
+    }
+
+    let code = match result {
+        SolveResult::Solved => EXIT_SUCCESS,
+        SolveResult::Stuck => EXIT_STUCK,
+        SolveResult::Contradiction => EXIT_CONTRADICTION,
+        SolveResult::TimeLimit | SolveResult::IterationLimit => EXIT_LIMIT,
+    };
+
+    if json {
+        let stats = Some(JsonStats {
+            seconds: elapsed.as_secs_f64(),
+            solver: stats.as_ref(),
+        });
+        print_json(args, &board, result, stats.filter(|_| args.get_flag("stats")));
+        return code;
+    }
+
+    if html {
+        outln!("{}", html::walkthrough(&board, result));
+        return code;
+    } else if trace {
+        outln!("{}", serde_json::to_string(&board.trace()).expect("serializable"));
+        return code;
+    }
+
+    let mut unsolved = if args.get_flag("unsolved") {
         Some(Vec::new())
     } else {
         None
     };
 
     if !quiet {
-        println!("\nSolution:\n");
-    }
-    let mut first = true;
-    print!("  ");
-    for (i, e) in board.fields().iter().enumerate() {
-        if first {
-            first = false;
-        } else if i % 27 == 0 {
-            print!("\n\n  ");
-        } else if i % 9 == 0 {
-            print!("\n  ");
-        } else if i % 3 == 0 {
-            print!("   ");
-        }
-
-        match e {
-            Field::Options(opts) => {
-                print!(".");
-                if let Some(ref mut u) = unsolved {
-                    u.push((i, opts));
+        match result {
+            SolveResult::Solved => outln!("\nSolution:\n"),
+            SolveResult::Stuck => outln!("\nStuck, partial solution:\n"),
+            SolveResult::TimeLimit => {
+                outln!("\nTime limit reached, partial solution:\n")
+            }
+            SolveResult::IterationLimit => {
+                outln!("\nIteration limit reached, partial solution:\n")
+            }
+            SolveResult::Contradiction => {
+                outln!("\nThe puzzle has no solution, last state:\n");
+                if let Some(contradiction) = board.contradiction() {
+                    outln!("  {}\n", contradiction);
                 }
             }
-            Field::Value(v) => print!("{}", v),
         }
     }
-    println!();
+
+    outln!("{}", board_text(args, &board));
+
+    if let Some(ref mut u) = unsolved {
+        for (i, e) in board.fields().enumerate() {
+            if let Field::Options(opts) = e {
+                u.push((i, opts));
+            }
+        }
+    }
 
     if let Some(uns) = unsolved {
         if !uns.is_empty() {
             if !quiet {
-                println!("\nUnsolved:");
+                outln!("\nUnsolved:");
             }
 
             for (idx, val) in uns {
-                println!("  ({}, {}) = {:?}", (idx / 9) + 1, (idx % 9) + 1, val);
+                outln!("  {} = {:?}", CellName((idx / N, idx % N)), val);
             }
         }
     }
 
-    if let Some(steps) = board.steps() {
+    if result == SolveResult::Stuck && args.get_flag("explain-unsolved") {
         if !quiet {
-            println!("\nSteps:");
+            outln!("\nWhy unsolved:");
         }
+        outln!("{}", indent(&board.diagnose().to_string()));
+    }
 
-        for (i, (idx, val, reason)) in steps.iter().enumerate() {
-            println!("  {:2}. ({}, {}) = {} ({})",
-                     i + 1, (idx / 9) + 1, (idx % 9) + 1, val, reason
-            );
+    if let Some(log) = board.log() {
+        if !quiet {
+            outln!("\nSteps:");
+        }
+
+        // indented by the open guesses
+        let mut open = 0;
+        for (i, entry) in log.iter().enumerate() {
+            let level = match *entry {
+                LogEntry::Step(_) | LogEntry::Contradiction { .. } => open,
+                LogEntry::Guess { depth, .. } => {
+                    open = depth;
+                    depth - 1
+                }
+                LogEntry::Backtrack { depth } => {
+                    open = depth - 1;
+                    open
+                }
+            };
+            outln!("  {:2}. {}{}", i + 1, "  ".repeat(level), entry);
+        }
+    } else if let Some(steps) = board.steps() {
+        if !quiet {
+            outln!("\nSteps:");
+        }
+
+        for (i, step) in steps.iter().enumerate() {
+            outln!("  {:2}. {}", i + 1, step);
         }
     }
-}
 
-fn board_from_string(data: &str) -> Board {
-    let mut board = Board::new();
+    if args.get_flag("stats") {
+        if !quiet {
+            outln!("\nStatistics:");
+        }
 
-    board.fill(data.chars().filter(|c| !c.is_whitespace()).map(|c| {
-        c.to_digit(10).and_then(|x| {
-            if (1..=9).contains(&x) {
-                Some(x as u8)
-            } else {
-                None
+        outln!("  time: {:.3} ms", elapsed.as_secs_f64() * 1000.0);
+        if let Some(stats) = &stats {
+            outln!("  rounds: {}", stats.rounds);
+            outln!("  peak candidates: {}", stats.peak_candidates);
+            for technique in &stats.techniques {
+                outln!(
+                    "  {}: {} placements, {} eliminations, {} uses in {:.3} ms",
+                    technique.name, technique.placements, technique.eliminations,
+                    technique.uses, technique.time.as_secs_f64() * 1000.0
+                );
             }
-        })
-    }));
-
-    board
-}
-
-#[cfg(test)]
-mod tests {
-    use super::*;
-    use sudoku::Field;
-
-    fn to_string(board: &Board) -> String {
-        board.fields().into_iter().map(|x| match x {
-            Field::Options(_) => '_',
-            Field::Value(v) => (v + 0x30).into(),
-        }).collect()
-    }
-
-    const TEST_BOARD : &str =
-        "92_______\
-         5__87____\
-         _38_91___\
-         _5293_16_\
-         _9_____3_\
-         _73_6498_\
-         ___41_25_\
-         ____53__1\
-         _______73";
-
-    #[test]
-    fn fill_board_simple() {
-        let board = board_from_string(TEST_BOARD);
-
-        assert_eq!(to_string(&board), TEST_BOARD);
-    }
-
-    #[test]
-    fn fill_board_placeholder_x() {
-        let board = board_from_string(
-            "92xxxxxxx\
-             5xx87xxxx\
-             x38x91xxx\
-             x5293x16x\
-             x9xxxxx3x\
-             x73x6498x\
-             xxx41x25x\
-             xxxx53xx1\
-             xxxxxxx73"
-        );
+        }
+    }
 
-        assert_eq!(to_string(&board), TEST_BOARD);
-    }
-
-    #[test]
-    fn fill_board_newline() {
-        let board = board_from_string(
-            "92_______\n\
-             5__87____\n\
-             _38_91___\n\
-             _5293_16_\n\
-             _9_____3_\n\
-             _73_6498_\n\
-             ___41_25_\n\
-             ____53__1\n\
-             _______73\n"
-        );
+    if let Some(stats) = stats.filter(|_| args.get_flag("summary")) {
+        if !quiet {
+            outln!("\nSummary:");
+        }
+        print_summary(&stats);
+    }
 
-        assert_eq!(to_string(&board), TEST_BOARD);
-    }
-
-    #[test]
-    fn fill_board_spaces() {
-        let board = board_from_string(
-            "92_ ___\t___
-             5__ 87_\t___
-             _38 _91\t___
-             _52 93_\t16_
-             _9_ ___\t_3_
-             _73 _64\t98_
-             ___ 41_\t25_
-             ___ _53\t__1
-             ___ ___\t_73"
-        );
+    code
+}
 
-        assert_eq!(to_string(&board), TEST_BOARD);
-    }
-
-    #[test]
-    fn fill_board_spaces_newline() {
-        let board = board_from_string(
-            "92_ ___\t___\n
-             5__ 87_\t___\n
-             _38 _91\t___\n
-             \n
-             _52 93_\t16_\n
-             _9_ ___\t_3_\n
-             _73 _64\t98_\n
-             \n
-             ___ 41_\t25_\n
-             ___ _53\t__1\n
-             ___ ___\t_73\n"
+// The table of `--summary` with the techniques that made progress
+fn print_summary(stats: &Stats) {
+    let used = stats.techniques.iter()
+        .filter(|t| t.placements + t.eliminations > 0)
+        .collect::<Vec<_>>();
+    let width = used.iter().map(|t| t.name.len()).max().unwrap_or(0).max(9);
+    let level = |t: &TechniqueStats| t.difficulty.map_or("guessing", Difficulty::name);
+
+    outln!("  {:<width$}  placements  eliminations  level", "technique", width = width);
+    for technique in &used {
+        outln!(
+            "  {:<width$}  {:>10}  {:>12}  {}",
+            technique.name, technique.placements, technique.eliminations,
+            level(technique), width = width
         );
+    }
 
-        assert_eq!(to_string(&board), TEST_BOARD);
+    match stats.hardest() {
+        Some(hardest) => {
+            outln!("  hardest technique: {} ({})", hardest.name, level(hardest))
+        }
+        None => outln!("  hardest technique: none"),
     }
 }
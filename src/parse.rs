@@ -0,0 +1,541 @@
+use std::{borrow::Cow, error, fmt, str::FromStr};
+
+use crate::step::Reason;
+use crate::sudoku::{Board, Parity};
+
+/// Error of parsing a board
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum ParseError {
+    /// The input holds only this number of cells
+    TooFewCells(usize),
+    /// The input holds this number of cells, more than the board has
+    TooManyCells(usize),
+    /// The line with this number, counted from 1, is no valid cage
+    InvalidCage(usize),
+    /// The line with this number, counted from 1, is no valid thermometer or
+    /// arrow
+    InvalidShape(usize),
+    /// The text is no cell like `r4c7`
+    InvalidCellName,
+}
+
+impl fmt::Display for ParseError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            ParseError::TooFewCells(n) => write!(f, "too few cells: {}", n),
+            ParseError::TooManyCells(n) => write!(f, "too many cells: {}", n),
+            ParseError::InvalidCage(n) => write!(f, "invalid cage in line {}", n),
+            ParseError::InvalidShape(n) => write!(f, "invalid shape in line {}", n),
+            ParseError::InvalidCellName => {
+                f.write_str("invalid cell, expected one like r4c7")
+            }
+        }
+    }
+}
+
+impl error::Error for ParseError {}
+
+/// Parses a board from a string
+///
+/// Whitespace is ignored, the digits 1 to 9 and the letters A to Z for
+/// the values from 10 set a cell and every other character, including the
+/// values larger than `N`, leaves a cell empty.
+///
+/// With `N * N * N` characters (729 for 9x9) the input is read as pencil
+/// marks in the Sukaku format: each cell has `N` characters and the `k`-th is
+/// the value `k` if it is a candidate of the cell or e.g. `0` or `.` if not.
+///
+/// Grids of Simple Sudoku (`.ss` files) with `|` between the boxes and border
+/// lines like `|---+---+---|` are recognized, too, as well as CSV with a line
+/// for every row and nothing or `0` between the commas for empty cells.
+impl<const N: usize> FromStr for Board<N> {
+    type Err = ParseError;
+
+    fn from_str(data: &str) -> Result<Self, Self::Err> {
+        if data.contains(['|', ',']) {
+            return data.lines().map(normalize_line).collect::<String>().parse();
+        }
+
+        let cells = data.chars().filter(|c| !c.is_whitespace());
+        let count = cells.clone().count();
+        if count == N * N * N {
+            return Ok(from_candidates(cells));
+        } else if count < N * N {
+            return Err(ParseError::TooFewCells(count));
+        } else if count > N * N {
+            return Err(ParseError::TooManyCells(count));
+        }
+
+        let mut board = Board::new();
+        board.fill(cells.map(|c| {
+            c.to_digit(36).and_then(|x| {
+                if (1..=N as u32).contains(&x) {
+                    Some(x as u8)
+                } else {
+                    None
+                }
+            })
+        })).expect("the number of cells is checked");
+
+        Ok(board)
+    }
+}
+
+/// Iterator over the puzzles of a text, see [`Board::parse_all`]
+pub struct Puzzles<'a, const N: usize = 9> {
+    lines: std::str::Lines<'a>,
+}
+
+impl<const N: usize> Board<N> {
+    /// Parses several puzzles from `data`, e.g. one per line or as grids
+    ///
+    /// The lines are joined until they hold at least the `N * N` cells of a
+    /// puzzle; blank lines and comments starting with `#`, like the headers of
+    /// the SDK format, are ignored. Pencil marks must be on one line. This
+    /// reads also the SDM collections of SudoCue with one puzzle per line.
+    pub fn parse_all(data: &str) -> Puzzles<'_, N> {
+        Puzzles { lines: data.lines() }
+    }
+
+    /// Parses a region map for [`Board::set_regions`] with a character for
+    /// each cell, like `a` or `1`, that names its region
+    ///
+    /// Whitespace is ignored and the regions get numbered in the order of
+    /// their first cell.
+    pub fn parse_regions(data: &str) -> Result<Vec<usize>, ParseError> {
+        let mut names = Vec::new();
+        let regions = data.chars()
+            .filter(|c| !c.is_whitespace())
+            .map(|c| match names.iter().position(|&name| name == c) {
+                Some(region) => region,
+                None => {
+                    names.push(c);
+                    names.len() - 1
+                }
+            })
+            .collect::<Vec<_>>();
+
+        match regions.len() {
+            count if count < N * N => Err(ParseError::TooFewCells(count)),
+            count if count > N * N => Err(ParseError::TooManyCells(count)),
+            _ => Ok(regions),
+        }
+    }
+
+    /// Parses the marks for [`Board::set_parities`] with `e` for an even cell,
+    /// `o` for an odd one and any other character, like `.`, for an unmarked
+    /// one; whitespace is ignored
+    pub fn parse_parities(data: &str) -> Result<Vec<Option<Parity>>, ParseError> {
+        let marks = data.chars()
+            .filter(|c| !c.is_whitespace())
+            .map(|c| match c.to_ascii_lowercase() {
+                'e' => Some(Parity::Even),
+                'o' => Some(Parity::Odd),
+                _ => None,
+            })
+            .collect::<Vec<_>>();
+
+        match marks.len() {
+            count if count < N * N => Err(ParseError::TooFewCells(count)),
+            count if count > N * N => Err(ParseError::TooManyCells(count)),
+            _ => Ok(marks),
+        }
+    }
+}
+
+impl<const N: usize> Iterator for Puzzles<'_, N> {
+    type Item = Result<Board<N>, ParseError>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let mut buffer = String::new();
+        let mut count = 0;
+
+        for line in &mut self.lines {
+            if line.starts_with('#') {
+                continue;
+            }
+
+            let line = normalize_line(line);
+            count += line.chars().filter(|c| !c.is_whitespace()).count();
+            buffer.push_str(&line);
+
+            if count >= N * N {
+                return Some(buffer.parse());
+            }
+        }
+
+        if count == 0 {
+            None
+        } else {
+            Some(buffer.parse())
+        }
+    }
+}
+
+/// Guesses the size `N` of the boards in `data` among 4, 6, 9 and 16
+///
+/// The first line that isn't blank or a comment tells it by its number of cells:
+/// `N * N` for a puzzle on one line, `N * N * N` for pencil marks or `N` for the
+/// first row of a grid.
+pub fn detect_size(data: &str) -> Option<usize> {
+    const SIZES: [usize; 4] = [4, 6, 9, 16];
+
+    let cells = |line: &str| {
+        normalize_line(line).chars().filter(|c| !c.is_whitespace()).count()
+    };
+    let lines = data.lines().filter(|line| !line.starts_with('#'));
+    let count = lines.clone().map(cells).find(|&count| count != 0)?;
+
+    // a 4x4 puzzle on one line or the first row of a 16x16 grid
+    if count == 16 {
+        let total = lines.map(cells).sum::<usize>();
+        return Some(if total < 16 * 16 { 4 } else { 16 });
+    }
+
+    SIZES.iter()
+        .copied()
+        .find(|&n| count == n * n || count == n * n * n)
+        .or_else(|| SIZES.iter().copied().find(|&n| count == n))
+}
+
+/// A puzzle in the SDK format of SudoCue: header lines like `#A author` or
+/// `#D description` followed by the grid with one line for each row
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Sdk<const N: usize = 9> {
+    /// The headers as `('A', "author")`
+    pub headers: Vec<(char, String)>,
+    /// The puzzle
+    pub board: Board<N>,
+}
+
+impl<const N: usize> FromStr for Sdk<N> {
+    type Err = ParseError;
+
+    fn from_str(data: &str) -> Result<Self, Self::Err> {
+        let (headers, grid): (Vec<_>, Vec<_>) =
+            data.lines().partition(|line| line.starts_with('#'));
+
+        let headers = headers.iter()
+            .filter_map(|line| {
+                let mut chars = line[1..].chars();
+                chars.next().map(|tag| (tag, chars.as_str().trim().to_string()))
+            })
+            .collect();
+
+        Ok(Sdk { headers, board: grid.concat().parse()? })
+    }
+}
+
+impl<const N: usize> fmt::Display for Sdk<N> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        for (tag, text) in &self.headers {
+            writeln!(f, "#{} {}", tag, text)?;
+        }
+
+        let line = self.board.to_string();
+        let cells = line.chars().collect::<Vec<_>>();
+        for (i, row) in cells.chunks(N).enumerate() {
+            if i != 0 {
+                f.write_str("\n")?;
+            }
+            row.iter().try_for_each(|c| fmt::Write::write_char(f, *c))?;
+        }
+
+        Ok(())
+    }
+}
+
+// The cells of a line of a Simple Sudoku grid or CSV as one character per
+// cell: nothing of border lines like `*-----*` or `|---+---|`, the line without
+// the `|` between the boxes and a character for every field between commas
+fn normalize_line(line: &str) -> Cow<'_, str> {
+    let border = line.contains('-')
+        && line.contains(['*', '+', '|'])
+        && line.trim().chars().all(|c| "*-+|".contains(c));
+
+    if border {
+        Cow::Borrowed("")
+    } else if line.contains(',') {
+        let cells = line.split(',').map(|field| {
+            field.trim()
+                .trim_matches('"')
+                .parse::<u32>()
+                .ok()
+                .and_then(|val| std::char::from_digit(val, 36))
+                .unwrap_or('.')
+        });
+        Cow::Owned(cells.collect())
+    } else if line.contains('|') {
+        Cow::Owned(line.replace('|', ""))
+    } else {
+        Cow::Borrowed(line)
+    }
+}
+
+fn from_candidates<const N: usize>(cells: impl Iterator<Item = char>) -> Board<N> {
+    let mut board = Board::new();
+
+    for (i, c) in cells.enumerate() {
+        let val = (i % N + 1) as u8;
+        if c.to_digit(36) != Some(u32::from(val)) {
+            board.eliminate(i / N, val, Reason::Given);
+        }
+    }
+
+    board
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::Field;
+
+    fn board_from_string(data: &str) -> Board {
+        data.parse().unwrap()
+    }
+
+    fn to_string(board: &Board) -> String {
+        board.fields().map(|x| match x {
+            Field::Options(_) => '_',
+            Field::Value(v) => (v + 0x30).into(),
+        }).collect()
+    }
+
+    const TEST_BOARD : &str =
+        "92_______\
+         5__87____\
+         _38_91___\
+         _5293_16_\
+         _9_____3_\
+         _73_6498_\
+         ___41_25_\
+         ____53__1\
+         _______73";
+
+    #[test]
+    fn fill_board_simple() {
+        let board = board_from_string(TEST_BOARD);
+
+        assert_eq!(to_string(&board), TEST_BOARD);
+    }
+
+    #[test]
+    fn fill_board_placeholder_x() {
+        let board = board_from_string(
+            "92xxxxxxx\
+             5xx87xxxx\
+             x38x91xxx\
+             x5293x16x\
+             x9xxxxx3x\
+             x73x6498x\
+             xxx41x25x\
+             xxxx53xx1\
+             xxxxxxx73"
+        );
+
+        assert_eq!(to_string(&board), TEST_BOARD);
+    }
+
+    #[test]
+    fn fill_board_newline() {
+        let board = board_from_string(
+            "92_______\n\
+             5__87____\n\
+             _38_91___\n\
+             _5293_16_\n\
+             _9_____3_\n\
+             _73_6498_\n\
+             ___41_25_\n\
+             ____53__1\n\
+             _______73\n"
+        );
+
+        assert_eq!(to_string(&board), TEST_BOARD);
+    }
+
+    #[test]
+    fn fill_board_spaces() {
+        let board = board_from_string(
+            "92_ ___\t___
+             5__ 87_\t___
+             _38 _91\t___
+             _52 93_\t16_
+             _9_ ___\t_3_
+             _73 _64\t98_
+             ___ 41_\t25_
+             ___ _53\t__1
+             ___ ___\t_73"
+        );
+
+        assert_eq!(to_string(&board), TEST_BOARD);
+    }
+
+    #[test]
+    fn fill_board_spaces_newline() {
+        let board = board_from_string(
+            "92_ ___\t___\n
+             5__ 87_\t___\n
+             _38 _91\t___\n
+             \n
+             _52 93_\t16_\n
+             _9_ ___\t_3_\n
+             _73 _64\t98_\n
+             \n
+             ___ 41_\t25_\n
+             ___ _53\t__1\n
+             ___ ___\t_73\n"
+        );
+
+        assert_eq!(to_string(&board), TEST_BOARD);
+    }
+
+    #[test]
+    fn pencil_marks() {
+        let mut data = "123456789".repeat(81);
+        data.replace_range(0..9, "1.3000000");
+        data.replace_range(9..18, "..3......");
+        data.replace_range(18..27, "000000000");
+
+        let board = board_from_string(&data);
+        assert_eq!(board.candidates((0, 0)).as_deref(), Some(&[1, 3][..]));
+        assert_eq!(board.candidates((0, 1)).as_deref(), Some(&[3][..]));
+        assert_eq!(board.candidates((0, 2)).as_deref(), Some(&[][..]));
+        assert_eq!(board.candidate_count((8, 8)), 9);
+        assert!(board.contradiction().is_some());
+        assert!(!board.is_given((0, 1)));
+    }
+
+    #[test]
+    fn parse_all() {
+        let grid = TEST_BOARD.chars()
+            .collect::<Vec<_>>()
+            .chunks(9)
+            .map(|line| line.iter().collect::<String>() + "\n")
+            .collect::<String>();
+        let data = format!(
+            "{}\n\n{}{}\n{}\n1234", TEST_BOARD, grid, ".".repeat(81), "6".repeat(729)
+        );
+
+        let boards = Board::<9>::parse_all(&data).collect::<Vec<_>>();
+        assert_eq!(boards.len(), 5);
+        assert_eq!(boards[0].as_ref().map(to_string), Ok(TEST_BOARD.to_string()));
+        assert_eq!(boards[1].as_ref().map(to_string), Ok(TEST_BOARD.to_string()));
+        assert_eq!(boards[2].as_ref().map(to_string), Ok("_".repeat(81)));
+        assert_eq!(boards[3].as_ref().map(|b| b.candidate_count((0, 0))), Ok(1));
+        assert_eq!(boards[4], Err(ParseError::TooFewCells(4)));
+
+        assert_eq!(Board::<9>::parse_all(" \n").count(), 0);
+    }
+
+    #[test]
+    fn sdk() {
+        let data = format!(
+            "#A Anonymous\n#D Test\n{}",
+            TEST_BOARD.replace('_', ".").as_bytes()
+                .chunks(9)
+                .map(|row| String::from_utf8_lossy(row) + "\n")
+                .collect::<String>()
+        );
+
+        let sdk = data.parse::<Sdk>().unwrap();
+        assert_eq!(sdk.headers, [('A', "Anonymous".into()), ('D', "Test".into())]);
+        assert_eq!(to_string(&sdk.board), TEST_BOARD);
+        assert_eq!(sdk.to_string() + "\n", data);
+
+        let boards = Board::<9>::parse_all(&data).collect::<Vec<_>>();
+        assert_eq!(boards, [Ok(sdk.board)]);
+    }
+
+    #[test]
+    fn simple_sudoku() {
+        let rows = TEST_BOARD.as_bytes()
+            .chunks(3)
+            .map(|part| String::from_utf8_lossy(part).replace('_', "X"))
+            .collect::<Vec<_>>();
+        let mut data = String::from("*-----------*\n");
+        for (i, row) in rows.chunks(3).enumerate() {
+            if i == 3 || i == 6 {
+                data += "|---+---+---|\n";
+            }
+            data += &format!("|{}|\n", row.join("|"));
+        }
+        data += "*-----------*\n";
+
+        assert_eq!(to_string(&board_from_string(&data)), TEST_BOARD);
+
+        let boards = Board::<9>::parse_all(&format!("{}\n{}", data, data))
+            .collect::<Vec<_>>();
+        assert_eq!(boards.len(), 2);
+        assert_eq!(boards[1].as_ref().map(to_string), Ok(TEST_BOARD.to_string()));
+    }
+
+    #[test]
+    fn csv() {
+        let data = TEST_BOARD.as_bytes()
+            .chunks(9)
+            .map(|row| {
+                let cells = row.iter()
+                    .map(|&c| char::from(c).to_string().replace('_', ""))
+                    .collect::<Vec<_>>();
+                cells.join(",") + "\n"
+            })
+            .collect::<String>();
+
+        assert_eq!(to_string(&board_from_string(&data)), TEST_BOARD);
+        assert_eq!(
+            to_string(&board_from_string(&data.replace(",,", ",0,"))),
+            TEST_BOARD
+        );
+
+        let boards = Board::<9>::parse_all(&format!("{}{}", data, data))
+            .collect::<Vec<_>>();
+        assert_eq!(boards.len(), 2);
+        assert_eq!(boards[1].as_ref().map(to_string), Ok(TEST_BOARD.to_string()));
+
+        let board: Board<16> = format!("16,10{}", ",".repeat(254)).parse().unwrap();
+        assert_eq!(board.field((0, 0)), Field::Value(16));
+        assert_eq!(board.field((0, 1)), Field::Value(10));
+    }
+
+    #[test]
+    fn regions() {
+        let regions = Board::<4>::parse_regions("aabb\naabb\ncddd\ncccd").unwrap();
+        assert_eq!(regions, vec![0, 0, 1, 1, 0, 0, 1, 1, 2, 3, 3, 3, 2, 2, 2, 3]);
+        assert_eq!(Board::<4>::parse_regions("aabb"), Err(ParseError::TooFewCells(4)));
+    }
+
+    #[test]
+    fn parities() {
+        let marks = Board::<4>::parse_parities("eO..\n....\n....\n...e").unwrap();
+        assert_eq!(marks[..3], [Some(Parity::Even), Some(Parity::Odd), None]);
+        assert_eq!(marks[15], Some(Parity::Even));
+        let marks = Board::<4>::parse_parities(&".".repeat(17));
+        assert_eq!(marks, Err(ParseError::TooManyCells(17)));
+    }
+
+    #[test]
+    fn detect_size() {
+        assert_eq!(super::detect_size(TEST_BOARD), Some(9));
+        assert_eq!(super::detect_size(&"1".repeat(729)), Some(9));
+        assert_eq!(super::detect_size("#A x\n\n12.   ...\n"), Some(6));
+        assert_eq!(super::detect_size("1... .... .... ...2"), Some(4));
+        assert_eq!(super::detect_size("1...\n....\n....\n...2"), Some(4));
+        assert_eq!(super::detect_size(&".".repeat(256)), Some(16));
+        let grid = format!("{}\n", ".".repeat(16)).repeat(16);
+        assert_eq!(super::detect_size(&grid), Some(16));
+        assert_eq!(super::detect_size("12345"), None);
+        assert_eq!(super::detect_size(""), None);
+    }
+
+    #[test]
+    fn too_few_cells() {
+        assert_eq!("12345".parse::<Board>().err(), Some(ParseError::TooFewCells(5)));
+        assert_eq!("".parse::<Board>().err(), Some(ParseError::TooFewCells(0)));
+    }
+
+    #[test]
+    fn too_many_cells() {
+        let data = format!("{} 1", TEST_BOARD);
+        assert_eq!(data.parse::<Board>().err(), Some(ParseError::TooManyCells(82)));
+    }
+}
@@ -0,0 +1,136 @@
+use std::io::{self, BufRead, Write};
+
+use serde_json::{json, Value};
+use sudoku_solver::{
+    Board, Difficulty, GeneratorConfig, Solver, SolverConfig, Symmetry,
+};
+
+/// The methods of [`call`]
+pub const METHODS: [&str; 5] = ["solve", "hint", "validate", "rate", "generate"];
+
+/// Why [`call`] failed
+pub enum Error {
+    UnknownMethod,
+    InvalidParams(String),
+}
+
+/// Runs `method` with the JSON object `params`
+///
+/// All methods except `generate` take the field `puzzle`; `generate` takes
+/// the optional fields `clues`, `seed`, `difficulty` and `symmetry`.
+pub fn call(method: &str, params: &Value, solver: &Solver) -> Result<Value, Error> {
+    if !METHODS.contains(&method) {
+        return Err(Error::UnknownMethod);
+    }
+
+    if method == "generate" {
+        let unknown = |field: &str, name: &str| {
+            Error::InvalidParams(format!("unknown {} {:?}", field, name))
+        };
+        let level = params["difficulty"].as_str();
+        let symmetry = params["symmetry"].as_str().unwrap_or("none");
+
+        let config = GeneratorConfig {
+            clues: params["clues"].as_u64().unwrap_or(0) as usize,
+            seed: params["seed"].as_u64(),
+            difficulty: level
+                .map(|level| {
+                    Difficulty::from_name(level).ok_or(unknown("difficulty", level))
+                })
+                .transpose()?,
+            symmetry: Symmetry::from_name(symmetry)
+                .ok_or_else(|| unknown("symmetry", symmetry))?,
+        };
+        let board = Board::<9>::generate_with_config(&config)
+            .ok_or_else(|| Error::InvalidParams("found no such puzzle".to_string()))?;
+        return Ok(json!({
+            "puzzle": board.to_string(),
+            "difficulty": board.rate().map(|level| level.name()),
+        }));
+    }
+
+    let mut board = params["puzzle"]
+        .as_str()
+        .ok_or_else(|| Error::InvalidParams("the field puzzle is missing".to_string()))?
+        .parse::<Board>()
+        .map_err(|err| Error::InvalidParams(err.to_string()))?;
+
+    if method == "validate" {
+        let conflicts = board.conflicts();
+        let solutions = if conflicts.is_empty() { board.count_solutions(2) } else { 0 };
+        return Ok(json!({
+            "valid": conflicts.is_empty(),
+            "conflicts": conflicts,
+            "unique": solutions == 1,
+            "solved": board.is_solved(),
+        }));
+    }
+
+    if !board.is_valid() {
+        let msg = "a value occurs twice in a row, column or square";
+        return Err(Error::InvalidParams(msg.to_string()));
+    }
+
+    Ok(match method {
+        "solve" => {
+            let status = solver.solve(&mut board);
+            json!({
+                "status": status,
+                "grid": board.to_string(),
+                "contradiction": board.contradiction().map(|c| c.to_string()),
+            })
+        }
+        "hint" => {
+            let hint = solver.next_hint(&board);
+            json!({
+                "explanation": hint.as_ref().map(|hint| hint.explain()),
+                "hint": hint,
+            })
+        }
+        _ => json!({ "difficulty": board.rate().map(|level| level.name()) }),
+    })
+}
+
+/// Answers one JSON-RPC request per line of stdin with one line on stdout
+/// until stdin ends
+pub fn daemon(config: &SolverConfig) -> io::Result<()> {
+    let solver = Solver::with_config(config);
+    let mut out = io::stdout().lock();
+
+    for line in io::stdin().lock().lines() {
+        let line = line?;
+        if line.trim().is_empty() {
+            continue;
+        }
+
+        let response = match serde_json::from_str::<Value>(&line) {
+            Ok(request) => {
+                let method = request["method"].as_str().unwrap_or_default();
+                let result = call(method, &request["params"], &solver);
+                let mut response = match result {
+                    Ok(result) => json!({ "jsonrpc": "2.0", "result": result }),
+                    Err(Error::UnknownMethod) => {
+                        error(-32601, format!("unknown method {:?}", method))
+                    }
+                    Err(Error::InvalidParams(msg)) => error(-32602, msg),
+                };
+                response["id"] = request["id"].clone();
+                response
+            }
+            Err(err) => error(-32700, err.to_string()),
+        };
+
+        writeln!(out, "{}", response)?;
+        out.flush()?;
+    }
+
+    Ok(())
+}
+
+fn error(code: i32, message: String) -> Value {
+    json!({
+        "jsonrpc": "2.0",
+        "error": { "code": code, "message": message },
+        "id": null,
+    })
+}
@@ -0,0 +1,275 @@
+use std::{fmt, iter, str::FromStr};
+
+use crate::parse::ParseError;
+use crate::step::Reason;
+use crate::strategy::{Solver, SolverConfig};
+use crate::sudoku::{Board, Field, SolveResult};
+
+/// Number of rows and columns of the square that holds the grids of a
+/// [`Samurai`]
+pub const SAMURAI_SIZE: usize = 21;
+
+/// Samurai Sudoku: five 9x9 grids in a square of 21x21 cells, where the grid
+/// in the center shares each of its corner boxes with another grid
+///
+/// Positions `(row, column)` count in the square; the grids are ordered row by
+/// row: top left, top right, center, bottom left and bottom right.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Samurai {
+    grids: [Board; 5],
+}
+
+impl Samurai {
+    /// Positions of the top left cells of the grids
+    pub const OFFSETS: [(usize, usize); 5] =
+        [(0, 0), (0, 12), (6, 6), (12, 0), (12, 12)];
+
+    /// The five grids
+    pub fn grids(&self) -> &[Board; 5] {
+        &self.grids
+    }
+
+    // The grids holding the cell at `pos` with the position in each
+    fn cells(pos: (usize, usize)) -> impl Iterator<Item = (usize, (usize, usize))> {
+        Self::OFFSETS.iter().enumerate().filter_map(move |(g, &(row, col))| {
+            let inside = (row..row + 9).contains(&pos.0)
+                && (col..col + 9).contains(&pos.1);
+            inside.then(|| (g, (pos.0 - row, pos.1 - col)))
+        })
+    }
+
+    /// The cell at `(row, column)` or `None` if no grid covers it; a shared
+    /// cell holds the value of either grid
+    pub fn field(&self, pos: (usize, usize)) -> Option<Field> {
+        let mut fields = Self::cells(pos).map(|(g, pos)| self.grids[g].field(pos));
+        let first = fields.next()?;
+        fields.find(|fld| matches!(fld, Field::Value(_))).or(Some(first))
+    }
+
+    /// Whether every grid is solved
+    pub fn is_solved(&self) -> bool {
+        self.grids.iter().all(Board::is_solved)
+    }
+
+    /// Solves the grids with the techniques selected by `config` and passes
+    /// the values and candidates of the shared boxes between the grids until
+    /// no grid makes progress; with backtracking it guesses the cell with the
+    /// fewest candidates of all grids then
+    pub fn solve_with_config(&mut self, config: &SolverConfig) -> SolveResult {
+        let solver = Solver::with_config(&SolverConfig {
+            backtracking: false,
+            ..config.clone()
+        });
+
+        self.solve_with(&solver, config.backtracking)
+    }
+
+    /// Solves the grids with the default [`Solver`] as far as possible
+    pub fn solve(&mut self) -> SolveResult {
+        self.solve_with_config(&SolverConfig::default())
+    }
+
+    fn solve_with(&mut self, solver: &Solver, backtracking: bool) -> SolveResult {
+        loop {
+            for grid in &mut self.grids {
+                match solver.solve(grid) {
+                    result @ (SolveResult::Contradiction
+                    | SolveResult::TimeLimit
+                    | SolveResult::IterationLimit) => return result,
+                    SolveResult::Solved | SolveResult::Stuck => (),
+                }
+            }
+
+            match self.share() {
+                None => return SolveResult::Contradiction,
+                Some(true) => (),
+                Some(false) => break,
+            }
+        }
+
+        if self.is_solved() {
+            return SolveResult::Solved;
+        } else if !backtracking {
+            return SolveResult::Stuck;
+        }
+
+        let (g, pos) = (0..5)
+            .flat_map(|g| (0..81).map(move |idx| (g, (idx / 9, idx % 9))))
+            .filter(|&(g, pos)| self.grids[g].candidates(pos).is_some())
+            .min_by_key(|&(g, pos)| self.grids[g].candidate_count(pos))
+            .expect("an unsolved grid has an unsolved cell");
+
+        for val in self.grids[g].candidates(pos).into_iter().flatten() {
+            let mut trial = self.clone();
+            trial.grids[g].set_idx(pos.0 * 9 + pos.1, val, Reason::Guess);
+            match trial.solve_with(solver, true) {
+                SolveResult::Solved => {
+                    *self = trial;
+                    return SolveResult::Solved;
+                }
+                result @ (SolveResult::TimeLimit | SolveResult::IterationLimit) => {
+                    return result;
+                }
+                SolveResult::Stuck | SolveResult::Contradiction => (),
+            }
+        }
+
+        SolveResult::Contradiction
+    }
+
+    // Passes the values and candidates of the cells shared by two grids from
+    // one to the other; `None` if they hold different values
+    fn share(&mut self) -> Option<bool> {
+        let mut changed = false;
+
+        for (g, &(row, col)) in Self::OFFSETS.iter().enumerate() {
+            for idx in 0..81 {
+                let pos = (row + idx / 9, col + idx % 9);
+                for (other, other_pos) in Self::cells(pos).filter(|&(o, _)| o != g) {
+                    let other_idx = other_pos.0 * 9 + other_pos.1;
+                    let theirs = self.grids[other].field_at(other_idx);
+                    let grid = &mut self.grids[g];
+
+                    match (grid.field_at(idx), theirs) {
+                        (Field::Value(a), Field::Value(b)) if a != b => return None,
+                        (Field::Options(_), Field::Value(v)) => {
+                            grid.set_idx(idx, v, Reason::Overlap);
+                            changed = true;
+                        }
+                        (Field::Options(mine), Field::Options(theirs)) => {
+                            let gone = mine.into_iter().filter(|v| !theirs.contains(v));
+                            for val in gone {
+                                changed |= grid.eliminate(idx, val, Reason::Overlap);
+                            }
+                        }
+                        _ => (),
+                    }
+                }
+            }
+        }
+
+        Some(changed)
+    }
+}
+
+/// Parses the 21 rows of the square with a character for every cell or the
+/// five grids one after another
+///
+/// The characters stand for the values as for [`Board`]. In the square, the
+/// cells outside the grids are spaces, as printed, or any other character on
+/// a single line; otherwise whitespace is ignored. The grids may leave a
+/// shared cell empty that the other grid sets.
+impl FromStr for Samurai {
+    type Err = ParseError;
+
+    fn from_str(data: &str) -> Result<Self, Self::Err> {
+        let mut cells = data.chars().filter(|c| !c.is_whitespace()).collect::<Vec<_>>();
+        let lines = data.lines()
+            .filter(|line| !line.trim().is_empty())
+            .collect::<Vec<_>>();
+        if cells.len() != 5 * 81 && lines.len() == SAMURAI_SIZE {
+            cells = lines.iter()
+                .flat_map(|line| {
+                    line.chars().chain(iter::repeat(' ')).take(SAMURAI_SIZE)
+                })
+                .collect();
+        }
+
+        let grid = |g: usize| -> String {
+            if cells.len() == 5 * 81 {
+                return cells[g * 81..(g + 1) * 81].iter().collect();
+            }
+
+            let (row, col) = Self::OFFSETS[g];
+            (0..81)
+                .map(|idx| cells[(row + idx / 9) * SAMURAI_SIZE + col + idx % 9])
+                .map(|c| if c.is_whitespace() { '.' } else { c })
+                .collect()
+        };
+
+        match cells.len() {
+            n if n == 5 * 81 || n == SAMURAI_SIZE * SAMURAI_SIZE => (),
+            n if n < 5 * 81 => return Err(ParseError::TooFewCells(n)),
+            n => return Err(ParseError::TooManyCells(n)),
+        }
+
+        let mut samurai = Samurai { grids: Default::default() };
+        for g in 0..5 {
+            samurai.grids[g] = grid(g).parse()?;
+        }
+
+        Ok(samurai)
+    }
+}
+
+/// Prints the square with a space for the cells outside the grids
+impl fmt::Display for Samurai {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        for row in 0..SAMURAI_SIZE {
+            let line = (0..SAMURAI_SIZE)
+                .map(|col| match self.field((row, col)) {
+                    Some(Field::Value(v)) => char::from(b'0' + v),
+                    Some(Field::Options(_)) => '.',
+                    None => ' ',
+                })
+                .collect::<String>();
+            writeln!(f, "{}", line.trim_end())?;
+        }
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // the grids one after another; the center leaves its corner boxes to
+    // the other grids and can't be solved alone
+    const PUZZLE: &str = "
+             2..78354. 78.92536. .35..48.7
+             .....86.. ..63.1.7. 82.4.7.15
+             3.8..2.9. 6...4.1.. 459.36.8.
+             ..8...129 2.139.6.7 46927.53.
+             .3.51..64 ....39..1 19.86.75.
+             5231.7496 7........ 9..6.3.75
+             ......... ...2.4... ...3.6...
+             347528169 ..1.4.3.. ...137.48
+             ...413... ......... ...7.....
+             531...2.8 ..9.27.34 47.358.19
+             9...41..2 2865793.. .1.2.6.9.
+             6...8.153 125963.87 .48.1..2.
+             .9...42.. 871.359.6 .32.9...5
+             .68.2.7.. 5.974.628 2.756...9
+             .5417683. 72.4.9561 .8635.497";
+
+    #[test]
+    fn solve() {
+        let mut samurai: Samurai = PUZZLE.parse().unwrap();
+        let mut center = samurai.grids()[2].clone();
+        assert_eq!(center.solve(), SolveResult::Stuck);
+
+        assert_eq!(samurai.field((0, 0)), Some(Field::Value(2)));
+        assert!(matches!(samurai.field((6, 6)), Some(Field::Options(_))));
+        assert_eq!(samurai.field((0, 10)), None);
+
+        assert_eq!(samurai.solve(), SolveResult::Solved);
+        assert!(samurai.is_solved());
+        assert_eq!(
+            samurai.grids()[2].to_string(),
+            "496871523153294786782356914347528169821649357\
+             965137248278413695534962871619785432"
+        );
+
+        let text = samurai.to_string();
+        assert_eq!(text.lines().nth(9), Some("      347528169"));
+        assert_eq!(text.parse::<Samurai>(), Ok(samurai));
+    }
+
+    #[test]
+    fn parse() {
+        assert_eq!("12".parse::<Samurai>(), Err(ParseError::TooFewCells(2)));
+        let data = ".".repeat(500);
+        assert_eq!(data.parse::<Samurai>(), Err(ParseError::TooManyCells(500)));
+    }
+}
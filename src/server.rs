@@ -0,0 +1,55 @@
+use serde_json::{json, Value};
+use sudoku_solver::{Solver, SolverConfig};
+use tiny_http::{Header, Method, Request, Response, Server};
+
+use crate::rpc;
+
+/// Answers `POST /METHOD` for the methods of [`rpc::call`] with JSON documents
+/// until the process gets killed
+pub fn serve(port: u16, config: &SolverConfig) -> Result<(), String> {
+    let server = Server::http(("0.0.0.0", port)).map_err(|err| err.to_string())?;
+    let solver = Solver::with_config(config);
+
+    for mut request in server.incoming_requests() {
+        let (status, body) = match handle(&mut request, &solver) {
+            Ok(body) => (200, body),
+            Err((status, msg)) => (status, json!({ "error": msg })),
+        };
+
+        let header = Header::from_bytes("Content-Type", "application/json")
+            .expect("valid header");
+        let response = Response::from_string(body.to_string())
+            .with_status_code(status)
+            .with_header(header);
+
+        if let Err(err) = request.respond(response) {
+            eprintln!("Failed to answer a request: {}", err);
+        }
+    }
+
+    Ok(())
+}
+
+fn handle(request: &mut Request, solver: &Solver) -> Result<Value, (u16, String)> {
+    let path = request.url().to_string();
+    let method = path.trim_start_matches('/');
+    if !rpc::METHODS.contains(&method) {
+        return Err((404, format!("unknown path {}", path)));
+    }
+
+    if *request.method() != Method::Post {
+        return Err((405, "use POST".to_string()));
+    }
+
+    let mut body = String::new();
+    request.as_reader()
+        .read_to_string(&mut body)
+        .map_err(|err| (400, err.to_string()))?;
+    let body = if body.trim().is_empty() { "{}" } else { &body };
+    let body: Value = serde_json::from_str(body).map_err(|err| (400, err.to_string()))?;
+
+    rpc::call(method, &body, solver).map_err(|err| match err {
+        rpc::Error::UnknownMethod => (404, format!("unknown path {}", path)),
+        rpc::Error::InvalidParams(msg) => (400, msg),
+    })
+}
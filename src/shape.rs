@@ -0,0 +1,355 @@
+use crate::constraint::{cell_names, Constraint};
+use crate::killer::parse_cell;
+use crate::parse::ParseError;
+use crate::step::Reason;
+use crate::strategy::{Difficulty, Strategy};
+use crate::sudoku::{Board, Field};
+
+/// A shape drawn on the board that restricts the values of its cells
+///
+/// Positions are `(row, column)` tuples.
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum Shape {
+    /// The values increase strictly from the bulb, the first cell, along the
+    /// path
+    Thermometer(Vec<(usize, usize)>),
+    /// The values of the cells add up to the value of the circle
+    Arrow {
+        circle: (usize, usize),
+        cells: Vec<(usize, usize)>,
+    },
+}
+
+impl Shape {
+    /// Parses a shape from each line like `thermo: r1c1 r2c1 r3c1`, starting
+    /// at the bulb, or `arrow: r5c5 r4c4 r3c3` with the circle first; empty
+    /// lines and lines starting with `#` are skipped
+    pub fn parse_all(data: &str) -> Result<Vec<Shape>, ParseError> {
+        data.lines()
+            .enumerate()
+            .map(|(i, line)| (i, line.trim()))
+            .filter(|(_, line)| !line.is_empty() && !line.starts_with('#'))
+            .map(|(i, line)| parse_shape(line).ok_or(ParseError::InvalidShape(i + 1)))
+            .collect()
+    }
+
+    /// All cells of the shape, the circle of an arrow first
+    pub fn cells(&self) -> Vec<(usize, usize)> {
+        match self {
+            Shape::Thermometer(cells) => cells.clone(),
+            Shape::Arrow { circle, cells } => {
+                std::iter::once(*circle).chain(cells.iter().copied()).collect()
+            }
+        }
+    }
+
+    /// Whether the values of the cells, in the order of [`Shape::cells`] and
+    /// `None` for empty ones, can still complete the shape with the values 1
+    /// to `size`
+    pub fn fits(&self, values: &[Option<u8>], size: usize) -> bool {
+        match self {
+            Shape::Thermometer(_) => {
+                let len = values.len();
+                let placed = values.iter()
+                    .enumerate()
+                    .filter_map(|(i, val)| val.map(|v| (i, v as usize)))
+                    .collect::<Vec<_>>();
+
+                placed.iter().all(|&(i, v)| v > i && v + len - 1 - i <= size)
+                    && placed.windows(2).all(|w| w[1].1 >= w[0].1 + w[1].0 - w[0].0)
+            }
+            Shape::Arrow { .. } => {
+                let sum = values[1..]
+                    .iter()
+                    .map(|val| val.map_or(1, usize::from))
+                    .sum::<usize>();
+                let complete = values.iter().all(Option::is_some);
+
+                match values[0] {
+                    Some(circle) if complete => sum == circle as usize,
+                    Some(circle) => sum <= circle as usize,
+                    None => sum <= size,
+                }
+            }
+        }
+    }
+}
+
+fn parse_shape(line: &str) -> Option<Shape> {
+    let (kind, cells) = line.split_once(':')?;
+    let cells = cells.split_whitespace().map(parse_cell).collect::<Option<Vec<_>>>()?;
+
+    if cells.len() < 2 {
+        return None;
+    }
+
+    match kind.trim().to_lowercase().as_str() {
+        "thermo" | "thermometer" => Some(Shape::Thermometer(cells)),
+        "arrow" => Some(Shape::Arrow { circle: cells[0], cells: cells[1..].to_vec() }),
+        _ => None,
+    }
+}
+
+// The value or the candidates of the cell
+fn candidates<const N: usize>(board: &Board<N>, pos: (usize, usize)) -> Vec<u8> {
+    match board.field(pos) {
+        Field::Value(v) => vec![v],
+        Field::Options(opts) => opts.to_vec(),
+    }
+}
+
+/// Removes the candidates of thermometer cells that leave too few smaller
+/// values for the cells towards the bulb or too few larger ones towards the
+/// tip
+pub struct Thermometers;
+
+impl<const N: usize> Strategy<N> for Thermometers {
+    fn name(&self) -> &'static str {
+        "thermometers"
+    }
+
+    fn difficulty(&self) -> Difficulty {
+        Difficulty::Easy
+    }
+
+    fn cost(&self) -> u32 {
+        3
+    }
+
+    fn apply(&self, board: &mut Board<N>) -> bool {
+        self.apply_changed(board, 0)
+    }
+
+    fn apply_changed(&self, board: &mut Board<N>, since: u32) -> bool {
+        let mut changed = false;
+        for shape in board.shapes().to_vec() {
+            if matches!(shape, Shape::Thermometer(_)) && touched(board, &shape, since) {
+                changed |= shape.propagate(board);
+            }
+        }
+
+        changed
+    }
+}
+
+/// Removes the candidates of the circle outside the range of the sums of the
+/// arrow cells and the candidates of arrow cells that make the sum too small
+/// or too large for the circle
+pub struct Arrows;
+
+impl<const N: usize> Strategy<N> for Arrows {
+    fn name(&self) -> &'static str {
+        "arrow sums"
+    }
+
+    fn difficulty(&self) -> Difficulty {
+        Difficulty::Medium
+    }
+
+    fn cost(&self) -> u32 {
+        3
+    }
+
+    fn apply(&self, board: &mut Board<N>) -> bool {
+        self.apply_changed(board, 0)
+    }
+
+    fn apply_changed(&self, board: &mut Board<N>, since: u32) -> bool {
+        let mut changed = false;
+        for shape in board.shapes().to_vec() {
+            if matches!(shape, Shape::Arrow { .. }) && touched(board, &shape, since) {
+                changed |= shape.propagate(board);
+            }
+        }
+
+        changed
+    }
+}
+
+// Whether a cell of `shape` changed after the generation `since`
+fn touched<const N: usize>(board: &Board<N>, shape: &Shape, since: u32) -> bool {
+    shape.cells()
+        .into_iter()
+        .any(|(row, col)| board.is_changed_since(row * N + col, since))
+}
+
+/// The cells of a thermometer hold different values; [`Thermometers`] and
+/// [`Arrows`] propagate the shapes
+impl<const N: usize> Constraint<N> for Shape {
+    fn peers(&self, _board: &Board<N>, pos: (usize, usize)) -> Vec<(usize, usize)> {
+        match self {
+            Shape::Thermometer(cells) if cells.contains(&pos) => {
+                cells.iter().copied().filter(|&other| other != pos).collect()
+            }
+            _ => Vec::new(),
+        }
+    }
+
+    fn propagate(&self, board: &mut Board<N>) -> bool {
+        let cells = self.cells();
+        let candidates = cells.iter()
+            .map(|&pos| candidates(board, pos))
+            .collect::<Vec<_>>();
+
+        let remove = match self {
+            Shape::Thermometer(_) => thermometer(&candidates),
+            Shape::Arrow { .. } => arrow(&candidates),
+        };
+        let reason = match self {
+            Shape::Thermometer(_) => Reason::Thermometer,
+            Shape::Arrow { .. } => Reason::ArrowSum,
+        };
+
+        let mut changed = false;
+        for (i, val) in remove {
+            let (row, col) = cells[i];
+            changed |= board.eliminate(row * N + col, val, reason.clone());
+        }
+
+        changed
+    }
+
+    fn is_valid(&self, board: &Board<N>) -> bool {
+        let values = self.cells()
+            .iter()
+            .map(|&pos| match board.field(pos) {
+                Field::Value(v) => Some(v),
+                Field::Options(_) => None,
+            })
+            .collect::<Vec<_>>();
+
+        self.fits(&values, N)
+    }
+
+    fn describe(&self) -> Option<String> {
+        Some(match self {
+            Shape::Thermometer(cells) => format!("thermometer: {}", cell_names(cells)),
+            Shape::Arrow { .. } => format!("arrow: {}", cell_names(&self.cells())),
+        })
+    }
+}
+
+// The candidates `(cell, value)` of a thermometer that leave too few smaller
+// values for the cells before or too few larger ones for the cells after
+fn thermometer(candidates: &[Vec<u8>]) -> Vec<(usize, u8)> {
+    // the smallest value each cell can take after the ones before it, and the
+    // largest before the ones after it
+    let mut low = vec![0; candidates.len()];
+    let mut prev = 0;
+    for (i, cands) in candidates.iter().enumerate() {
+        let larger = cands.iter().copied().filter(|&v| v > prev);
+        prev = larger.min().unwrap_or(u8::MAX);
+        low[i] = prev;
+    }
+
+    let mut high = vec![0; candidates.len()];
+    let mut next = u8::MAX;
+    for (i, cands) in candidates.iter().enumerate().rev() {
+        let smaller = cands.iter().copied().filter(|&v| v < next);
+        next = smaller.max().unwrap_or(0);
+        high[i] = next;
+    }
+
+    candidates.iter()
+        .enumerate()
+        .flat_map(|(i, cands)| cands.iter().map(move |&val| (i, val)))
+        .filter(|&(i, val)| val < low[i] || val > high[i])
+        .collect()
+}
+
+// The candidates `(cell, value)` of an arrow, the circle first, that leave
+// the circle out of the range of the sums of the other cells
+fn arrow(candidates: &[Vec<u8>]) -> Vec<(usize, u8)> {
+    if candidates.iter().any(Vec::is_empty) {
+        return Vec::new();
+    }
+
+    let bounds = |cands: &[u8]| {
+        let min = *cands.iter().min().unwrap_or(&0) as usize;
+        (min, *cands.iter().max().unwrap_or(&0) as usize)
+    };
+    let (circle_min, circle_max) = bounds(&candidates[0]);
+    let (sum_min, sum_max) = candidates[1..]
+        .iter()
+        .map(|cands| bounds(cands))
+        .fold((0, 0), |(lo, hi), (min, max)| (lo + min, hi + max));
+
+    let mut remove = Vec::new();
+    for &val in &candidates[0] {
+        if (val as usize) < sum_min || val as usize > sum_max {
+            remove.push((0, val));
+        }
+    }
+
+    for (i, cands) in candidates.iter().enumerate().skip(1) {
+        let (min, max) = bounds(cands);
+        for &val in cands {
+            let val_min = sum_min - min + val as usize;
+            let val_max = sum_max - max + val as usize;
+            if val_min > circle_max || val_max < circle_min {
+                remove.push((i, val));
+            }
+        }
+    }
+
+    remove
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parse() {
+        let data = "# shapes\nthermo: r1c1 r1c2\n\nArrow: r5c5 r4c4 r3c3";
+        let shapes = Shape::parse_all(data);
+        assert_eq!(shapes.unwrap(), vec![
+            Shape::Thermometer(vec![(0, 0), (0, 1)]),
+            Shape::Arrow { circle: (4, 4), cells: vec![(3, 3), (2, 2)] },
+        ]);
+
+        assert_eq!(Shape::parse_all("thermo: r1c1"), Err(ParseError::InvalidShape(1)));
+        let shapes = Shape::parse_all("\nline: r1c1 r1c2");
+        assert_eq!(shapes, Err(ParseError::InvalidShape(2)));
+    }
+
+    #[test]
+    fn fits() {
+        let thermo = Shape::Thermometer(vec![(0, 0), (0, 1), (0, 2)]);
+        assert!(thermo.fits(&[Some(1), None, Some(3)], 9));
+        assert!(!thermo.fits(&[Some(2), None, Some(3)], 9));
+        assert!(!thermo.fits(&[None, None, Some(2)], 9));
+        assert!(!thermo.fits(&[Some(8), None, None], 9));
+
+        let arrow = Shape::Arrow { circle: (0, 0), cells: vec![(1, 1), (2, 2)] };
+        assert!(arrow.fits(&[Some(5), Some(2), Some(3)], 9));
+        assert!(!arrow.fits(&[Some(5), Some(2), Some(4)], 9));
+        assert!(arrow.fits(&[Some(5), Some(4), None], 9));
+        assert!(!arrow.fits(&[Some(5), Some(5), None], 9));
+        assert!(!arrow.fits(&[None, Some(9), None], 9));
+    }
+
+    #[test]
+    fn strategies() {
+        let mut board = Board::<9>::new();
+        let thermo = Shape::Thermometer(vec![(0, 0), (1, 0), (2, 0), (3, 0)]);
+        board.add_shape(thermo).unwrap();
+        board.set((2, 0), 5);
+
+        assert!(Thermometers.apply(&mut board));
+        assert_eq!(board.candidates((0, 0)).as_deref(), Some(&[1, 2, 3][..]));
+        assert_eq!(board.candidates((1, 0)).as_deref(), Some(&[2, 3, 4][..]));
+        assert_eq!(board.candidates((3, 0)).as_deref(), Some(&[6, 7, 8, 9][..]));
+        assert!(!Thermometers.apply(&mut board));
+
+        let arrow = Shape::Arrow { circle: (4, 4), cells: vec![(3, 3), (5, 5)] };
+        board.add_shape(arrow).unwrap();
+        board.set((3, 3), 6);
+
+        assert!(Arrows.apply(&mut board));
+        assert_eq!(board.candidates((4, 4)).as_deref(), Some(&[7, 8, 9][..]));
+        assert_eq!(board.candidates((5, 5)).as_deref(), Some(&[1, 2, 3][..]));
+        assert!(!Arrows.apply(&mut board));
+    }
+}
@@ -0,0 +1,250 @@
+use crate::chess::Move;
+use crate::constraint::Constraint;
+use crate::error::Error;
+use crate::killer::{parse_cell, Cage};
+use crate::parse::detect_size;
+use crate::shape::Shape;
+use crate::sudoku::Board;
+
+/// A puzzle with its variants as JSON document, like
+/// `{"grid": "...", "variant": ["x"], "cages": [{"sum": 15, "cells": ["r1c1",
+/// "r1c2"]}]}`
+///
+/// Cells are written like `r1c1` with the row and column counted from 1.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+#[derive(serde::Serialize, serde::Deserialize)]
+#[serde(rename_all = "kebab-case", deny_unknown_fields)]
+pub struct Spec {
+    /// The cells as for parsing a board
+    pub grid: String,
+    /// The number of rows and columns; by default that of the grid
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub size: Option<usize>,
+    /// `x`, `windoku`, `disjoint`, `anti-knight` or `anti-king`
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub variant: Vec<String>,
+    /// The irregular regions instead of the boxes, see
+    /// [`Board::parse_regions`]
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub regions: Option<String>,
+    /// The parity marks, see [`Board::parse_parities`]
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub parity: Option<String>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub cages: Vec<SpecCage>,
+    /// Further sets of cells that must hold every value once
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub extra_regions: Vec<Vec<String>>,
+    /// The cells of each thermometer from the bulb on
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub thermometers: Vec<Vec<String>>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub arrows: Vec<SpecArrow>,
+    /// Cells of which one is smaller than the other, like `r1c1 < r1c2`
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub inequalities: Vec<String>,
+}
+
+/// A cage of a [`Spec`]
+#[derive(Clone, Debug, PartialEq, Eq, serde::Serialize, serde::Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct SpecCage {
+    pub sum: usize,
+    pub cells: Vec<String>,
+}
+
+/// An arrow of a [`Spec`]
+#[derive(Clone, Debug, PartialEq, Eq, serde::Serialize, serde::Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct SpecArrow {
+    pub circle: String,
+    pub cells: Vec<String>,
+}
+
+// A further region whose cells hold different values
+#[derive(Debug)]
+struct ExtraRegion(Vec<(usize, usize)>);
+
+impl<const N: usize> Constraint<N> for ExtraRegion {
+    fn peers(&self, _board: &Board<N>, pos: (usize, usize)) -> Vec<(usize, usize)> {
+        if !self.0.contains(&pos) {
+            return Vec::new();
+        }
+
+        self.0.iter().copied().filter(|&other| other != pos).collect()
+    }
+}
+
+impl Spec {
+    /// The number of rows and columns of the puzzle
+    pub fn size(&self) -> Option<usize> {
+        self.size.or_else(|| detect_size(&self.grid))
+    }
+
+    /// The board of the grid with all variants
+    pub fn board<const N: usize>(&self) -> Result<Board<N>, Error> {
+        let mut board: Board<N> = self.grid.parse()?;
+
+        if let Some(regions) = &self.regions {
+            board.set_regions(&Board::<N>::parse_regions(regions)?)?;
+        }
+
+        if let Some(parity) = &self.parity {
+            board.set_parities(&Board::<N>::parse_parities(parity)?)?;
+        }
+
+        for name in &self.variant {
+            match name.as_str() {
+                "x" => board.diagonals().collect::<Vec<_>>(),
+                "windoku" => board.windows().collect(),
+                "disjoint" => board.groups().collect(),
+                "anti-knight" => {
+                    board.add_move(Move::Knight);
+                    Vec::new()
+                }
+                "anti-king" => {
+                    board.add_move(Move::King);
+                    Vec::new()
+                }
+                _ => return Err(Error::InvalidSpec("variant")),
+            }
+            .into_iter()
+            .for_each(|unit| board.add_unit(unit));
+        }
+
+        for cage in &self.cages {
+            let cells = cells::<N>(&cage.cells, "cages")?;
+            board.add_cage(Cage { sum: cage.sum, cells })?;
+        }
+
+        for (i, region) in self.extra_regions.iter().enumerate() {
+            let cells = cells::<N>(region, "extra-regions")?;
+            let distinct = cells.iter()
+                .enumerate()
+                .all(|(j, pos)| !cells[..j].contains(pos));
+            if cells.len() != N || !distinct {
+                return Err(Error::InvalidRegion(i));
+            }
+            board.add_constraint(ExtraRegion(cells));
+        }
+
+        for thermometer in &self.thermometers {
+            let cells = cells::<N>(thermometer, "thermometers")?;
+            if cells.len() < 2 {
+                return Err(Error::InvalidSpec("thermometers"));
+            }
+            board.add_shape(Shape::Thermometer(cells))?;
+        }
+
+        for arrow in &self.arrows {
+            let circle = cells::<N>(std::slice::from_ref(&arrow.circle), "arrows")?[0];
+            let cells = cells::<N>(&arrow.cells, "arrows")?;
+            if cells.is_empty() {
+                return Err(Error::InvalidSpec("arrows"));
+            }
+            board.add_shape(Shape::Arrow { circle, cells })?;
+        }
+
+        // a thermometer of two cells, from the smaller one to the larger one
+        for inequality in &self.inequalities {
+            let cells = inequality_cells(inequality)
+                .ok_or(Error::InvalidSpec("inequalities"))?;
+            if cells.iter().any(|pos| pos.0 >= N || pos.1 >= N) {
+                return Err(Error::InvalidSpec("inequalities"));
+            }
+            board.add_shape(Shape::Thermometer(cells.to_vec()))?;
+        }
+
+        Ok(board)
+    }
+}
+
+// The positions of the cells like `r1c1` on a board with `N` rows and columns
+fn cells<const N: usize>(
+    cells: &[String], field: &'static str
+) -> Result<Vec<(usize, usize)>, Error> {
+    cells.iter()
+        .map(|cell| {
+            parse_cell(cell.trim())
+                .filter(|pos| pos.0 < N && pos.1 < N)
+                .ok_or(Error::InvalidSpec(field))
+        })
+        .collect()
+}
+
+// The smaller and the larger cell of `r1c1 < r1c2` or `r1c2 > r1c1`
+fn inequality_cells(text: &str) -> Option<[(usize, usize); 2]> {
+    let (smaller, larger) = match text.split_once('<') {
+        Some((a, b)) => (a, b),
+        None => {
+            let (a, b) = text.split_once('>')?;
+            (b, a)
+        }
+    };
+
+    Some([parse_cell(smaller.trim())?, parse_cell(larger.trim())?])
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::sudoku::SolveResult;
+
+    #[test]
+    fn board() {
+        let spec: Spec = serde_json::from_str(r#"{
+            "grid": "................",
+            "variant": ["x"],
+            "cages": [{"sum": 3, "cells": ["r1c1", "r1c2"]}],
+            "inequalities": ["r1c1 < r1c2", "r2c2 > r2c1"]
+        }"#).unwrap();
+        assert_eq!(spec.size(), Some(4));
+
+        let board = spec.board::<4>().unwrap();
+        assert_eq!(board.extra_units().len(), 2);
+        assert_eq!(board.cages().len(), 1);
+        assert_eq!(board.shapes()[1], Shape::Thermometer(vec![(1, 0), (1, 1)]));
+
+        let mut board = board;
+        assert_eq!(board.solve_dlx(), SolveResult::Solved);
+        assert_eq!(board.to_string()[..2], *"12");
+    }
+
+    #[test]
+    fn extra_regions() {
+        let row = (1..=4).map(|c| format!("r1c{}", c)).collect::<Vec<_>>();
+        let spec = Spec {
+            grid: "1...............".into(),
+            extra_regions: vec![row, vec!["r2c2".into()]],
+            ..Spec::default()
+        };
+        assert_eq!(spec.board::<4>().err(), Some(Error::InvalidRegion(1)));
+
+        let region = ["r2c1", "r2c2", "r3c3", "r4c4"].map(String::from);
+        let spec = Spec {
+            grid: "....1...........".into(),
+            extra_regions: vec![region.to_vec()],
+            ..spec
+        };
+        let board = spec.board::<4>().unwrap();
+        assert!(board.sees(4, 15));
+        assert!(!board.candidates((3, 3)).unwrap().contains(&1));
+    }
+
+    #[test]
+    fn invalid() {
+        let spec = |json: &str| {
+            serde_json::from_str::<Spec>(json).unwrap().board::<9>()
+        };
+        let grid = format!(r#""grid": "{}""#, ".".repeat(81));
+
+        let variant = format!(r#"{{{}, "variant": ["y"]}}"#, grid);
+        assert_eq!(spec(&variant).err(), Some(Error::InvalidSpec("variant")));
+        let cage = format!(r#"{{{}, "cages": [{{"sum": 3, "cells": ["a1"]}}]}}"#, grid);
+        assert_eq!(spec(&cage).err(), Some(Error::InvalidSpec("cages")));
+        let thermo = format!(r#"{{{}, "thermometers": [["r1c1", "r10c1"]]}}"#, grid);
+        assert_eq!(spec(&thermo).err(), Some(Error::InvalidSpec("thermometers")));
+
+        assert!(serde_json::from_str::<Spec>(r#"{"grid": "", "colors": []}"#).is_err());
+    }
+}
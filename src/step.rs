@@ -0,0 +1,384 @@
+use std::{borrow::Cow, fmt};
+
+use crate::killer::parse_cell;
+use crate::parse::ParseError;
+use crate::sudoku::{Board, Field, MAX_SIZE};
+use crate::unit::Unit;
+
+/// Why a value got placed or a candidate eliminated
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum Reason {
+    /// Part of the puzzle
+    Given,
+    /// Placed with [`Board::set`](crate::Board::set)
+    Set,
+    /// The only candidate left in the cell
+    SoleCandidate,
+    /// The only cell of the unit that can take the value
+    HiddenSingle(Unit),
+    /// The candidates of the box are confined to one row or column and can
+    /// be removed from the rest of that line
+    Pointing(Unit),
+    /// The cell sees both ends of a remote pair chain
+    RemotePair,
+    /// No combination of the cage adding up to its sum uses the candidate
+    CageSum,
+    /// The values of the thermometer can't increase along it with the
+    /// candidate
+    Thermometer,
+    /// The arrow can't add up to its circle with the candidate
+    ArrowSum,
+    /// The candidate leads to a contradiction
+    ForcingChain,
+    /// Assumed by a forcing chain to find a contradiction
+    Assumption,
+    /// Guessed by backtracking
+    Guess,
+    /// Found by the exact cover solver
+    ExactCover,
+    /// Found by the bit parallel engine of
+    /// [`Board::solve_fast`](crate::Board::solve_fast)
+    BitBoard,
+    /// Known from another grid of a [`Samurai`](crate::Samurai) that shares
+    /// the cell
+    Overlap,
+    /// Placed by a custom strategy with this name
+    Custom(Cow<'static, str>),
+}
+
+impl fmt::Display for Reason {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Reason::Given => f.write_str("given"),
+            Reason::Set => f.write_str("known in advance"),
+            Reason::SoleCandidate => f.write_str("sole candidate"),
+            Reason::HiddenSingle(unit) => write!(f, "hidden single in {}", unit),
+            Reason::Pointing(unit) => write!(f, "pointing candidates in {}", unit),
+            Reason::RemotePair => f.write_str("remote pair"),
+            Reason::CageSum => f.write_str("cage sum"),
+            Reason::Thermometer => f.write_str("thermometer"),
+            Reason::ArrowSum => f.write_str("arrow sum"),
+            Reason::ForcingChain => f.write_str("forcing chain"),
+            Reason::Assumption => f.write_str("assumption"),
+            Reason::Guess => f.write_str("guess"),
+            Reason::ExactCover => f.write_str("exact cover"),
+            Reason::BitBoard => f.write_str("bit boards"),
+            Reason::Overlap => f.write_str("overlapping grid"),
+            Reason::Custom(name) => f.write_str(name),
+        }
+    }
+}
+
+/// A cell `(row, column)` shown as `r3c5` for the cell in row 3 and column 5,
+/// both counted from 1
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub struct CellName(pub (usize, usize));
+
+impl fmt::Display for CellName {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let (row, col) = self.0;
+        write!(f, "r{}c{}", row + 1, col + 1)
+    }
+}
+
+impl std::str::FromStr for CellName {
+    type Err = ParseError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        parse_cell(s.trim()).map(CellName).ok_or(ParseError::InvalidCellName)
+    }
+}
+
+/// What a step did to the cell
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum Action {
+    /// The value got placed in the cell
+    Place,
+    /// The value got removed from the candidates of the cell
+    Eliminate,
+}
+
+/// A recorded placement or elimination
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Step {
+    /// Position `(row, column)` of the cell
+    pub cell: (usize, usize),
+    /// The placed or eliminated value
+    pub value: u8,
+    /// Whether the value got placed or eliminated
+    pub action: Action,
+    /// Why the value got placed or eliminated
+    pub reason: Reason,
+}
+
+impl fmt::Display for Step {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let op = match self.action {
+            Action::Place => '=',
+            Action::Eliminate => '≠',
+        };
+
+        let cell = CellName(self.cell);
+        write!(f, "{} {} {} ({}", cell, op, self.value, self.reason)?;
+        match (self.action, &self.reason) {
+            (Action::Place, Reason::SoleCandidate) => {
+                write!(f, ": {} can only hold {})", cell, self.value)
+            }
+            (Action::Place, Reason::HiddenSingle(_)) => {
+                write!(f, ": {} can only go in {})", self.value, cell)
+            }
+            _ => f.write_str(")"),
+        }
+    }
+}
+
+/// The cells of a board after a recorded step, see
+/// [`Board::record_snapshots`]
+///
+/// Every cell takes one number in reading order: the bits `v - 1` of the
+/// candidates `v` of an unsolved cell or the value of a solved one with the
+/// top bit set.
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct GridState(Vec<u64>);
+
+const SOLVED: u64 = 1 << 63;
+
+impl GridState {
+    pub(crate) fn new<const N: usize>(board: &Board<N>) -> Self {
+        GridState((0..N * N)
+            .map(|idx| match board.value(idx) {
+                0 => board.mask(idx),
+                v => SOLVED | u64::from(v),
+            })
+            .collect())
+    }
+
+    /// The value of the cell `idx` or `None` if it is unsolved
+    pub fn value(&self, idx: usize) -> Option<u8> {
+        let cell = self.0[idx];
+        (cell & SOLVED != 0).then_some(cell as u8)
+    }
+
+    /// The candidates of the cell `idx` as bit mask with the bit `v - 1` for
+    /// the candidate `v`; 0 for a solved cell
+    pub fn mask(&self, idx: usize) -> u64 {
+        let cell = self.0[idx];
+        if cell & SOLVED == 0 { cell } else { 0 }
+    }
+
+    /// The cell `idx`
+    pub fn field(&self, idx: usize) -> Field {
+        if let Some(v) = self.value(idx) {
+            return Field::Value(v);
+        }
+
+        let mut fld = Field::with_all_options(MAX_SIZE as u8);
+        if let Field::Options(opts) = &mut fld {
+            opts.retain(|v| self.0[idx] & 1 << (v - 1) != 0);
+        }
+        fld
+    }
+}
+
+/// An entry of the search log of a board, see [`Board::record_log`]
+///
+/// The guesses nest: the steps after a guess at depth 1 build on it until the
+/// backtrack to before it, a guess at depth 2 on both of them.
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(rename_all = "kebab-case"))]
+pub enum LogEntry {
+    /// A placement or elimination by logic or given
+    Step(Step),
+    /// Backtracking guessed the value of the cell `(row, column)` after the
+    /// logic got stuck; the depth counts the open guesses from 1 on
+    Guess { cell: (usize, usize), value: u8, depth: usize },
+    /// Solving on from the guess at the depth ran into a contradiction
+    Contradiction { depth: usize },
+    /// The board went back to the state before the guess at the depth
+    Backtrack { depth: usize },
+}
+
+impl fmt::Display for LogEntry {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            LogEntry::Step(step) => step.fmt(f),
+            LogEntry::Guess { cell, value, depth } => {
+                write!(f, "guess {} = {} at depth {}", CellName(*cell), value, depth)
+            }
+            LogEntry::Contradiction { depth } => {
+                write!(f, "contradiction at depth {}", depth)
+            }
+            LogEntry::Backtrack { depth } => {
+                write!(f, "back to before the guess at depth {}", depth)
+            }
+        }
+    }
+}
+
+/// Progress of [`Solver::solve_with`](crate::Solver::solve_with)
+#[derive(Clone, Copy, Debug)]
+pub enum Event<'a, const N: usize = 9> {
+    /// A value was placed or a candidate eliminated
+    Step(&'a Step),
+    /// Backtracking reverted the board after a wrong guess
+    Restore(&'a Board<N>),
+}
+
+/// The next move found by [`Solver::next_hint`](crate::Solver::next_hint)
+#[derive(Clone, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum Hint {
+    /// Place a value
+    Place(Step),
+    /// Remove the candidates `((row, column), value)` for `reason`
+    Eliminate {
+        reason: Reason,
+        candidates: Vec<((usize, usize), u8)>,
+    },
+}
+
+impl fmt::Display for Hint {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Hint::Place(step) => write!(f, "{}", step),
+            Hint::Eliminate { reason, candidates } => {
+                f.write_str("remove ")?;
+                for (i, ((row, col), val)) in candidates.iter().enumerate() {
+                    if i != 0 {
+                        f.write_str(", ")?;
+                    }
+                    write!(f, "{} from {}", val, CellName((*row, *col)))?;
+                }
+                write!(f, " ({})", reason)
+            }
+        }
+    }
+}
+
+impl Hint {
+    /// The move with a sentence why it is right, e.g. for players
+    pub fn explain(&self) -> String {
+        match self {
+            Hint::Place(step) => {
+                let why = match &step.reason {
+                    Reason::SoleCandidate => {
+                        "every other value already occurs in its row, column or box"
+                            .to_string()
+                    }
+                    Reason::HiddenSingle(unit) => {
+                        format!("no other cell of {} can take {}", unit, step.value)
+                    }
+                    reason => reason.to_string(),
+                };
+
+                format!("Place {} at {}: {}", step.value, CellName(step.cell), why)
+            }
+            Hint::Eliminate { reason, candidates } => {
+                let why = match reason {
+                    Reason::Pointing(unit) => format!(
+                        "the cells of {} that can take the value lie in one row or \
+                         column, so the rest of that line can't",
+                        unit
+                    ),
+                    Reason::RemotePair => {
+                        "the cells see both ends of a remote pair chain".to_string()
+                    }
+                    Reason::CageSum => {
+                        "no combination of different values adding up to the sum \
+                         of the cage uses them"
+                            .to_string()
+                    }
+                    Reason::Thermometer => {
+                        "the values of the thermometer couldn't increase from the \
+                         bulb to the tip"
+                            .to_string()
+                    }
+                    Reason::ArrowSum => {
+                        "the cells of the arrow couldn't add up to the value in its \
+                         circle"
+                            .to_string()
+                    }
+                    Reason::ForcingChain => {
+                        "each candidate leads to a contradiction".to_string()
+                    }
+                    reason => reason.to_string(),
+                };
+
+                let cells = candidates.iter()
+                    .map(|&(cell, val)| format!("{} from {}", val, CellName(cell)))
+                    .collect::<Vec<_>>();
+                format!("Remove {}: {}", cells.join(", "), why)
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn display() {
+        assert_eq!(
+            Reason::HiddenSingle(Unit::Box(2)).to_string(),
+            "hidden single in box 3"
+        );
+
+        let step = Step {
+            cell: (1, 1),
+            value: 3,
+            action: Action::Place,
+            reason: Reason::SoleCandidate,
+        };
+        assert_eq!(step.to_string(), "r2c2 = 3 (sole candidate: r2c2 can only hold 3)");
+        assert_eq!(Hint::Place(step.clone()).to_string(), step.to_string());
+        assert_eq!(
+            Hint::Place(step).explain(),
+            "Place 3 at r2c2: every other value already occurs in its row, column or \
+             box"
+        );
+
+        let step = Step {
+            cell: (2, 4),
+            value: 2,
+            action: Action::Place,
+            reason: Reason::HiddenSingle(Unit::Box(1)),
+        };
+        assert_eq!(
+            step.to_string(),
+            "r3c5 = 2 (hidden single in box 2: 2 can only go in r3c5)"
+        );
+
+        let step = Step {
+            cell: (8, 8),
+            value: 2,
+            action: Action::Eliminate,
+            reason: Reason::Pointing(Unit::Box(8)),
+        };
+        assert_eq!(step.to_string(), "r9c9 ≠ 2 (pointing candidates in box 9)");
+
+        let hint = Hint::Eliminate {
+            reason: Reason::RemotePair,
+            candidates: vec![((0, 0), 1), ((8, 8), 2)],
+        };
+        assert_eq!(
+            hint.to_string(),
+            "remove 1 from r1c1, 2 from r9c9 (remote pair)"
+        );
+        assert_eq!(
+            hint.explain(),
+            "Remove 1 from r1c1, 2 from r9c9: the cells see both ends of a remote pair \
+             chain"
+        );
+        assert_eq!(Reason::Custom("eights".into()).to_string(), "eights");
+
+        assert_eq!("R4c7".parse(), Ok(CellName((3, 6))));
+        assert_eq!(CellName((3, 6)).to_string(), "r4c7");
+        assert_eq!("r0c1".parse::<CellName>(), Err(ParseError::InvalidCellName));
+    }
+}
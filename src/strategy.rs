@@ -0,0 +1,1313 @@
+use std::{
+    fmt,
+    time::{Duration, Instant},
+};
+
+pub use crate::constraint::CustomConstraints;
+pub use crate::killer::CageSums;
+pub use crate::shape::{Arrows, Thermometers};
+use crate::step::{Action, Event, Hint, LogEntry, Reason};
+use crate::sudoku::{Board, Candidates, Field, SolveResult, MAX_SIZE};
+use crate::unit::Unit;
+
+/// How hard a technique is for a human
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
+#[cfg_attr(
+    feature = "serde",
+    derive(serde::Serialize, serde::Deserialize),
+    serde(rename_all = "lowercase")
+)]
+pub enum Difficulty {
+    Easy,
+    Medium,
+    Hard,
+    Expert,
+}
+
+impl Difficulty {
+    /// All levels, easiest first
+    pub const ALL: [Difficulty; 4] =
+        [Difficulty::Easy, Difficulty::Medium, Difficulty::Hard, Difficulty::Expert];
+
+    /// Lower case name of the level
+    pub fn name(self) -> &'static str {
+        match self {
+            Difficulty::Easy => "easy",
+            Difficulty::Medium => "medium",
+            Difficulty::Hard => "hard",
+            Difficulty::Expert => "expert",
+        }
+    }
+
+    /// The level called `name`
+    pub fn from_name(name: &str) -> Option<Self> {
+        Self::ALL.iter().copied().find(|d| d.name() == name)
+    }
+}
+
+impl fmt::Display for Difficulty {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str(self.name())
+    }
+}
+
+/// Which cell backtracking guesses the value of
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash)]
+pub enum CellOrder {
+    /// The first cell without value, row by row
+    First,
+    /// The cell with the fewest candidates left
+    #[default]
+    Fewest,
+}
+
+impl CellOrder {
+    /// All orders
+    pub const ALL: [CellOrder; 2] = [CellOrder::First, CellOrder::Fewest];
+
+    /// Lower case name of the order
+    pub fn name(self) -> &'static str {
+        match self {
+            CellOrder::First => "first",
+            CellOrder::Fewest => "fewest",
+        }
+    }
+
+    /// The order called `name`
+    pub fn from_name(name: &str) -> Option<Self> {
+        Self::ALL.iter().copied().find(|o| o.name() == name)
+    }
+}
+
+impl fmt::Display for CellOrder {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str(self.name())
+    }
+}
+
+/// In which order backtracking tries the candidates of a cell
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash)]
+pub enum ValueOrder {
+    /// The smallest value first
+    Ascending,
+    /// The value the fewest other cells of the units can take first, which
+    /// removes the fewest candidates
+    #[default]
+    LeastConstraining,
+}
+
+impl ValueOrder {
+    /// All orders
+    pub const ALL: [ValueOrder; 2] =
+        [ValueOrder::Ascending, ValueOrder::LeastConstraining];
+
+    /// Lower case name of the order
+    pub fn name(self) -> &'static str {
+        match self {
+            ValueOrder::Ascending => "ascending",
+            ValueOrder::LeastConstraining => "least-constraining",
+        }
+    }
+
+    /// The order called `name`
+    pub fn from_name(name: &str) -> Option<Self> {
+        Self::ALL.iter().copied().find(|o| o.name() == name)
+    }
+}
+
+impl fmt::Display for ValueOrder {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str(self.name())
+    }
+}
+
+/// A solving technique for boards of size `N`
+pub trait Strategy<const N: usize = 9> {
+    /// Name of the technique
+    fn name(&self) -> &'static str;
+
+    /// How hard the technique is; unknown techniques count as expert ones
+    fn difficulty(&self) -> Difficulty {
+        Difficulty::Expert
+    }
+
+    /// The relative effort of applying the technique, by which
+    /// [`Solver::schedule`] orders the pipeline; 100 by default, twice that
+    /// of forcing chains with one assumption
+    fn cost(&self) -> u32 {
+        100
+    }
+
+    /// Applies the technique once and returns whether the board changed
+    fn apply(&self, board: &mut Board<N>) -> bool;
+
+    /// Applies the technique like [`apply`](Self::apply), but only where
+    /// cells changed after the [`generation`](Board::generation) `since`,
+    /// when the technique found nothing the last time; the whole board by
+    /// default
+    fn apply_changed(&self, board: &mut Board<N>, since: u32) -> bool {
+        let _ = since;
+        self.apply(board)
+    }
+}
+
+/// Selection of the techniques used by [`Solver::with_config`]
+#[derive(Clone, Debug, Default)]
+pub struct SolverConfig {
+    /// Maximum nesting of assumptions made by forcing chains; 0 disables them
+    pub forcing_chain_depth: usize,
+    /// Guess values with a depth-first search if the logic gets stuck
+    pub backtracking: bool,
+    /// The cell to guess by backtracking
+    pub cell_order: CellOrder,
+    /// The order of the values to guess by backtracking
+    pub value_order: ValueOrder,
+    /// Names of the built-in techniques to use; `None` selects all of them
+    pub enabled: Option<Vec<String>>,
+    /// Names of the built-in techniques to leave out
+    pub disabled: Vec<String>,
+    /// Leave out the techniques harder than this
+    pub max_difficulty: Option<Difficulty>,
+    /// Give up after this many rounds of the techniques and guesses
+    pub max_iterations: Option<usize>,
+    /// Give up after this time
+    pub timeout: Option<Duration>,
+}
+
+/// Sets cells with only one candidate left
+pub struct SoleOption;
+
+impl<const N: usize> Strategy<N> for SoleOption {
+    fn name(&self) -> &'static str {
+        "sole option"
+    }
+
+    fn difficulty(&self) -> Difficulty {
+        Difficulty::Easy
+    }
+
+    fn cost(&self) -> u32 {
+        1
+    }
+
+    fn apply(&self, board: &mut Board<N>) -> bool {
+        self.apply_changed(board, 0)
+    }
+
+    fn apply_changed(&self, board: &mut Board<N>, since: u32) -> bool {
+        let mut changed = false;
+        for idx in 0..N * N {
+            if !board.is_changed_since(idx, since) {
+                continue;
+            }
+
+            // an earlier placement may have taken the last candidate
+            let mask = board.mask(idx);
+            if mask.count_ones() == 1 {
+                let val = mask.trailing_zeros() as u8 + 1;
+                board.set_idx(idx, val, Reason::SoleCandidate);
+                changed = true;
+            }
+        }
+
+        changed
+    }
+}
+
+/// Sets values that fit in only one cell of a row, column or square and
+/// removes candidates that are confined to one row or column of a unit from
+/// the rest of that line
+pub struct Neighbourhood;
+
+impl Neighbourhood {
+    fn solve_unit<const N: usize>(&self, board: &mut Board<N>, unit: Unit) -> bool {
+        // the cells of each value and their number
+        let mut list = [[0; N]; N];
+        let mut len = [0; N];
+        let mut changed = false;
+
+        for idx in board.unit_cells(unit) {
+            let mut mask = board.mask(idx);
+            while mask != 0 {
+                let i = mask.trailing_zeros() as usize;
+                mask &= mask - 1;
+                list[i][len[i]] = idx;
+                len[i] += 1;
+            }
+        }
+
+        for (num, e) in list.iter().enumerate() {
+            let e = &e[..len[num]];
+            let num = num as u8 + 1;
+
+            match e.len() {
+                0 => (),
+                1 => {
+                    // an earlier placement in this unit may have taken the cell
+                    if board.value(e[0]) == 0 {
+                        board.set_idx(e[0], num, Reason::HiddenSingle(unit));
+                        changed = true;
+                    }
+                }
+                _ => {
+                    let mut it = e.iter();
+                    let (row, col) = it.next().map(|&x| Board::<N>::pos(x)).unwrap();
+
+                    let mut sole_row = true;
+                    let mut sole_col = true;
+                    for x in it {
+                        if x / N != row {
+                            sole_row = false;
+                        }
+
+                        if x % N != col {
+                            sole_col = false;
+                        }
+                    }
+
+                    let reason = Reason::Pointing(unit);
+
+                    if sole_row {
+                        for idx in (0..N).map(|c| row * N + c) {
+                            if !e.contains(&idx) {
+                                changed |= board.eliminate(idx, num, reason.clone());
+                            }
+                        }
+                    }
+
+                    if sole_col {
+                        for idx in (0..N).map(|r| r * N + col) {
+                            if !e.contains(&idx) {
+                                changed |= board.eliminate(idx, num, reason.clone());
+                            }
+                        }
+                    }
+                }
+            }
+        }
+
+        changed
+    }
+}
+
+impl<const N: usize> Strategy<N> for Neighbourhood {
+    fn name(&self) -> &'static str {
+        "sole position by neighbours"
+    }
+
+    fn difficulty(&self) -> Difficulty {
+        Difficulty::Medium
+    }
+
+    fn cost(&self) -> u32 {
+        2
+    }
+
+    fn apply(&self, board: &mut Board<N>) -> bool {
+        self.apply_changed(board, 0)
+    }
+
+    fn apply_changed(&self, board: &mut Board<N>, since: u32) -> bool {
+        let mut changed = false;
+
+        for unit in board.units() {
+            if board.unit_cells(unit).any(|idx| board.is_changed_since(idx, since)) {
+                changed |= self.solve_unit(board, unit);
+            }
+        }
+
+        changed
+    }
+}
+
+/// Eliminates candidates by remote pairs
+///
+/// A remote pair is a chain of bivalue cells with the same two candidates
+/// where each cell sees the next one. The values alternate along the chain,
+/// hence a cell seeing two chain cells of different parity can hold none of
+/// the two values.
+pub struct RemotePairs;
+
+impl<const N: usize> Strategy<N> for RemotePairs {
+    fn name(&self) -> &'static str {
+        "remote pairs"
+    }
+
+    fn difficulty(&self) -> Difficulty {
+        Difficulty::Hard
+    }
+
+    fn cost(&self) -> u32 {
+        10
+    }
+
+    fn apply(&self, board: &mut Board<N>) -> bool {
+        // the candidates of a cell with exactly two of them as bit mask
+        let pair_of = |mask: u64| (mask.count_ones() == 2).then_some(mask);
+
+        let len = N * N;
+        let mut visited = [[false; N]; N];
+        let visited = visited.as_flattened_mut();
+        // the cells of a chain with their parity
+        let mut buffer = [[(0, false); N]; N];
+        let buffer = buffer.as_flattened_mut();
+        let mut changed = false;
+
+        for start in 0..len {
+            let pair = match pair_of(board.mask(start)) {
+                Some(p) if !visited[start] => p,
+                _ => continue,
+            };
+
+            // collect the chain by colouring the connected cells alternately
+            buffer[0] = (start, false);
+            let mut chain_len = 1;
+            visited[start] = true;
+            let mut bipartite = true;
+            let mut i = 0;
+            while i < chain_len {
+                let (idx, parity) = buffer[i];
+                i += 1;
+
+                for &other in board.neighbours(idx) {
+                    if pair_of(board.mask(other)) != Some(pair) {
+                        continue;
+                    }
+
+                    match buffer[..chain_len].iter().find(|(x, _)| *x == other) {
+                        Some((_, p)) if *p == parity => bipartite = false,
+                        Some(_) => (),
+                        None => {
+                            visited[other] = true;
+                            buffer[chain_len] = (other, !parity);
+                            chain_len += 1;
+                        }
+                    }
+                }
+            }
+
+            let chain = &buffer[..chain_len];
+            if !bipartite || chain.len() < 4 {
+                continue;
+            }
+
+            let low = pair.trailing_zeros() as u8 + 1;
+            let high = 64 - pair.leading_zeros() as u8;
+            for idx in 0..len {
+                if chain.iter().any(|(x, _)| *x == idx) {
+                    continue;
+                }
+
+                let sees_even = chain.iter().any(|&(x, p)| !p && board.sees(idx, x));
+                let sees_odd = chain.iter().any(|&(x, p)| p && board.sees(idx, x));
+                if sees_even && sees_odd {
+                    changed |= board.eliminate(idx, low, Reason::RemotePair);
+                    changed |= board.eliminate(idx, high, Reason::RemotePair);
+                }
+            }
+        }
+
+        changed
+    }
+}
+
+/// Assumes a candidate, propagates it and drops the candidate if this leads
+/// to a contradiction
+pub struct ForcingChains {
+    /// Maximum number of nested assumptions
+    pub depth: usize,
+}
+
+impl<const N: usize> Strategy<N> for ForcingChains {
+    fn name(&self) -> &'static str {
+        "forcing chains"
+    }
+
+    fn difficulty(&self) -> Difficulty {
+        Difficulty::Expert
+    }
+
+    fn cost(&self) -> u32 {
+        50 * self.depth as u32
+    }
+
+    fn apply(&self, board: &mut Board<N>) -> bool {
+        if self.depth == 0 {
+            return false;
+        }
+
+        let solver = Solver::<N>::with_config(&SolverConfig {
+            forcing_chain_depth: self.depth - 1,
+            ..Default::default()
+        });
+
+        for idx in 0..N * N {
+            let opts = match board.field_at(idx) {
+                Field::Options(opts) => opts,
+                Field::Value(_) => continue,
+            };
+
+            for val in opts {
+                let mut trial = board.clone();
+                trial.record_steps(false);
+                trial.set_idx(idx, val, Reason::Assumption);
+                solver.solve(&mut trial);
+
+                if trial.has_contradiction() {
+                    return board.eliminate(idx, val, Reason::ForcingChain);
+                }
+            }
+        }
+
+        false
+    }
+}
+
+/// A pipeline of strategies
+///
+/// The strategies run in order and the solver starts over with the first one
+/// as soon as one changes the board, such that expensive techniques are tried
+/// only if the cheaper ones made no progress; [`Solver::schedule`] orders them
+/// by cost.
+pub struct Solver<const N: usize = 9> {
+    strategies: Vec<Box<dyn Strategy<N>>>,
+    backtracking: bool,
+    cell_order: CellOrder,
+    value_order: ValueOrder,
+    max_iterations: Option<usize>,
+    timeout: Option<Duration>,
+}
+
+/// Counters of [`Solver::solve_with_stats`]
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+pub struct Stats {
+    /// Rounds of the strategies and guesses
+    pub rounds: usize,
+    /// The counters of each technique, in the order of their first use;
+    /// guesses count as `"backtracking"`
+    pub techniques: Vec<TechniqueStats>,
+    /// The highest number of candidates left on the board at the start of a
+    /// round
+    pub peak_candidates: usize,
+}
+
+/// Counters of one technique in [`Stats`]
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+pub struct TechniqueStats {
+    pub name: &'static str,
+    /// The level of the technique; `None` for backtracking, which is harder
+    /// than every technique
+    pub difficulty: Option<Difficulty>,
+    pub placements: usize,
+    pub eliminations: usize,
+    /// How often the technique got applied, also without progress
+    pub uses: usize,
+    /// The time of all uses
+    #[cfg_attr(
+        feature = "serde",
+        serde(rename = "seconds", serialize_with = "seconds")
+    )]
+    pub time: Duration,
+}
+
+#[cfg(feature = "serde")]
+fn seconds<S: serde::Serializer>(
+    time: &Duration, serializer: S
+) -> Result<S::Ok, S::Error> {
+    serializer.serialize_f64(time.as_secs_f64())
+}
+
+impl Stats {
+    /// The hardest technique that placed a value or eliminated a candidate,
+    /// the first one used of equally hard ones; the difficulty of the puzzle
+    /// for the solver
+    pub fn hardest(&self) -> Option<&TechniqueStats> {
+        self.techniques.iter()
+            .rev()
+            .filter(|t| t.placements + t.eliminations > 0)
+            .max_by_key(|t| (t.difficulty.is_none(), t.difficulty))
+    }
+
+    // Counts a use of the technique `name` of the level `difficulty` that took
+    // `time` and the steps it recorded since `start`
+    fn count<const N: usize>(
+        &mut self, name: &'static str, difficulty: Option<Difficulty>, board: &Board<N>,
+        start: usize, time: Duration
+    ) {
+        let pos = match self.techniques.iter().position(|t| t.name == name) {
+            Some(pos) => pos,
+            None => {
+                self.techniques.push(TechniqueStats {
+                    name,
+                    difficulty,
+                    ..Default::default()
+                });
+                self.techniques.len() - 1
+            }
+        };
+
+        let technique = &mut self.techniques[pos];
+        technique.uses += 1;
+        technique.time += time;
+
+        let steps = match board.steps() {
+            Some(steps) if steps.len() > start => &steps[start..],
+            _ => return,
+        };
+        for step in steps {
+            match step.action {
+                Action::Place => technique.placements += 1,
+                Action::Eliminate => technique.eliminations += 1,
+            }
+        }
+    }
+}
+
+// The number of strategies whose last use a run tracks; the others always
+// work on the whole board
+const TRACKED: usize = 32;
+
+// The limits and counters of a run of the solver
+struct Run {
+    deadline: Option<Instant>,
+    // whether the stats count the uses of the techniques
+    counting: bool,
+    stats: Stats,
+    // the generation of the board at the last use of each strategy
+    used: [u32; TRACKED],
+    // the number of open guesses
+    depth: usize,
+}
+
+impl Run {
+    // The generation of the board at the last use of the strategy `i`, 0 if
+    // unknown, and records the current one
+    fn begin(&mut self, i: usize, board_generation: u32) -> u32 {
+        match self.used.get_mut(i) {
+            Some(used) => std::mem::replace(used, board_generation),
+            None => 0,
+        }
+    }
+}
+
+impl<const N: usize> Solver<N> {
+    /// A solver without any strategy
+    pub fn new() -> Self {
+        Solver {
+            strategies: Vec::new(),
+            backtracking: false,
+            cell_order: CellOrder::default(),
+            value_order: ValueOrder::default(),
+            max_iterations: None,
+            timeout: None,
+        }
+    }
+
+    /// The built-in strategies, cheapest first, selected by `config`
+    pub fn with_config(config: &SolverConfig) -> Self {
+        let mut solver = Self::new();
+        solver.push(SoleOption);
+        solver.push(Neighbourhood);
+        solver.push(CageSums);
+        solver.push(Thermometers);
+        solver.push(Arrows);
+        solver.push(CustomConstraints);
+        solver.push(RemotePairs);
+
+        if config.forcing_chain_depth > 0 {
+            solver.push(ForcingChains { depth: config.forcing_chain_depth });
+        }
+
+        solver.strategies.retain(|s| {
+            let enabled = config.enabled.as_ref()
+                .is_none_or(|names| names.iter().any(|name| name == s.name()));
+
+            enabled
+                && !config.disabled.iter().any(|name| name == s.name())
+                && config.max_difficulty.is_none_or(|max| s.difficulty() <= max)
+        });
+        solver.schedule();
+        solver.backtracking(config.backtracking);
+        solver.heuristics(config.cell_order, config.value_order);
+        solver.max_iterations(config.max_iterations);
+        solver.timeout(config.timeout);
+
+        solver
+    }
+
+    /// Appends a strategy to the pipeline
+    pub fn push(&mut self, strategy: impl Strategy<N> + 'static) {
+        self.strategies.push(Box::new(strategy));
+    }
+
+    /// Inserts a strategy at position `index` of the pipeline
+    pub fn insert(&mut self, index: usize, strategy: impl Strategy<N> + 'static) {
+        self.strategies.insert(index, Box::new(strategy));
+    }
+
+    /// Removes the strategy called `name` from the pipeline
+    pub fn remove(&mut self, name: &str) -> Option<Box<dyn Strategy<N>>> {
+        let pos = self.strategies.iter().position(|s| s.name() == name)?;
+        Some(self.strategies.remove(pos))
+    }
+
+    /// Orders the strategies by their [cost](Strategy::cost), cheapest first
+    /// and otherwise as they were
+    pub fn schedule(&mut self) {
+        self.strategies.sort_by_key(|s| s.cost());
+    }
+
+    /// The strategies in the order they get applied
+    pub fn strategies(&self) -> &[Box<dyn Strategy<N>>] {
+        &self.strategies
+    }
+
+    /// Enables guessing if the strategies get stuck
+    pub fn backtracking(&mut self, enable: bool) {
+        self.backtracking = enable;
+    }
+
+    /// Selects the cell and the order of its values to guess by backtracking
+    pub fn heuristics(&mut self, cell: CellOrder, value: ValueOrder) {
+        self.cell_order = cell;
+        self.value_order = value;
+    }
+
+    /// Limits the number of rounds of the strategies and guesses; the solver
+    /// gives up and reports [`SolveResult::IterationLimit`] if they are used up
+    pub fn max_iterations(&mut self, limit: Option<usize>) {
+        self.max_iterations = limit;
+    }
+
+    /// Limits the time for solving; it gets checked between the rounds of the
+    /// strategies and guesses and the solver reports [`SolveResult::TimeLimit`]
+    /// when it is over
+    pub fn timeout(&mut self, limit: Option<Duration>) {
+        self.timeout = limit;
+    }
+
+    fn start(&self) -> Run {
+        Run {
+            deadline: self.timeout.map(|limit| Instant::now() + limit),
+            counting: false,
+            stats: Stats::default(),
+            used: [0; TRACKED],
+            depth: 0,
+        }
+    }
+
+    // The result for the limit that is used up, if any
+    fn exhausted(&self, run: &Run) -> Option<SolveResult> {
+        if self.max_iterations.is_some_and(|max| run.stats.rounds >= max) {
+            Some(SolveResult::IterationLimit)
+        } else if run.deadline.is_some_and(|deadline| Instant::now() >= deadline) {
+            Some(SolveResult::TimeLimit)
+        } else {
+            None
+        }
+    }
+
+    /// Solves `board` as far as possible
+    pub fn solve(&self, board: &mut Board<N>) -> SolveResult {
+        self.run(board, &mut |_| (), &mut self.start())
+    }
+
+    /// Solves `board` like [`solve`](Self::solve) and passes every placement,
+    /// elimination and backtracking to `observer` as soon as the technique that
+    /// made it returns
+    pub fn solve_with(
+        &self, board: &mut Board<N>, mut observer: impl FnMut(Event<N>)
+    ) -> SolveResult {
+        let recording = board.steps().is_some();
+        if !recording {
+            board.record_steps(true);
+        }
+
+        let result = self.run(board, &mut observer, &mut self.start());
+
+        if !recording {
+            board.record_steps(false);
+        }
+
+        result
+    }
+
+    /// Solves `board` like [`solve`](Self::solve) and counts the work of the
+    /// techniques
+    pub fn solve_with_stats(&self, board: &mut Board<N>) -> (SolveResult, Stats) {
+        let recording = board.steps().is_some();
+        if !recording {
+            board.record_steps(true);
+        }
+
+        let mut run = Run { counting: true, ..self.start() };
+        let result = self.run(board, &mut |_| (), &mut run);
+
+        if !recording {
+            board.record_steps(false);
+        }
+
+        (result, run.stats)
+    }
+
+    fn run(
+        &self, board: &mut Board<N>, observer: &mut dyn FnMut(Event<N>),
+        run: &mut Run
+    ) -> SolveResult {
+        'restart: loop {
+            if let Some(limit) = self.exhausted(run) {
+                return match board.status() {
+                    SolveResult::Stuck => limit,
+                    status => status,
+                };
+            }
+
+            let stats = &mut run.stats;
+            stats.rounds += 1;
+            let candidates = (0..N * N)
+                .map(|idx| board.mask(idx).count_ones() as usize)
+                .sum();
+            stats.peak_candidates = stats.peak_candidates.max(candidates);
+
+            // a strategy looks only at the cells changed since its last use
+            for (i, strategy) in self.strategies.iter().enumerate() {
+                let start = step_count(board);
+                let since = run.begin(i, board.generation());
+                let clock = run.counting.then(Instant::now);
+                let changed = strategy.apply_changed(board, since);
+                notify(board, start, observer);
+                if let Some(clock) = clock {
+                    let (name, level) = (strategy.name(), Some(strategy.difficulty()));
+                    run.stats.count(name, level, board, start, clock.elapsed());
+                }
+
+                if changed {
+                    if board.has_contradiction() {
+                        break 'restart;
+                    }
+
+                    continue 'restart;
+                }
+            }
+
+            break;
+        }
+
+        let result = board.status();
+        if self.backtracking && result == SolveResult::Stuck {
+            self.solve_backtracking(board, observer, run)
+        } else {
+            result
+        }
+    }
+
+    /// Finds the next placement or elimination without changing `board`;
+    /// guessing is never used for hints
+    pub fn next_hint(&self, board: &Board<N>) -> Option<Hint> {
+        if board.has_contradiction() {
+            return None;
+        }
+
+        for strategy in &self.strategies {
+            let mut trial = board.clone();
+            trial.record_steps(true);
+
+            if !strategy.apply(&mut trial) {
+                continue;
+            }
+
+            let steps = trial.steps().as_deref().unwrap_or(&[]);
+            let first = match steps.first() {
+                Some(step) if step.action == Action::Place => {
+                    return Some(Hint::Place(step.clone()));
+                }
+                Some(step) => step,
+                None => continue,
+            };
+
+            let candidates = steps.iter()
+                .take_while(|s| {
+                    s.action == Action::Eliminate && s.reason == first.reason
+                })
+                .map(|s| (s.cell, s.value))
+                .collect();
+
+            return Some(Hint::Eliminate { reason: first.reason.clone(), candidates });
+        }
+
+        None
+    }
+
+    fn solve_backtracking(
+        &self, board: &mut Board<N>, observer: &mut dyn FnMut(Event<N>),
+        run: &mut Run
+    ) -> SolveResult {
+        let mut cells = (0..N * N).filter(|&idx| board.value(idx) == 0);
+        let cell = match self.cell_order {
+            CellOrder::First => cells.next(),
+            CellOrder::Fewest => cells.min_by_key(|&idx| board.mask(idx).count_ones()),
+        };
+        let (idx, opts) = match cell {
+            Some(idx) => (idx, Candidates::from_mask(board.mask(idx))),
+            None => return board.status(),
+        };
+
+        // the values with the number of peers that can take them
+        let mut order = [(0, 0); MAX_SIZE];
+        for (i, val) in opts.into_iter().enumerate() {
+            order[i].1 = val;
+            if self.value_order == ValueOrder::LeastConstraining {
+                order[i].0 = board.neighbours(idx).iter()
+                    .filter(|&&peer| board.mask(peer) & 1 << (val - 1) != 0)
+                    .count();
+            }
+        }
+        order[..opts.len()].sort_unstable();
+
+        let snapshot = board.snapshot();
+        let depth = run.depth + 1;
+        for &(_, val) in &order[..opts.len()] {
+            let start = step_count(board);
+            let clock = run.counting.then(Instant::now);
+            let cell = (idx / N, idx % N);
+            board.log_entry(|| LogEntry::Guess { cell, value: val, depth });
+            board.set_idx(idx, val, Reason::Guess);
+            notify(board, start, observer);
+            if let Some(clock) = clock {
+                run.stats.count("backtracking", None, board, start, clock.elapsed());
+            }
+
+            run.depth = depth;
+            let result = self.run(board, observer, run);
+            run.depth = depth - 1;
+            match result {
+                SolveResult::Solved => return SolveResult::Solved,
+                SolveResult::Contradiction => {
+                    board.log_entry(|| LogEntry::Contradiction { depth })
+                }
+                SolveResult::Stuck
+                | SolveResult::TimeLimit
+                | SolveResult::IterationLimit => (),
+            }
+
+            board.restore(&snapshot);
+            board.log_entry(|| LogEntry::Backtrack { depth });
+            observer(Event::Restore(board));
+
+            if let Some(limit) = self.exhausted(run) {
+                return limit;
+            }
+        }
+
+        SolveResult::Contradiction
+    }
+}
+
+fn step_count<const N: usize>(board: &Board<N>) -> usize {
+    board.steps().as_ref().map_or(0, Vec::len)
+}
+
+// Passes the steps recorded since `start` to `observer`
+fn notify<const N: usize>(
+    board: &Board<N>, start: usize, observer: &mut dyn FnMut(Event<N>)
+) {
+    if let Some(steps) = board.steps() {
+        steps[start..].iter().for_each(|step| observer(Event::Step(step)));
+    }
+}
+
+impl<const N: usize> Default for Solver<N> {
+    fn default() -> Self {
+        Self::with_config(&SolverConfig::default())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::step::Step;
+    use std::alloc::{GlobalAlloc, Layout, System};
+    use std::cell::Cell;
+
+    // Counts the allocations of each thread
+    struct Counting;
+
+    thread_local! {
+        static ALLOCATIONS: Cell<usize> = const { Cell::new(0) };
+    }
+
+    unsafe impl GlobalAlloc for Counting {
+        unsafe fn alloc(&self, layout: Layout) -> *mut u8 {
+            let _ = ALLOCATIONS.try_with(|n| n.set(n.get() + 1));
+            System.alloc(layout)
+        }
+
+        unsafe fn dealloc(&self, ptr: *mut u8, layout: Layout) {
+            System.dealloc(ptr, layout)
+        }
+    }
+
+    #[global_allocator]
+    static ALLOCATOR: Counting = Counting;
+
+    struct Eights;
+
+    impl Strategy for Eights {
+        fn name(&self) -> &'static str {
+            "eights"
+        }
+
+        fn apply(&self, board: &mut Board) -> bool {
+            match board.fields().position(|f| matches!(f, Field::Options(_))) {
+                Some(idx) if idx < 9 => {
+                    let reason = Reason::Custom(self.name().into());
+                    board.set_idx(idx, 8 - idx as u8 + 1, reason);
+                    true
+                }
+                _ => false,
+            }
+        }
+    }
+
+    #[test]
+    fn apply_changed() {
+        let mut board: Board = Board::new();
+        (2..=9).for_each(|val| { board.eliminate(0, val, Reason::Set); });
+
+        let since = board.generation();
+        board.eliminate(80, 9, Reason::Set);
+        assert!(!SoleOption.apply_changed(&mut board, since));
+        assert!(!Neighbourhood.apply_changed(&mut board, since));
+
+        assert!(SoleOption.apply_changed(&mut board, 0));
+        assert_eq!(board.field((0, 0)), Field::Value(1));
+    }
+
+    #[test]
+    fn no_allocations() {
+        let puzzle = "..58..2.9.......3.......5..2..6..4.1.8...9....57....6.72..4.15\
+                      .....1..27..8..3...";
+        let solver = Solver::default();
+        // the first board of a size makes the shared tables
+        solver.solve(&mut puzzle.parse().unwrap());
+
+        let mut board: Board = puzzle.parse().unwrap();
+        let before = ALLOCATIONS.with(Cell::get);
+        assert_eq!(solver.solve(&mut board), SolveResult::Solved);
+        assert_eq!(ALLOCATIONS.with(Cell::get), before);
+    }
+
+    #[test]
+    fn remote_pairs() {
+        let mut board: Board = Board::new();
+        for idx in [0, 4, 3 * 9 + 4, 3 * 9 + 8] {
+            for val in 3..=9 {
+                board.eliminate(idx, val, Reason::Set);
+            }
+        }
+
+        board.record_steps(true);
+        assert!(RemotePairs.apply(&mut board));
+
+        let steps = board.steps().as_ref().unwrap();
+        assert!(steps.contains(&Step {
+            cell: (0, 8),
+            value: 1,
+            action: Action::Eliminate,
+            reason: Reason::RemotePair,
+        }));
+        assert!(steps.iter().all(|s| s.action == Action::Eliminate));
+
+        // r1c9 and r4c1 see both ends of the chain r1c1-r1c5-r4c5-r4c9
+        assert_eq!(
+            board.candidates((0, 8)).as_deref(),
+            Some(&[3, 4, 5, 6, 7, 8, 9][..])
+        );
+        assert_eq!(
+            board.candidates((3, 0)).as_deref(),
+            Some(&[3, 4, 5, 6, 7, 8, 9][..])
+        );
+        // r5c9 only sees r4c9
+        assert_eq!(board.candidate_count((4, 8)), 9);
+
+        assert!(!RemotePairs.apply(&mut board));
+    }
+
+    #[test]
+    fn next_hint() {
+        let mut board: Board = format!("12345678.{}", ".".repeat(72)).parse().unwrap();
+        let solver = Solver::default();
+
+        assert_eq!(
+            solver.next_hint(&board),
+            Some(Hint::Place(Step {
+                cell: (0, 8),
+                value: 9,
+                action: Action::Place,
+                reason: Reason::SoleCandidate,
+            }))
+        );
+        assert_eq!(board.candidate_count((0, 8)), 1);
+
+        let mut solver = Solver::new();
+        solver.push(RemotePairs);
+        assert_eq!(solver.next_hint(&board), None);
+
+        board = Board::new();
+        for idx in [0, 4, 3 * 9 + 4, 3 * 9 + 8] {
+            for val in 3..=9 {
+                board.eliminate(idx, val, Reason::Set);
+            }
+        }
+        match solver.next_hint(&board) {
+            Some(Hint::Eliminate { reason, candidates }) => {
+                assert_eq!(reason, Reason::RemotePair);
+                assert!(candidates.contains(&((0, 8), 1)));
+                assert!(candidates.contains(&((0, 8), 2)));
+                assert!(!candidates.contains(&((4, 8), 1)));
+            }
+            hint => panic!("unexpected hint {:?}", hint),
+        }
+    }
+
+    #[test]
+    fn custom_strategy() {
+        let mut solver = Solver::new();
+        solver.push(Eights);
+        assert_eq!(solver.strategies().len(), 1);
+
+        let mut board: Board = Board::new();
+        board.record_steps(true);
+        solver.solve(&mut board);
+
+        assert_eq!(board.steps().as_ref().unwrap().len(), 9);
+        assert!(board.steps().as_ref().unwrap().iter().all(|s| {
+            s.reason == Reason::Custom("eights".into())
+        }));
+        assert!(matches!(board.field((0, 0)), Field::Value(9)));
+
+        assert!(solver.remove("eights").is_some());
+        assert!(solver.remove("eights").is_none());
+    }
+
+    #[test]
+    fn schedule() {
+        let mut solver: Solver = Solver::new();
+        solver.push(ForcingChains { depth: 3 });
+        solver.push(RemotePairs);
+        solver.push(Eights);
+        solver.push(SoleOption);
+        solver.schedule();
+
+        let names = solver.strategies().iter().map(|s| s.name()).collect::<Vec<_>>();
+        assert_eq!(names, ["sole option", "remote pairs", "eights", "forcing chains"]);
+    }
+
+    #[test]
+    fn solve_with() {
+        let mut solver: Solver = Solver::default();
+        solver.backtracking(true);
+
+        let mut board: Board = Board::new();
+        let mut placed = Vec::new();
+        let result = solver.solve_with(&mut board, |event| match event {
+            Event::Step(step) if step.action == Action::Place => {
+                placed.push(step.clone())
+            }
+            Event::Step(_) => (),
+            Event::Restore(board) => {
+                placed.truncate(81 - board.fields().filter(|f| {
+                    matches!(f, Field::Options(_))
+                }).count())
+            }
+        });
+
+        assert_eq!(result, SolveResult::Solved);
+        // the empty board needs a guess to start
+        assert_eq!(placed[0].reason, Reason::Guess);
+        assert_eq!(placed.len(), 81);
+        assert!(board.steps().is_none());
+    }
+
+    #[test]
+    fn record_log() {
+        let mut board: Board = concat!(
+            "..58..2.9.......3.......5..2..6..4.1.8...9....57....6.72..4.15.....1",
+            "..27..8..3..."
+        ).parse().unwrap();
+        board.record_log(true);
+        let config = SolverConfig {
+            max_difficulty: Some(Difficulty::Medium),
+            backtracking: true,
+            cell_order: CellOrder::First,
+            ..SolverConfig::default()
+        };
+        assert_eq!(board.solve_with_config(&config), SolveResult::Solved);
+
+        // the guesses open and close in order and the logic goes first
+        let log = board.log().unwrap();
+        let first = log.iter().position(|e| matches!(e, LogEntry::Guess { .. }));
+        assert!(log[..first.unwrap()].iter().all(|e| matches!(e, LogEntry::Step(_))));
+        let mut depth = 0;
+        for entry in log {
+            match *entry {
+                LogEntry::Guess { depth: d, .. } => {
+                    assert_eq!(d, depth + 1);
+                    depth = d;
+                }
+                LogEntry::Contradiction { depth: d } => assert_eq!(d, depth),
+                LogEntry::Backtrack { depth: d } => {
+                    assert_eq!(d, depth);
+                    depth -= 1;
+                }
+                LogEntry::Step(_) => (),
+            }
+        }
+        assert!(depth > 0);
+        assert!(log.iter().any(|e| matches!(e, LogEntry::Backtrack { depth: 1 })));
+        assert!(log.iter().any(|e| matches!(e, LogEntry::Contradiction { .. })));
+        assert_eq!(
+            LogEntry::Guess { cell: (0, 5), value: 1, depth: 2 }.to_string(),
+            "guess r1c6 = 1 at depth 2"
+        );
+    }
+
+    #[test]
+    fn heuristics() {
+        let mut board: Board = Board::new();
+        board.set((4, 4), 1);
+        board.set((8, 1), 5);
+        board.set((5, 6), 7);
+        board.record_steps(true);
+
+        let first_guess = |cell, value| {
+            let mut solver: Solver = Solver::new();
+            solver.backtracking(true);
+            solver.heuristics(cell, value);
+
+            let mut board = board.clone();
+            assert_eq!(solver.solve(&mut board), SolveResult::Solved);
+            let steps = board.steps().clone().unwrap();
+            let step = steps.iter().find(|s| s.reason == Reason::Guess).unwrap();
+            (step.cell, step.value)
+        };
+
+        assert_eq!(first_guess(CellOrder::First, ValueOrder::Ascending), ((0, 0), 1));
+        assert_eq!(first_guess(CellOrder::Fewest, ValueOrder::Ascending), ((4, 1), 2));
+        // the cells of row 5 and the right box can't take 7
+        let least_constraining = ValueOrder::LeastConstraining;
+        assert_eq!(first_guess(CellOrder::Fewest, least_constraining), ((4, 1), 7));
+        assert_eq!(CellOrder::from_name("fewest"), Some(CellOrder::default()));
+        assert_eq!(ValueOrder::LeastConstraining.to_string(), "least-constraining");
+    }
+
+    #[test]
+    fn solve_with_stats() {
+        let mut solver: Solver = Solver::default();
+        solver.backtracking(true);
+
+        let mut board: Board = Board::new();
+        let (result, stats) = solver.solve_with_stats(&mut board);
+
+        assert_eq!(result, SolveResult::Solved);
+        assert!(stats.rounds > 1);
+        assert_eq!(stats.peak_candidates, 729);
+        // the techniques got used before the first guess
+        assert_eq!(stats.techniques[0].name, "sole option");
+        assert!(stats.techniques[0].uses > 1);
+        assert_eq!(stats.techniques[0].difficulty, Some(Difficulty::Easy));
+        let guesses = stats.techniques.iter().find(|t| t.name == "backtracking");
+        assert!(guesses.unwrap().placements > 0);
+        assert_eq!(stats.hardest().map(|t| t.name), Some("backtracking"));
+        // wrong guesses get reverted
+        assert!(stats.techniques.iter().map(|t| t.placements).sum::<usize>() >= 81);
+        assert!(board.steps().is_none());
+
+        let mut board: Board = concat!(
+            "..4..5.2..52.3684..16.82...2...5.4......1.73.641.....8...8....712.....",
+            ".47.....1.9"
+        ).parse().unwrap();
+        let (_, stats) = Solver::default().solve_with_stats(&mut board);
+        let hardest = stats.hardest().unwrap();
+        assert_eq!(hardest.name, "sole option");
+        assert_eq!(hardest.difficulty, Some(Difficulty::Easy));
+    }
+
+    #[test]
+    fn config() {
+        let names = |config: &SolverConfig| {
+            Solver::<9>::with_config(config)
+                .strategies()
+                .iter()
+                .map(|s| s.name())
+                .collect::<Vec<_>>()
+        };
+
+        let config = SolverConfig {
+            forcing_chain_depth: 1,
+            disabled: vec!["remote pairs".into()],
+            ..Default::default()
+        };
+        assert_eq!(
+            names(&config),
+            vec![
+                "sole option", "sole position by neighbours", "cage sums",
+                "thermometers", "arrow sums", "custom constraints", "forcing chains",
+            ]
+        );
+
+        let config = SolverConfig {
+            forcing_chain_depth: 1,
+            max_difficulty: Some(Difficulty::Medium),
+            ..Default::default()
+        };
+        assert_eq!(
+            names(&config),
+            vec![
+                "sole option", "sole position by neighbours", "cage sums",
+                "thermometers", "arrow sums", "custom constraints",
+            ]
+        );
+
+        let config = SolverConfig {
+            enabled: Some(vec!["sole option".into(), "remote pairs".into()]),
+            ..Default::default()
+        };
+        assert_eq!(names(&config), vec!["sole option", "remote pairs"]);
+
+        let mut board: Board = format!("1{}", ".".repeat(80)).parse().unwrap();
+        let result = board.solve_with_config(&SolverConfig {
+            backtracking: true,
+            max_iterations: Some(5),
+            ..Default::default()
+        });
+        assert_eq!(result, SolveResult::IterationLimit);
+        assert_eq!(board.to_string(), format!("1{}", ".".repeat(80)));
+
+        let result = board.solve_with_config(&SolverConfig {
+            backtracking: true,
+            timeout: Some(Duration::ZERO),
+            ..Default::default()
+        });
+        assert_eq!(result, SolveResult::TimeLimit);
+
+        assert_eq!(Difficulty::from_name("hard"), Some(Difficulty::Hard));
+        assert!(Difficulty::Easy < Difficulty::Expert);
+    }
+
+    #[test]
+    fn remove_strategy() {
+        let mut solver: Solver = Solver::default();
+        solver.insert(0, Eights);
+        assert!(solver.remove("sole option").is_some());
+        assert_eq!(
+            solver.strategies().iter().map(|s| s.name()).collect::<Vec<_>>(),
+            vec![
+                "eights", "sole position by neighbours", "cage sums", "thermometers",
+                "arrow sums", "custom constraints", "remote pairs",
+            ]
+        );
+    }
+}
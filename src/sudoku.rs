@@ -1,20 +1,191 @@
-#[derive(Debug)]
+use std::{
+    fmt,
+    hash::{Hash, Hasher},
+    iter::FromIterator,
+    sync::{
+        atomic::{AtomicBool, AtomicUsize, Ordering},
+        Arc, OnceLock,
+    },
+    thread,
+};
+
+use crate::chess::Move;
+use crate::constraint::{Boxes, Columns, Constraint, CustomConstraints, Parities, Rows};
+use crate::dlx::Dlx;
+use crate::error::Error;
+use crate::fast;
+use crate::killer::Cage;
+use crate::layout::{Cells, Fields};
+use crate::shape::Shape;
+use crate::step::{Action, Event, GridState, Hint, LogEntry, Reason, Step};
+use crate::strategy::{
+    Arrows, CageSums, Difficulty, Neighbourhood, SoleOption, Solver, SolverConfig,
+    Thermometers,
+};
+use crate::tables::{PeerTable, UnitTable};
+use crate::unit::Unit;
+
+/// The largest number of rows of a board
+pub(crate) const MAX_SIZE: usize = 35;
+
+/// The candidates of an unsolved cell, kept in the cell without allocating
+///
+/// They dereference to a slice of the values.
+#[derive(Clone, Copy)]
+pub struct Candidates {
+    len: u8,
+    values: [u8; MAX_SIZE],
+}
+
+impl Candidates {
+    /// Keeps only the values for which `keep` returns true
+    pub fn retain(&mut self, mut keep: impl FnMut(&u8) -> bool) {
+        let mut len = 0;
+        for i in 0..self.len as usize {
+            if keep(&self.values[i]) {
+                self.values[len] = self.values[i];
+                len += 1;
+            }
+        }
+        self.len = len as u8;
+    }
+
+    // The values `v` of the bits `v - 1` of `mask`
+    pub(crate) fn from_mask(mut mask: u64) -> Self {
+        let mut ret = Candidates { len: 0, values: [0; MAX_SIZE] };
+        while mask != 0 {
+            ret.values[ret.len as usize] = mask.trailing_zeros() as u8 + 1;
+            ret.len += 1;
+            mask &= mask - 1;
+        }
+        ret
+    }
+}
+
+impl std::ops::Deref for Candidates {
+    type Target = [u8];
+
+    fn deref(&self) -> &[u8] {
+        &self.values[..self.len as usize]
+    }
+}
+
+impl IntoIterator for Candidates {
+    type Item = u8;
+    type IntoIter = std::iter::Take<std::array::IntoIter<u8, MAX_SIZE>>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        IntoIterator::into_iter(self.values).take(self.len as usize)
+    }
+}
+
+impl<'a> IntoIterator for &'a Candidates {
+    type Item = &'a u8;
+    type IntoIter = std::slice::Iter<'a, u8>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.iter()
+    }
+}
+
+/// # Panics
+///
+/// Panics if there are more than 35 values.
+impl FromIterator<u8> for Candidates {
+    fn from_iter<I: IntoIterator<Item = u8>>(iter: I) -> Self {
+        let mut ret = Candidates { len: 0, values: [0; MAX_SIZE] };
+        for val in iter {
+            assert!((ret.len as usize) < MAX_SIZE, "Too many candidates");
+            ret.values[ret.len as usize] = val;
+            ret.len += 1;
+        }
+        ret
+    }
+}
+
+impl PartialEq for Candidates {
+    fn eq(&self, other: &Self) -> bool {
+        **self == **other
+    }
+}
+
+impl Eq for Candidates {}
+
+impl Hash for Candidates {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        (**self).hash(state);
+    }
+}
+
+impl fmt::Debug for Candidates {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        (**self).fmt(f)
+    }
+}
+
+#[cfg(feature = "serde")]
+impl serde::Serialize for Candidates {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: serde::Serializer,
+    {
+        serializer.collect_seq(self.iter())
+    }
+}
+
+#[cfg(feature = "serde")]
+impl<'de> serde::Deserialize<'de> for Candidates {
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: serde::Deserializer<'de>,
+    {
+        use serde::de::Error;
+
+        let values = Vec::<u8>::deserialize(deserializer)?;
+        if values.len() > MAX_SIZE {
+            return Err(D::Error::invalid_length(values.len(), &"at most 35 values"));
+        }
+
+        Ok(values.into_iter().collect())
+    }
+}
+
+/// A single cell of the board
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub enum Field {
+    /// The cell holds this value
     Value(u8),
-    Options(Vec<u8>),
+    /// The cell is unsolved and can still take these values
+    Options(Candidates),
 }
 
 impl Field {
-    pub fn with_all_options() -> Self {
-        Field::Options(vec![1,2,3,4,5,6,7,8,9])
+    /// An unsolved cell that can take every value from 1 to `size`
+    pub fn with_all_options(size: u8) -> Self {
+        Field::Options((1..=size).collect())
     }
 
-    pub fn set(&mut self, val: u8) {
-        assert!((1..=9).contains(&val), "Invalid field value: {}", val);
+    /// Turns the cell into a solved one, unless `val` is not in the range 1 to
+    /// `size`
+    pub fn set(&mut self, val: u8, size: u8) -> Result<(), Error> {
+        if val == 0 || val > size {
+            return Err(Error::InvalidValue(val));
+        }
 
         *self = Field::Value(val);
+        Ok(())
     }
 
+    /// The candidates of an unsolved cell
+    pub fn candidates(&self) -> Option<&[u8]> {
+        match self {
+            Field::Options(opts) => Some(opts),
+            Field::Value(_) => None,
+        }
+    }
+
+    /// Drops `val` from the candidates of an unsolved cell
     pub fn remove_option(&mut self, val: u8) {
         if let Field::Options(opts) = self {
             opts.retain(|&x| x != val);
@@ -22,201 +193,1193 @@ impl Field {
     }
 }
 
-pub struct Board {
-    data: Vec<Field>,
-    changed: bool,
-    steps: Option<Vec<(u8, u8, &'static str)>>,
+/// Mark of a cell that holds only even or only odd values
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum Parity {
+    Even,
+    Odd,
 }
 
-impl Board {
-    pub fn new() -> Self {
-        let mut data = Vec::with_capacity(9 * 9);
-        for _ in 0..9 * 9 {
-            data.push(Field::with_all_options());
+impl Parity {
+    /// Whether `val` has this parity
+    pub fn allows(self, val: u8) -> bool {
+        val.is_multiple_of(2) == (self == Parity::Even)
+    }
+}
+
+/// Outcome of solving a board
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(rename_all = "snake_case"))]
+pub enum SolveResult {
+    /// Every cell holds a value
+    Solved,
+    /// The techniques found no further progress
+    Stuck,
+    /// The board violates the rules and has no solution
+    Contradiction,
+    /// The solver gave up unsolved when the time of
+    /// [`SolverConfig::timeout`] was over
+    TimeLimit,
+    /// The solver gave up unsolved after the rounds of
+    /// [`SolverConfig::max_iterations`]
+    IterationLimit,
+}
+
+/// The first violation of the rules found while solving
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Contradiction {
+    /// The cell that has no candidate left or already holds the placed value
+    pub cell: (usize, usize),
+    /// The placement `(cell, value)` that caused the contradiction or `None`
+    /// if the last candidate got eliminated by a technique
+    pub cause: Option<((usize, usize), u8)>,
+}
+
+impl fmt::Display for Contradiction {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "conflict at ({}, {})", self.cell.0 + 1, self.cell.1 + 1)?;
+
+        if let Some(((row, col), val)) = self.cause {
+            write!(f, " caused by placing {} at ({}, {})", val, row + 1, col + 1)?;
         }
 
+        Ok(())
+    }
+}
+
+/// Error of [`Board::try_set`]: a neighbour already holds the value
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct ConflictError {
+    /// The neighbour holding the value
+    pub cell: (usize, usize),
+    /// The value that was to be placed
+    pub value: u8,
+}
+
+impl fmt::Display for ConflictError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(
+            f, "{} is already placed at ({}, {})",
+            self.value, self.cell.0 + 1, self.cell.1 + 1
+        )
+    }
+}
+
+impl std::error::Error for ConflictError {}
+
+/// Saved state of a board, see [`Board::snapshot`]
+#[derive(Clone, Debug)]
+pub struct Snapshot<const N: usize = 9> {
+    data: Box<Cells<N>>,
+    given: Box<[[bool; N]; N]>,
+    steps: Option<usize>,
+    contradiction: Option<Contradiction>,
+}
+
+// The generation of the board, counting its changes, and the generation of
+// the last change of every cell; they start at 1
+#[derive(Clone, Copy, Debug)]
+struct Stamps<const N: usize> {
+    generation: u32,
+    cells: [[u32; N]; N],
+}
+
+impl<const N: usize> Default for Stamps<N> {
+    fn default() -> Self {
+        Stamps { generation: 1, cells: [[1; N]; N] }
+    }
+}
+
+impl<const N: usize> Stamps<N> {
+    fn touch(&mut self, idx: usize) {
+        self.generation = self.generation.wrapping_add(1);
+        self.cells[idx / N][idx % N] = self.generation;
+    }
+
+    fn touch_all(&mut self) {
+        self.generation = self.generation.wrapping_add(1);
+        self.cells = [[self.generation; N]; N];
+    }
+}
+
+/// A sudoku board with `N` rows, columns and boxes and the values 1 to `N`
+///
+/// Cells are addressed either by an index from 0 to `N * N - 1`, counting row
+/// by row, or by a zero-based `(row, column)` tuple. The boxes are `N` cells
+/// large, but need not be square, e.g. 2x3 on a 6x6 board. Variants add
+/// further units, like the diagonals of X-Sudoku, with [`Board::add_unit`] and
+/// the cages of Killer Sudoku with [`Board::add_cage`]. Jigsaw Sudoku replaces
+/// the boxes by irregular regions with [`Board::set_regions`], and
+/// [`Board::add_move`] keeps cells a knight's or king's move apart from
+/// holding the same value. Thermometers and arrows come with
+/// [`Board::add_shape`] and cells marked even or odd with
+/// [`Board::set_parities`].
+///
+/// Boards compare equal and hash alike if their cells are equal; the recorded
+/// steps are not taken into account.
+#[derive(Clone, Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Board<const N: usize = 9> {
+    data: Cells<N>,
+    #[cfg_attr(feature = "serde", serde(
+        serialize_with = "serialize_cells", deserialize_with = "deserialize_cells"
+    ))]
+    given: [[bool; N]; N],
+    box_rows: usize,
+    box_cols: usize,
+    /// The region of every cell or nothing for boxes
+    #[cfg_attr(feature = "serde", serde(default))]
+    regions: Vec<usize>,
+    #[cfg_attr(feature = "serde", serde(default))]
+    extra_units: Vec<Unit>,
+    #[cfg_attr(feature = "serde", serde(default))]
+    cages: Vec<Cage>,
+    #[cfg_attr(feature = "serde", serde(default))]
+    moves: Vec<Move>,
+    #[cfg_attr(feature = "serde", serde(default))]
+    shapes: Vec<Shape>,
+    #[cfg_attr(feature = "serde", serde(default))]
+    parities: Parities,
+    #[cfg_attr(feature = "serde", serde(skip))]
+    custom: Vec<Arc<dyn Constraint<N>>>,
+    #[cfg_attr(feature = "serde", serde(skip))]
+    unit_table: OnceLock<Arc<UnitTable>>,
+    #[cfg_attr(feature = "serde", serde(skip))]
+    peer_table: OnceLock<Arc<PeerTable>>,
+    #[cfg_attr(feature = "serde", serde(skip))]
+    stamps: Stamps<N>,
+    steps: Option<Vec<Step>>,
+    #[cfg_attr(feature = "serde", serde(
+        default, skip_serializing_if = "Option::is_none"
+    ))]
+    snapshots: Option<Vec<GridState>>,
+    #[cfg_attr(feature = "serde", serde(
+        default, skip_serializing_if = "Option::is_none"
+    ))]
+    log: Option<Vec<LogEntry>>,
+    contradiction: Option<Contradiction>,
+}
+
+impl<const N: usize> Board<N> {
+    /// An empty board where every cell can take every value
+    ///
+    /// The boxes are as square as possible: 3x3 for `N = 9`, 2x3 for `N = 6`
+    /// and 1xN for a prime `N`.
+    pub fn new() -> Self {
+        let rows = (1..=N)
+            .rev()
+            .find(|r| r * r <= N && N.is_multiple_of(*r))
+            .unwrap_or(1);
+        Self::with_boxes(rows, N / rows)
+    }
+
+    /// An empty board with boxes of `rows` by `cols` cells
+    ///
+    /// # Panics
+    ///
+    /// Panics if `rows * cols` is not `N` or `N` is larger than 35.
+    pub fn with_boxes(rows: usize, cols: usize) -> Self {
+        assert!(rows * cols == N, "Boxes of {}x{} don't fit {}", rows, cols, N);
+        assert!(N <= 35, "Board too large: {}", N);
+
         Board {
-            data,
-            changed: false,
+            data: Cells::new(Field::with_all_options(N as u8)),
+            given: [[false; N]; N],
+            box_rows: rows,
+            box_cols: cols,
+            regions: Vec::new(),
+            extra_units: Vec::new(),
+            cages: Vec::new(),
+            moves: Vec::new(),
+            shapes: Vec::new(),
+            parities: Parities::default(),
+            custom: Vec::new(),
+            unit_table: OnceLock::new(),
+            peer_table: OnceLock::new(),
+            stamps: Stamps::default(),
             steps: None,
+            snapshots: None,
+            log: None,
+            contradiction: None,
         }
     }
 
-    fn neighbours(pos: (usize, usize)) -> Vec<(usize, usize)> {
-        let (row, col) = pos;
-        let mut ret = Vec::with_capacity(9 + 9 + 9 - 4 - 3);
+    // Replaces the cell `idx` without any checks or eliminations
+    pub(crate) fn put_field(&mut self, idx: usize, fld: Field, given: bool) {
+        self.update_field(idx, |old| *old = fld);
+        self.given[idx / N][idx % N] = given;
+    }
 
-        for x in 0..9 {
-            if x != col {
-                ret.push((row, x));
-            }
+    // Changes the cell `idx` with `change`
+    fn update_field<R>(
+        &mut self, idx: usize, change: impl FnOnce(&mut Field) -> R
+    ) -> R {
+        self.stamps.touch(idx);
+        self.data.update(idx, change)
+    }
 
-            if x != row {
-                ret.push((x, col));
-            }
+    // Removes the candidate `val` from the cell `idx`
+    fn remove_candidate(&mut self, idx: usize, val: u8) {
+        self.stamps.touch(idx);
+        self.data.remove(idx, val);
+    }
+
+    /// The number of changes of the cells so far, see
+    /// [`Board::changed_since`]
+    pub fn generation(&self) -> u32 {
+        self.stamps.generation
+    }
+
+    /// Indices of the cells changed after the [`generation`](Self::generation)
+    /// `since`; all cells for 0
+    pub fn changed_since(&self, since: u32) -> impl Iterator<Item = usize> + '_ {
+        (0..N * N).filter(move |&idx| self.is_changed_since(idx, since))
+    }
+
+    // Whether the cell `idx` changed after the generation `since`
+    pub(crate) fn is_changed_since(&self, idx: usize, since: u32) -> bool {
+        self.stamps.cells[idx / N][idx % N].wrapping_sub(since) as i32 > 0
+            || since == 0
+    }
+
+    /// Number of rows, columns and values
+    pub fn size(&self) -> usize {
+        N
+    }
+
+    /// Number of rows and columns of a box
+    pub fn box_size(&self) -> (usize, usize) {
+        (self.box_rows, self.box_cols)
+    }
+
+    /// The units added with [`Board::add_unit`]
+    pub fn extra_units(&self) -> &[Unit] {
+        &self.extra_units
+    }
+
+    /// Adds a unit whose cells must hold every value once, too, e.g. a
+    /// diagonal for X-Sudoku; the values already placed get eliminated from
+    /// the other cells of the unit
+    pub fn add_unit(&mut self, unit: Unit) {
+        if self.extra_units.contains(&unit) {
+            return;
+        }
+
+        self.eliminate_peers(&unit);
+        self.extra_units.push(unit);
+        self.peer_table = OnceLock::new();
+    }
+
+    /// The region of every cell, row by row, if the board has irregular
+    /// regions instead of boxes
+    pub fn regions(&self) -> Option<&[usize]> {
+        if self.regions.is_empty() {
+            None
+        } else {
+            Some(&self.regions)
+        }
+    }
+
+    /// Replaces the boxes by irregular regions as for Jigsaw Sudoku; `regions`
+    /// holds the number of the region, from 0 to `N - 1`, of every cell row by
+    /// row
+    ///
+    /// The candidates get computed anew from the values of the board.
+    pub fn set_regions(&mut self, regions: &[usize]) -> Result<(), Error> {
+        if regions.len() < N * N {
+            return Err(Error::TooFewCells(regions.len()));
+        } else if regions.len() > N * N {
+            return Err(Error::TooManyCells(regions.len()));
+        }
+
+        if let Some(&r) = regions.iter().find(|&&r| r >= N) {
+            return Err(Error::InvalidRegion(r));
+        }
+
+        let mut count = [0; 35];
+        regions.iter().for_each(|&r| count[r] += 1);
+        if let Some(r) = count[..N].iter().position(|&n| n != N) {
+            return Err(Error::InvalidRegion(r));
+        }
+
+        let values = self.fields()
+            .zip(self.given.as_flattened())
+            .enumerate()
+            .filter_map(|(idx, (fld, &given))| match fld {
+                Field::Value(v) => Some((idx, v, given)),
+                Field::Options(_) => None,
+            })
+            .collect::<Vec<_>>();
+
+        self.regions = regions.to_vec();
+        self.unit_table = OnceLock::new();
+        self.peer_table = OnceLock::new();
+        self.data = Cells::new(Field::with_all_options(N as u8));
+        self.given = [[false; N]; N];
+        self.stamps.touch_all();
+        self.contradiction = None;
+        self.parities.clone().propagate(self);
+        for (idx, val, given) in values {
+            self.set_idx(idx, val, if given { Reason::Given } else { Reason::Set });
         }
 
-        let square_base_row = 3 * (row / 3);
-        let square_base_col = 3 * (col / 3);
+        Ok(())
+    }
+
+    /// The parity mark of the cell at `(row, column)`
+    pub fn parity(&self, pos: (usize, usize)) -> Option<Parity> {
+        self.parities.0.get(pos.0 * N + pos.1).copied().flatten()
+    }
 
-        for r in square_base_row .. square_base_row + 3 {
-            if r == row {
-                continue;
+    /// Marks the cells as even or odd, row by row with `None` for unmarked
+    /// cells, and removes the candidates of the other parity
+    pub fn set_parities(&mut self, parities: &[Option<Parity>]) -> Result<(), Error> {
+        if parities.len() < N * N {
+            return Err(Error::TooFewCells(parities.len()));
+        } else if parities.len() > N * N {
+            return Err(Error::TooManyCells(parities.len()));
+        }
+
+        self.parities = Parities(parities.to_vec());
+        self.parities.clone().propagate(self);
+        Ok(())
+    }
+
+    pub(crate) fn pos(idx: usize) -> (usize, usize) {
+        (idx / N, idx % N)
+    }
+
+    /// The cages added with [`Board::add_cage`]
+    pub fn cages(&self) -> &[Cage] {
+        &self.cages
+    }
+
+    /// Adds a cage of Killer Sudoku; the values already placed get eliminated
+    /// from the other cells of the cage
+    pub fn add_cage(&mut self, cage: Cage) -> Result<(), Error> {
+        if let Some(&pos) = cage.cells.iter().find(|pos| pos.0 >= N || pos.1 >= N) {
+            return Err(Error::InvalidCell(pos));
+        }
+
+        self.eliminate_peers(&cage);
+        self.cages.push(cage);
+        self.peer_table = OnceLock::new();
+        Ok(())
+    }
+
+    /// The moves added with [`Board::add_move`]
+    pub fn moves(&self) -> &[Move] {
+        &self.moves
+    }
+
+    /// Forbids the same value in cells one `mv` apart as in anti-knight and
+    /// anti-king Sudoku; the values already placed get eliminated from the
+    /// cells they reach
+    pub fn add_move(&mut self, mv: Move) {
+        if self.moves.contains(&mv) {
+            return;
+        }
+
+        self.eliminate_peers(&mv);
+        self.moves.push(mv);
+        self.peer_table = OnceLock::new();
+    }
+
+    /// The shapes added with [`Board::add_shape`]
+    pub fn shapes(&self) -> &[Shape] {
+        &self.shapes
+    }
+
+    /// Adds a thermometer or arrow; the cells of a thermometer hold different
+    /// values, so the values already placed get eliminated from the others
+    pub fn add_shape(&mut self, shape: Shape) -> Result<(), Error> {
+        let cells = shape.cells();
+        if let Some(&pos) = cells.iter().find(|pos| pos.0 >= N || pos.1 >= N) {
+            return Err(Error::InvalidCell(pos));
+        }
+
+        self.eliminate_peers(&shape);
+        self.shapes.push(shape);
+        self.peer_table = OnceLock::new();
+        Ok(())
+    }
+
+    /// The constraints added with [`Board::add_constraint`]
+    pub fn custom_constraints(&self) -> &[Arc<dyn Constraint<N>>] {
+        &self.custom
+    }
+
+    /// Adds a constraint of another variant; the values already placed get
+    /// eliminated from their peers
+    ///
+    /// The solver propagates it with the technique "custom constraints". The
+    /// constraint is not serialized.
+    pub fn add_constraint(&mut self, constraint: impl Constraint<N> + 'static) {
+        self.eliminate_peers(&constraint);
+        self.custom.push(Arc::new(constraint));
+        self.peer_table = OnceLock::new();
+    }
+
+    /// All constraints of the board: the rows, columns and boxes followed by
+    /// the units, cages, moves, shapes and parity marks of the variants and
+    /// the custom constraints
+    pub fn constraints(&self) -> impl Iterator<Item = &dyn Constraint<N>> {
+        let builtin: [&dyn Constraint<N>; 3] = [&Rows, &Columns, &Boxes];
+
+        IntoIterator::into_iter(builtin)
+            .chain(self.extra_units.iter().map(|c| c as &dyn Constraint<N>))
+            .chain(self.cages.iter().map(|c| c as &dyn Constraint<N>))
+            .chain(self.moves.iter().map(|c| c as &dyn Constraint<N>))
+            .chain(self.shapes.iter().map(|c| c as &dyn Constraint<N>))
+            .chain(std::iter::once(&self.parities as &dyn Constraint<N>))
+            .chain(self.custom.iter().map(|c| c.as_ref()))
+    }
+
+    // Removes the values already placed from the cells they see by
+    // `constraint`
+    fn eliminate_peers(&mut self, constraint: &dyn Constraint<N>) {
+        let eliminations = (0..N * N)
+            .filter_map(|idx| match self.field_at(idx) {
+                Field::Value(val) => Some((idx, val)),
+                Field::Options(_) => None,
+            })
+            .flat_map(|(idx, val)| {
+                let peers = constraint.peers(self, Self::pos(idx));
+                peers.into_iter().map(move |pos| (pos, val))
+            })
+            .collect::<Vec<_>>();
+
+        for ((row, col), val) in eliminations {
+            self.eliminate(row * N + col, val, Reason::Given);
+        }
+    }
+
+    // The cells of the units, computed once for the boxes or regions; boards
+    // without regions share them
+    pub(crate) fn unit_table(&self) -> &UnitTable {
+        self.unit_table.get_or_init(|| {
+            if self.regions.is_empty() {
+                UnitTable::shared(self)
+            } else {
+                Arc::new(UnitTable::new(self))
             }
+        })
+    }
 
-            for c in square_base_col .. square_base_col + 3 {
-                if c != col {
-                    ret.push((r, c));
-                }
+    // The peers and units of the cells, computed once for the constraints;
+    // boards without variants share them
+    pub(crate) fn peer_table(&self) -> &Arc<PeerTable> {
+        self.peer_table.get_or_init(|| {
+            if self.is_plain() {
+                PeerTable::shared(self)
+            } else {
+                Arc::new(PeerTable::new(self))
             }
+        })
+    }
+
+    // Whether the rows, the columns and the boxes are the only constraints
+    fn is_plain(&self) -> bool {
+        self.regions.is_empty()
+            && self.extra_units.is_empty()
+            && self.cages.is_empty()
+            && self.moves.is_empty()
+            && self.shapes.is_empty()
+            && self.custom.is_empty()
+    }
+
+    // Whether the bit parallel engine can solve the board
+    fn is_classic(&self) -> bool {
+        N == 9 && self.box_size() == (3, 3) && self.is_plain()
+    }
+
+    // The cells that can't hold the same value as the cell `idx` by any
+    // constraint
+    pub(crate) fn neighbours(&self, idx: usize) -> &[usize] {
+        self.peer_table().peers(idx)
+    }
+
+    pub(crate) fn sees(&self, a: usize, b: usize) -> bool {
+        self.neighbours(a).contains(&b)
+    }
+
+    pub(crate) fn has_contradiction(&self) -> bool {
+        self.contradiction.is_some()
+    }
+
+    /// The first contradiction found while filling or solving the board
+    pub fn contradiction(&self) -> Option<Contradiction> {
+        self.contradiction
+    }
+
+    fn found_contradiction(&mut self, cell: usize, cause: Option<(usize, u8)>) {
+        if self.contradiction.is_none() {
+            self.contradiction = Some(Contradiction {
+                cell: Self::pos(cell),
+                cause: cause.map(|(idx, val)| (Self::pos(idx), val)),
+            });
         }
+    }
 
-        ret
+    pub(crate) fn is_complete(&self) -> bool {
+        self.fields().all(|fld| matches!(fld, Field::Value(_)))
+    }
+
+    /// Whether the values keep every constraint: no value occurs twice in
+    /// a row, column, square, cage or in cells a move apart and no cage,
+    /// shape or parity mark gets broken
+    pub fn is_valid(&self) -> bool {
+        self.constraints().all(|constraint| constraint.is_valid(self))
+    }
+
+    /// The constraints the values break
+    pub fn broken_constraints(&self) -> impl Iterator<Item = &dyn Constraint<N>> {
+        self.constraints().filter(move |constraint| !constraint.is_valid(self))
+    }
+
+    /// Positions of the values that occur more than once in a row, column or
+    /// square
+    pub fn conflicts(&self) -> Vec<(usize, usize)> {
+        (0..N * N)
+            .filter(|&idx| match self.field_at(idx) {
+                Field::Value(v) => self.neighbours(idx)
+                    .iter()
+                    .any(|&other| self.field_at(other) == Field::Value(v)),
+                Field::Options(_) => false,
+            })
+            .map(Self::pos)
+            .collect()
     }
 
+    /// Whether every cell holds a value and the board is valid
+    pub fn is_solved(&self) -> bool {
+        self.is_complete() && self.is_valid()
+    }
+
+    /// Whether the board is solved, stuck or contradictory
+    pub fn status(&self) -> SolveResult {
+        if self.has_contradiction() || !self.is_valid() {
+            SolveResult::Contradiction
+        } else if self.is_complete() {
+            SolveResult::Solved
+        } else {
+            SolveResult::Stuck
+        }
+    }
+
+    /// Saves the cells and the number of recorded steps
+    pub fn snapshot(&self) -> Snapshot<N> {
+        Snapshot {
+            data: Box::new(self.data),
+            given: Box::new(self.given),
+            steps: self.steps.as_ref().map(Vec::len),
+            contradiction: self.contradiction,
+        }
+    }
+
+    /// Reverts the board to the state of `snapshot` and drops the steps
+    /// recorded since then
+    pub fn restore(&mut self, snapshot: &Snapshot<N>) {
+        self.data = *snapshot.data;
+        self.given = *snapshot.given;
+        self.stamps.touch_all();
+        self.contradiction = snapshot.contradiction;
+
+        if let (Some(steps), Some(len)) = (&mut self.steps, snapshot.steps) {
+            steps.truncate(len);
+        }
+        if let (Some(snapshots), Some(len)) = (&mut self.snapshots, snapshot.steps) {
+            snapshots.truncate(len);
+        }
+    }
+
+    /// Enables or disables the recording of steps; enabling it drops
+    /// all recorded steps and their snapshots
     pub fn record_steps(&mut self, enable: bool) {
         self.steps = if enable { Some(Vec::new()) } else { None };
+        self.snapshots = match self.snapshots {
+            Some(_) if enable => Some(Vec::new()),
+            _ => None,
+        };
     }
 
-    #[allow(dead_code)]
-    pub fn field(&self, pos: (usize, usize)) -> &Field {
-        &self.data[pos.0 * 9 + pos.1]
+    /// Enables or disables the recording of the cells after every step, such
+    /// that they can be shown at any step without replaying the steps before;
+    /// enabling it starts the recording of steps anew, too
+    pub fn record_snapshots(&mut self, enable: bool) {
+        if enable {
+            self.steps = Some(Vec::new());
+            self.snapshots = Some(Vec::new());
+        } else {
+            self.snapshots = None;
+        }
     }
 
-    pub fn fields(&self) -> &[Field] {
-        &self.data
+    /// The cells after each of the recorded [`steps`](Self::steps), if
+    /// enabled with [`Board::record_snapshots`]
+    pub fn snapshots(&self) -> Option<&[GridState]> {
+        self.snapshots.as_deref()
     }
 
-    pub fn steps(&self) -> &Option<Vec<(u8, u8, &str)>> {
-        &self.steps
+    /// Enables or disables the search log, which keeps the steps of the
+    /// guesses of backtracking that led to a contradiction, too, with entries
+    /// for the guesses, contradictions and backtracks; enabling it drops the
+    /// entries logged so far
+    pub fn record_log(&mut self, enable: bool) {
+        self.log = if enable { Some(Vec::new()) } else { None };
     }
 
-    fn set_idx(&mut self, idx: usize, val: u8, reason: &'static str) {
-        self.data[idx].set(val);
+    /// The search log, if enabled
+    pub fn log(&self) -> Option<&[LogEntry]> {
+        self.log.as_deref()
+    }
 
-        for pos in Self::neighbours((idx / 9, idx % 9)) {
-            self.data[pos.0 * 9 + pos.1].remove_option(val);
+    // Adds `entry` to the search log if enabled
+    pub(crate) fn log_entry(&mut self, entry: impl FnOnce() -> LogEntry) {
+        if let Some(log) = &mut self.log {
+            log.push(entry());
         }
+    }
 
+    // Records a step and, if enabled, the cells after it; the guesses are
+    // logged by the solver that knows their depth
+    fn record(&mut self, step: Step) {
+        if step.reason != Reason::Guess {
+            self.log_entry(|| LogEntry::Step(step.clone()));
+        }
+        let state = self.snapshots.as_ref().map(|_| GridState::new(self));
         if let Some(ref mut steps) = self.steps {
-            steps.push( (idx as u8, val, reason) );
+            steps.push(step);
+        }
+        if let (Some(snapshots), Some(state)) = (&mut self.snapshots, state) {
+            snapshots.push(state);
+        }
+    }
+
+    /// The cell at `(row, column)`
+    pub fn field(&self, pos: (usize, usize)) -> Field {
+        self.data.get(pos.0 * N + pos.1)
+    }
+
+    /// The candidates of the cell at `(row, column)` or `None` if it holds a
+    /// value
+    pub fn candidates(&self, pos: (usize, usize)) -> Option<Candidates> {
+        match self.field(pos) {
+            Field::Options(opts) => Some(opts),
+            Field::Value(_) => None,
         }
+    }
 
-        self.changed = true;
+    /// Number of candidates of the cell at `(row, column)`; 0 if the cell
+    /// holds a value
+    pub fn candidate_count(&self, pos: (usize, usize)) -> usize {
+        self.mask(pos.0 * N + pos.1).count_ones() as usize
     }
 
-    #[allow(dead_code)]
-    pub fn set(&mut self, pos: (usize, usize), val: u8) {
-        self.set_idx(pos.0 * 9 + pos.1, val, "known in advance")
+    /// Whether the cell at `(row, column)` is a clue of the puzzle, i.e. it was
+    /// set with [`Reason::Given`]
+    pub fn is_given(&self, pos: (usize, usize)) -> bool {
+        self.given[pos.0][pos.1]
     }
 
-    pub fn fill(&mut self, data: impl Iterator<Item = Option<u8>>) {
-        data
-            .take(self.data.len())
-            .enumerate()
-            .filter_map(|(i, e)| e.map(|x| (i, x)))
-            .for_each(|(i, val)| self.set_idx(i, val as u8, "init"));
+    /// A new board holding only the clues of this one
+    pub fn puzzle(&self) -> Self {
+        let cells = self.fields().zip(self.given.as_flattened());
+        self.with_clues(cells.map(|(fld, &given)| match fld {
+            Field::Value(v) if given => Some(v),
+            _ => None,
+        }))
     }
 
-    fn solve_sole_option(&mut self) {
-        self.data
-            .iter()
-            .enumerate()
-            .filter_map(|(idx, fld)| match fld {
-                Field::Options(opts) if opts.len() == 1 => Some((idx, opts[0])),
-                _ => None,
-            })
-            .collect::<Vec<_>>()
-            .iter()
-            .for_each(|(idx, val)| self.set_idx(*idx, *val, "sole option"));
+    // A new board with the variants of this one and `clues` as givens
+    pub(crate) fn with_clues(&self, clues: impl Iterator<Item = Option<u8>>) -> Self {
+        let mut board = Self::with_boxes(self.box_rows, self.box_cols);
+        board.extra_units.clone_from(&self.extra_units);
+        board.cages.clone_from(&self.cages);
+        board.regions.clone_from(&self.regions);
+        board.moves.clone_from(&self.moves);
+        board.shapes.clone_from(&self.shapes);
+        board.parities.clone_from(&self.parities);
+        board.custom.clone_from(&self.custom);
+        board.unit_table.clone_from(&self.unit_table);
+        board.peer_table.clone_from(&self.peer_table);
+        board.parities.clone().propagate(&mut board);
+        board.fill(clues).expect("values of a board are valid");
+
+        board
+    }
+
+    /// All cells, row by row
+    pub fn fields(&self) -> Fields<'_, N> {
+        self.data.fields()
+    }
+
+    // The cell `idx`
+    pub(crate) fn field_at(&self, idx: usize) -> Field {
+        self.data.get(idx)
     }
 
-    fn solve_by_neighbourhood(
-        &mut self, positions: impl Iterator<Item = usize>
-    ) {
-        let mut list : [Vec<_>; 9] = Default::default();
+    // The value of the cell `idx` or 0 if it has none
+    pub(crate) fn value(&self, idx: usize) -> u8 {
+        self.data.value(idx)
+    }
+
+    // The candidates of the cell `idx` as bit mask with the bit `v - 1` for
+    // the candidate `v`; 0 if it holds a value
+    pub(crate) fn mask(&self, idx: usize) -> u64 {
+        self.data.mask(idx)
+    }
 
-        for idx in positions {
-            if let Field::Options(opts) = &self.data[idx] {
-                for num in opts {
-                    list[*num as usize - 1].push(idx);
+    /// The recorded placements and eliminations, if recording is enabled
+    pub fn steps(&self) -> &Option<Vec<Step>> {
+        &self.steps
+    }
+
+    /// Places `val` in the cell `idx`, removes it from the candidates of all
+    /// neighbours and records the step with `reason`
+    ///
+    /// # Panics
+    ///
+    /// Panics if `val` is not in the range 1 to `N`.
+    pub fn set_idx(&mut self, idx: usize, val: u8, reason: Reason) {
+        assert!((1..=N).contains(&(val as usize)), "{}", Error::InvalidValue(val));
+        self.stamps.touch(idx);
+        self.data.place(idx, val);
+        self.given[idx / N][idx % N] = reason == Reason::Given;
+
+        let peers = Arc::clone(self.peer_table());
+        let bit = 1 << (val - 1);
+        for &other in peers.peers(idx) {
+            if self.value(other) == val {
+                self.found_contradiction(other, Some((idx, val)))
+            } else if self.mask(other) & bit != 0 {
+                self.remove_candidate(other, val);
+                if self.mask(other) == 0 {
+                    self.found_contradiction(other, Some((idx, val)));
                 }
             }
         }
 
-        for (num, e) in list.iter().enumerate() {
-            let num = num as u8 + 1;
+        self.record(Step {
+            cell: Self::pos(idx),
+            value: val,
+            action: Action::Place,
+            reason,
+        });
+    }
 
-            match e.len() {
-                0 => (),
-                1 => self.set_idx(e[0], num, "sole positon by neighbours"),
-                _ => {
-                    let mut it = e.iter();
-                    let (row, col) = it.next().map(|x| (x / 9, x % 9)).unwrap();
+    /// Removes the candidate `val` from the cell `idx`, records the step with
+    /// `reason` and returns whether the cell had the candidate
+    pub fn eliminate(&mut self, idx: usize, val: u8, reason: Reason) -> bool {
+        if !(1..=N).contains(&(val as usize)) || self.mask(idx) & 1 << (val - 1) == 0 {
+            return false;
+        }
 
-                    let mut sole_row = true;
-                    let mut sole_col = true;
-                    for x in it {
-                        if x / 9 != row {
-                            sole_row = false;
-                        }
+        self.remove_candidate(idx, val);
+        if self.mask(idx) == 0 {
+            self.found_contradiction(idx, None);
+        }
 
-                        if x % 9 != col {
-                            sole_col = false;
-                        }
-                    }
+        self.record(Step {
+            cell: Self::pos(idx),
+            value: val,
+            action: Action::Eliminate,
+            reason,
+        });
+        true
+    }
 
-                    if sole_row {
-                        (0..9).map(|c| row * 9 + c)
-                            .filter(|idx| !e.contains(idx))
-                            .for_each(|idx| self.data[idx].remove_option(num));
-                    }
+    /// Places `val` at `(row, column)`
+    ///
+    /// # Panics
+    ///
+    /// Panics if `val` is not in the range 1 to `N`.
+    pub fn set(&mut self, pos: (usize, usize), val: u8) {
+        self.set_idx(pos.0 * N + pos.1, val, Reason::Set)
+    }
 
-                    if sole_col {
-                        (0..9).map(|r| r * 9 + col)
-                            .filter(|idx| !e.contains(idx))
-                            .for_each(|idx| self.data[idx].remove_option(num));
-                    }
+    /// Places `val` at `(row, column)` unless the cell is off the board, `val`
+    /// is not in the range 1 to `N` or a neighbour already holds it
+    pub fn try_set(&mut self, pos: (usize, usize), val: u8) -> Result<(), Error> {
+        if pos.0 >= N || pos.1 >= N {
+            return Err(Error::InvalidCell(pos));
+        }
+        if !(1..=N).contains(&(val as usize)) {
+            return Err(Error::InvalidValue(val));
+        }
+
+        let conflict = self.neighbours(pos.0 * N + pos.1)
+            .iter()
+            .find(|&&other| self.field_at(other) == Field::Value(val))
+            .map(|&other| Self::pos(other));
+
+        match conflict {
+            Some(cell) => Err(ConflictError { cell, value: val }.into()),
+            None => {
+                self.set(pos, val);
+                Ok(())
+            }
+        }
+    }
+
+    /// Sets the cells row by row from `data`, where `None` leaves a cell
+    /// empty
+    ///
+    /// The board is left unchanged if `data` doesn't hold an item for
+    /// every cell or holds a value not in the range 1 to `N`.
+    pub fn fill(
+        &mut self, data: impl Iterator<Item = Option<u8>>
+    ) -> Result<(), Error> {
+        let data = data.collect::<Vec<_>>();
+        if data.len() < N * N {
+            return Err(Error::TooFewCells(data.len()));
+        } else if data.len() > N * N {
+            return Err(Error::TooManyCells(data.len()));
+        }
+
+        let invalid = data.iter().flatten().find(|&&v| v == 0 || v as usize > N);
+        if let Some(val) = invalid {
+            return Err(Error::InvalidValue(*val));
+        }
+
+        data.into_iter()
+            .enumerate()
+            .filter_map(|(i, e)| e.map(|x| (i, x)))
+            .for_each(|(i, val)| self.set_idx(i, val, Reason::Given));
+
+        Ok(())
+    }
+
+    /// Replays recorded steps onto the board, e.g. the steps of a solution
+    /// onto a fresh copy of the puzzle
+    ///
+    /// Stops at the first step whose value is not a candidate of the cell and
+    /// reports its position in `steps`; the steps before stay applied.
+    pub fn apply_steps(&mut self, steps: &[Step]) -> Result<(), Error> {
+        for (i, step) in steps.iter().enumerate() {
+            let (row, col) = step.cell;
+            let idx = row * N + col;
+            let valid = row < N && col < N
+                && self.field_at(idx)
+                    .candidates()
+                    .is_some_and(|c| c.contains(&step.value));
+            if !valid {
+                return Err(Error::InvalidStep(i));
+            }
+
+            match step.action {
+                Action::Place => self.set_idx(idx, step.value, step.reason.clone()),
+                Action::Eliminate => {
+                    self.eliminate(idx, step.value, step.reason.clone());
                 }
             }
         }
+
+        Ok(())
     }
 
-    pub fn solve(&mut self) {
-        loop {
-            self.changed = false;
-            self.solve_sole_option();
+    // Encodes the board as exact cover problem with a row per cell and
+    // candidate (idx * N + val - 1) and a column for every cell and every
+    // value in a unit.
+    fn exact_cover(&self) -> Dlx {
+        let cells = N * N;
+        let units = self.units().collect::<Vec<_>>();
+        let mut dlx = Dlx::new(cells + units.len() * N);
+
+        let mut cell_units = vec![Vec::new(); cells];
+        for (u, &unit) in units.iter().enumerate() {
+            for idx in self.unit_cells(unit) {
+                cell_units[idx].push(u);
+            }
+        }
 
-            for row in 0..9 {
-                self.solve_by_neighbourhood((0..9).map(|c| row * 9 + c));
+        for (idx, units) in cell_units.iter().enumerate() {
+            let vals = match self.value(idx) {
+                0 => self.mask(idx),
+                v => 1 << (v - 1),
+            };
+
+            for v in (0..N).filter(|v| vals & 1 << v != 0) {
+                let columns = std::iter::once(idx)
+                    .chain(units.iter().map(|u| cells + u * N + v))
+                    .collect::<Vec<_>>();
+                dlx.add_row(idx * N + v, &columns);
             }
+        }
+
+        dlx
+    }
+
+    // Searches up to `limit` solutions as exact cover problem, or by guessing
+    // with the propagation of the constraints the exact cover can't express,
+    // until `cancel` gets set and passes the rows of the exact cover problem
+    // to `found`
+    fn search(
+        &self, limit: usize, cancel: &AtomicBool, found: &mut dyn FnMut(&[usize])
+    ) -> usize {
+        if self.constraints().all(|constraint| constraint.is_exact()) {
+            return self.exact_cover().search(limit, cancel, found);
+        }
+
+        let mut solver = Solver::new();
+        solver.push(SoleOption);
+        solver.push(Neighbourhood);
+        solver.push(CageSums);
+        solver.push(Thermometers);
+        solver.push(Arrows);
+        solver.push(CustomConstraints);
+
+        let mut board = self.clone();
+        board.record_steps(false);
+        board.guess(&solver, limit, cancel, found)
+    }
 
-            for col in 0..9 {
-                self.solve_by_neighbourhood((0..9).map(|r| r * 9 + col));
+    // Tries every candidate of the cell with the fewest after propagating the
+    // board with `solver`
+    fn guess(
+        mut self, solver: &Solver<N>, limit: usize, cancel: &AtomicBool,
+        found: &mut dyn FnMut(&[usize])
+    ) -> usize {
+        match solver.solve(&mut self) {
+            SolveResult::Contradiction => return 0,
+            SolveResult::Solved => {
+                let rows = self.fields()
+                    .enumerate()
+                    .filter_map(|(idx, fld)| match fld {
+                        Field::Value(v) => Some(idx * N + v as usize - 1),
+                        Field::Options(_) => None,
+                    })
+                    .collect::<Vec<_>>();
+                found(&rows);
+                return 1;
             }
+            SolveResult::Stuck
+            | SolveResult::TimeLimit
+            | SolveResult::IterationLimit => (),
+        }
+
+        let idx = (0..N * N)
+            .filter(|&idx| self.field_at(idx).candidates().is_some())
+            .min_by_key(|&idx| self.candidate_count(Self::pos(idx)))
+            .expect("a stuck board has an unsolved cell");
+
+        let mut count = 0;
+        for val in self.field_at(idx).candidates().unwrap_or_default().to_vec() {
+            if count >= limit || cancel.load(Ordering::Relaxed) {
+                break;
+            }
+
+            let mut trial = self.clone();
+            trial.set_idx(idx, val, Reason::Guess);
+            count += trial.guess(solver, limit - count, cancel, found);
+        }
+
+        count
+    }
+
+    /// Solves the board as exact cover problem with dancing links; the board
+    /// is either solved afterwards or has no solution
+    pub fn solve_dlx(&mut self) -> SolveResult {
+        let mut solution = None;
+        let cancel = AtomicBool::new(false);
+        self.search(1, &cancel, &mut |rows| solution = Some(rows.to_vec()));
+
+        match solution {
+            Some(rows) => {
+                self.cover(&rows);
+                SolveResult::Solved
+            }
+            None => SolveResult::Contradiction,
+        }
+    }
+
+    /// Solves a classic 9x9 board with bit boards of its bands, many times
+    /// faster than the techniques, but without steps to follow; other boards
+    /// get solved by [`solve_dlx`](Self::solve_dlx)
+    pub fn solve_fast(&mut self) -> SolveResult {
+        if !self.is_classic() {
+            return self.solve_dlx();
+        }
 
-            for square_row in 0..3 {
-                for square_col in 0..3 {
-                    let square_base_row = 3 * square_row;
-                    let square_base_col = 3 * square_col;
-
-                    self.solve_by_neighbourhood(
-                        (square_base_row .. square_base_row + 3).flat_map(|r| {
-                            (square_base_col .. square_base_col + 3)
-                                .map(move |c| r * 9 + c)
-                        })
-                    );
+        let mut solution = None;
+        let cancel = AtomicBool::new(false);
+        fast::search(self.fields(), 1, &cancel, &mut |values| solution = Some(*values));
+
+        match solution {
+            Some(values) => {
+                for (idx, &val) in values.iter().enumerate() {
+                    if self.value(idx) == 0 {
+                        self.set_idx(idx, val, Reason::BitBoard);
+                    }
                 }
+                SolveResult::Solved
             }
+            None => SolveResult::Contradiction,
+        }
+    }
 
-            if !self.changed {
-                break;
+    // Sets the values of the rows of the exact cover problem
+    fn cover(&mut self, rows: &[usize]) {
+        for r in rows {
+            let idx = r / N;
+            if self.value(idx) == 0 {
+                self.set_idx(idx, (r % N) as u8 + 1, Reason::ExactCover);
             }
         }
     }
+
+    /// Up to `limit` solutions of the board
+    pub fn solutions(&self, limit: usize) -> Vec<Self> {
+        let mut ret = Vec::new();
+        self.search(limit, &AtomicBool::new(false), &mut |rows| {
+            let mut board = self.clone();
+            board.cover(rows);
+            ret.push(board);
+        });
+
+        ret
+    }
+
+    /// Counts the solutions of the board, but stops at `limit`
+    pub fn count_solutions(&self, limit: usize) -> usize {
+        self.count(limit, &AtomicBool::new(false), &mut || ())
+    }
+
+    /// Counts the solutions like [`count_solutions`](Self::count_solutions),
+    /// but searches with each candidate of the cell with the fewest on a
+    /// thread of its own; all threads stop once they found `limit`
+    /// solutions together
+    pub fn count_solutions_parallel(&self, limit: usize) -> usize {
+        let idx = match (0..N * N)
+            .filter(|&idx| self.field_at(idx).candidates().is_some())
+            .min_by_key(|&idx| self.candidate_count(Self::pos(idx)))
+        {
+            Some(idx) => idx,
+            None => return self.count_solutions(limit),
+        };
+
+        let total = AtomicUsize::new(0);
+        let cancel = AtomicBool::new(false);
+        thread::scope(|scope| {
+            for &val in self.field_at(idx).candidates().unwrap_or_default() {
+                let (total, cancel) = (&total, &cancel);
+                scope.spawn(move || {
+                    let mut trial = self.clone();
+                    trial.record_steps(false);
+                    trial.set_idx(idx, val, Reason::Guess);
+                    trial.count(limit, cancel, &mut || {
+                        if total.fetch_add(1, Ordering::Relaxed) + 1 >= limit {
+                            cancel.store(true, Ordering::Relaxed);
+                        }
+                    });
+                });
+            }
+        });
+
+        total.into_inner().min(limit)
+    }
+
+    // Counts up to `limit` solutions with the fastest engine for the board
+    // until `cancel` gets set and calls `found` for each
+    fn count(
+        &self, limit: usize, cancel: &AtomicBool, found: &mut dyn FnMut()
+    ) -> usize {
+        if self.is_classic() {
+            return fast::search(self.fields(), limit, cancel, &mut |_| found());
+        }
+
+        self.search(limit, cancel, &mut |_| found())
+    }
+
+    /// Solves the board with the default [`Solver`] as far as possible
+    pub fn solve(&mut self) -> SolveResult {
+        Solver::default().solve(self)
+    }
+
+    /// Solves the board with the default [`Solver`] and passes every step to
+    /// `observer`, see [`Solver::solve_with`]
+    pub fn solve_with(&mut self, observer: impl FnMut(Event<N>)) -> SolveResult {
+        Solver::default().solve_with(self, observer)
+    }
+
+    /// The next move the default [`Solver`] would make
+    pub fn next_hint(&self) -> Option<Hint> {
+        Solver::default().next_hint(self)
+    }
+
+    /// Solves the board with the techniques selected by `config`
+    pub fn solve_with_config(&mut self, config: &SolverConfig) -> SolveResult {
+        Solver::with_config(config).solve(self)
+    }
+
+    /// The easiest [`Difficulty`] whose techniques solve the board; `None` if
+    /// the board can't be solved by logic
+    pub fn rate(&self) -> Option<Difficulty> {
+        if !self.is_valid() {
+            return None;
+        }
+
+        Difficulty::ALL.iter().copied().find(|&level| {
+            let config = SolverConfig {
+                forcing_chain_depth: 2,
+                max_difficulty: Some(level),
+                ..SolverConfig::default()
+            };
+
+            let mut board = self.clone();
+            board.record_steps(false);
+            board.solve_with_config(&config) == SolveResult::Solved
+        })
+    }
+}
+
+impl<const N: usize> PartialEq for Board<N> {
+    fn eq(&self, other: &Self) -> bool {
+        self.data == other.data && self.box_size() == other.box_size()
+    }
+}
+
+impl<const N: usize> Eq for Board<N> {}
+
+impl<const N: usize> Hash for Board<N> {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        self.data.hash(state);
+    }
+}
+
+#[cfg(feature = "serde")]
+pub(crate) fn serialize_cells<S, T, const N: usize>(
+    cells: &[[T; N]; N], serializer: S
+) -> Result<S::Ok, S::Error>
+where
+    S: serde::Serializer,
+    T: serde::Serialize,
+{
+    serializer.collect_seq(cells.as_flattened())
+}
+
+#[cfg(feature = "serde")]
+pub(crate) fn deserialize_cells<'de, D, T, const N: usize>(
+    deserializer: D
+) -> Result<[[T; N]; N], D::Error>
+where
+    D: serde::Deserializer<'de>,
+    T: serde::Deserialize<'de> + Copy,
+{
+    use serde::{de::Error, Deserialize};
+
+    let data = Vec::<T>::deserialize(deserializer)?;
+    if data.len() != N * N {
+        return Err(D::Error::invalid_length(data.len(), &"a cell for each position"));
+    }
+
+    Ok(std::array::from_fn(|row| std::array::from_fn(|col| data[row * N + col])))
 }
 
-impl Default for Board {
+impl<const N: usize> Default for Board<N> {
     fn default() -> Self {
         Self::new()
     }
@@ -225,63 +1388,242 @@ impl Default for Board {
 #[cfg(test)]
 mod tests {
     use super::*;
-    use super::super::board_from_string;
+
+    fn board_from_string(data: &str) -> Board {
+        data.parse().unwrap()
+    }
 
     impl Board {
         fn to_num_vec(&self) -> Vec<u8> {
-            self.fields().iter().map(|x| match x {
+            self.fields().map(|x| match x {
                 Field::Options(_) => 0,
-                Field::Value(v) => *v,
+                Field::Value(v) => v,
             }).collect()
         }
     }
 
+    #[test]
+    fn field_set() {
+        let mut fld = Field::with_all_options(9);
+        assert_eq!(fld.set(0, 9), Err(Error::InvalidValue(0)));
+        assert_eq!(fld.set(10, 9), Err(Error::InvalidValue(10)));
+        assert_eq!(fld.set(200, 9), Err(Error::InvalidValue(200)));
+        assert_eq!(fld, Field::with_all_options(9));
+        assert_eq!(fld.set(9, 9), Ok(()));
+        assert_eq!(fld, Field::Value(9));
+    }
+
     #[test]
     fn neighbours_0_0() {
         assert_eq!(
-            Board::neighbours((0, 0)),
-            vec![
-                (0, 1), (1, 0), (0, 2), (2, 0), (0, 3), (3, 0), (0, 4), (4, 0),
-                (0, 5), (5, 0), (0, 6), (6, 0), (0, 7), (7, 0), (0, 8), (8, 0),
-                (1, 1), (1, 2), (2, 1), (2, 2),
-            ]
+            Board::<9>::new().neighbours(0),
+            &[
+                1, 2, 3, 4, 5, 6, 7, 8,
+                9, 18, 27, 36, 45, 54, 63, 72,
+                10, 11, 19, 20,
+            ][..]
         );
     }
 
     #[test]
     fn neighbours_1_1() {
         assert_eq!(
-            Board::neighbours((1, 1)),
-            vec![
-                (1, 0), (0, 1), (1, 2), (2, 1), (1, 3), (3, 1), (1, 4), (4, 1),
-                (1, 5), (5, 1), (1, 6), (6, 1), (1, 7), (7, 1), (1, 8), (8, 1),
-                (0, 0), (0, 2), (2, 0), (2, 2),
-            ]
+            Board::<9>::new().neighbours(10),
+            &[
+                9, 11, 12, 13, 14, 15, 16, 17,
+                1, 19, 28, 37, 46, 55, 64, 73,
+                0, 2, 18, 20,
+            ][..]
         );
     }
 
     #[test]
     fn neighbours_5_5() {
         assert_eq!(
-            Board::neighbours((5, 5)),
-            vec![
-                (5, 0), (0, 5), (5, 1), (1, 5), (5, 2), (2, 5), (5, 3), (3, 5),
-                (5, 4), (4, 5), (5, 6), (6, 5), (5, 7), (7, 5), (5, 8), (8, 5),
-                (3, 3), (3, 4), (4, 3), (4, 4),
-            ]
+            Board::<9>::new().neighbours(50),
+            &[
+                45, 46, 47, 48, 49, 51, 52, 53,
+                5, 14, 23, 32, 41, 59, 68, 77,
+                30, 31, 39, 40,
+            ][..]
         );
     }
 
     #[test]
     fn neighbours_6_2() {
         assert_eq!(
-            Board::neighbours((6, 2)),
-            vec![
-                (6, 0), (0, 2), (6, 1), (1, 2), (2, 2), (6, 3), (3, 2), (6, 4),
-                (4, 2), (6, 5), (5, 2), (6, 6), (6, 7), (7, 2), (6, 8), (8, 2),
-                (7, 0), (7, 1), (8, 0), (8, 1),
-            ]
+            Board::<9>::new().neighbours(56),
+            &[
+                54, 55, 57, 58, 59, 60, 61, 62,
+                2, 11, 20, 29, 38, 47, 65, 74,
+                63, 64, 72, 73,
+            ][..]
+        );
+    }
+
+    #[test]
+    fn diagonal_units() {
+        let mut board = board_from_string(&format!("5{}", ".".repeat(80)));
+        board.add_unit(Unit::Diagonal(0));
+
+        assert!(board.neighbours(0).contains(&80));
+        assert!(!board.neighbours(8).contains(&72));
+        assert!(board.sees(0, 80));
+        assert!(!board.candidates((4, 4)).unwrap().contains(&5));
+        assert!(board.candidates((4, 5)).unwrap().contains(&5));
+        assert_eq!(board.puzzle().extra_units(), &[Unit::Diagonal(0)]);
+
+        board.add_unit(Unit::Diagonal(1));
+        assert_eq!(board.solve_dlx(), SolveResult::Solved);
+        assert!(board.is_solved());
+        for unit in board.diagonals() {
+            let mut values = board.unit(unit).map(|fld| match fld {
+                Field::Value(v) => v,
+                Field::Options(_) => 0,
+            }).collect::<Vec<_>>();
+            values.sort_unstable();
+            assert_eq!(values, (1..=9).collect::<Vec<_>>());
+        }
+    }
+
+    #[test]
+    fn jigsaw() {
+        let regions = Board::<4>::parse_regions("aabb aabb cddd cccd").unwrap();
+        let mut board: Board<4> = "1... .... .... ....".parse().unwrap();
+        board.set_regions(&regions).unwrap();
+
+        assert_eq!(board.regions(), Some(&regions[..]));
+        assert!(board.neighbours(8).contains(&14));
+        assert!(!board.neighbours(8).contains(&15));
+        assert!(!board.candidates((1, 1)).unwrap().contains(&1));
+        assert_eq!(board.puzzle().regions(), Some(&regions[..]));
+
+        assert_eq!(board.solve_dlx(), SolveResult::Solved);
+        assert!(board.is_solved());
+        assert_eq!(
+            board.unit_cells(Unit::Box(2)).collect::<Vec<_>>(),
+            vec![8, 12, 13, 14]
+        );
+
+        let mut regions = regions;
+        regions[0] = 3;
+        assert_eq!(board.set_regions(&regions), Err(Error::InvalidRegion(0)));
+        regions[0] = 4;
+        assert_eq!(board.set_regions(&regions), Err(Error::InvalidRegion(4)));
+    }
+
+    #[test]
+    fn killer() {
+        let cages = Cage::parse_all(
+            "6: r1c1 r1c2 r1c3
+             6: r1c4 r2c4
+             5: r2c1 r3c1
+             5: r2c2 r2c3
+             5: r3c2 r3c3
+             6: r3c4 r4c4 r4c3
+             7: r4c1 r4c2"
+        ).unwrap();
+        let mut board = Board::<4>::new();
+        for cage in cages {
+            board.add_cage(cage).unwrap();
+        }
+
+        assert!(board.neighbours(7).contains(&3));
+        assert!(board.sees(4, 8));
+        assert_eq!(board.count_solutions(2), 1);
+        assert_eq!(board.puzzle().cages().len(), 7);
+
+        let mut copy = board.clone();
+        assert_eq!(copy.solve_dlx(), SolveResult::Solved);
+        assert_eq!(board.solve(), SolveResult::Solved);
+        assert_eq!(board.to_string(), "1234341221434321");
+        assert_eq!(board, copy);
+
+        let mut board = Board::<4>::new();
+        let cage = Cage { sum: 3, cells: vec![(0, 0), (0, 1)] };
+        board.add_cage(cage).unwrap();
+        board.set((0, 0), 2);
+        board.set((0, 1), 3);
+        assert!(!board.is_valid());
+
+        let cage = Cage { sum: 3, cells: vec![(0, 4)] };
+        assert_eq!(board.add_cage(cage), Err(Error::InvalidCell((0, 4))));
+    }
+
+    #[test]
+    fn anti_knight() {
+        let mut board = board_from_string(
+            "6....54.74.....2.....6...5...........158.......\
+             ........9.....8..38..4..11..9.8..."
+        );
+        board.add_move(Move::Knight);
+
+        assert!(board.neighbours(0).contains(&11));
+        assert!(board.sees(0, 19));
+        assert!(!board.candidates((2, 1)).unwrap().contains(&6));
+        assert_eq!(board.puzzle().moves(), &[Move::Knight]);
+        assert_eq!(board.count_solutions(2), 1);
+
+        assert_eq!(board.solve_dlx(), SolveResult::Solved);
+        assert!(board.is_valid());
+        assert_eq!(
+            board.to_string(),
+            "683295417451387269729641358872469135315872946946513872294156783\
+             538724691167938524"
         );
+
+        let mut board = Board::<9>::new();
+        board.add_move(Move::King);
+        board.set((0, 0), 1);
+        board.put_field(10, Field::Value(1), false);
+        assert!(!board.is_valid());
+    }
+
+    #[test]
+    fn shapes() {
+        let mut board = Board::<4>::new();
+        let thermo = Shape::Thermometer(vec![(0, 0), (0, 1), (0, 2), (0, 3)]);
+        board.add_shape(thermo).unwrap();
+        let arrow = Shape::Arrow { circle: (3, 3), cells: vec![(1, 2), (2, 0)] };
+        board.add_shape(arrow).unwrap();
+
+        assert!(board.sees(0, 3));
+        assert_eq!(board.puzzle().shapes().len(), 2);
+        assert_eq!(board.count_solutions(2), 1);
+
+        let mut copy = board.clone();
+        assert_eq!(copy.solve_dlx(), SolveResult::Solved);
+        assert!(copy.is_valid());
+        assert_eq!(board.solve(), SolveResult::Solved);
+        assert_eq!(board.to_string(), "1234341223414123");
+
+        let arrow = Shape::Arrow { circle: (4, 0), cells: vec![(0, 0)] };
+        assert_eq!(board.add_shape(arrow), Err(Error::InvalidCell((4, 0))));
+    }
+
+    #[test]
+    fn parities() {
+        let mut board = Board::<4>::new();
+        let marks = Board::<4>::parse_parities("eo.. .... .... ...e").unwrap();
+        board.set_parities(&marks).unwrap();
+
+        assert_eq!(board.parity((0, 1)), Some(Parity::Odd));
+        assert_eq!(board.parity((1, 1)), None);
+        assert_eq!(board.candidates((0, 0)).as_deref(), Some(&[2, 4][..]));
+        assert_eq!(board.candidates((0, 1)).as_deref(), Some(&[1, 3][..]));
+        assert_eq!(board.puzzle().candidates((3, 3)).as_deref(), Some(&[2, 4][..]));
+
+        let regions = Board::<4>::parse_regions("aabb aabb ccdd ccdd").unwrap();
+        board.set_regions(&regions).unwrap();
+        assert_eq!(board.candidates((3, 3)).as_deref(), Some(&[2, 4][..]));
+
+        let mut copy = board.clone();
+        assert_eq!(copy.solve_dlx(), SolveResult::Solved);
+        assert!(copy.is_valid());
+
+        board.put_field(0, Field::Value(1), false);
+        assert!(!board.is_valid());
+        assert_eq!(board.set_parities(&marks[1..]), Err(Error::TooFewCells(15)));
     }
 
     #[test]
@@ -300,7 +1642,7 @@ mod tests {
              ...   .53   ..1
              ...   ...   .73"
         );
-        board.solve();
+        assert_eq!(board.solve(), SolveResult::Solved);
 
         assert_eq!(
             board.to_num_vec(),
@@ -430,6 +1772,500 @@ mod tests {
         );
     }
 
+    #[test]
+    fn solve_forcing_chains() {
+        // http://opensudoku.moire.org/#about-puzzles
+        let mut board = board_from_string(
+            "...   9..   2.3
+             .26   ..3   .8.
+             83.   7..   ...
+
+             5.3   ..1   6..
+             ...   .3.   ...
+             ..2   5..   8.9
+
+             ...   ..7   .61
+             .6.   3..   47.
+             7.4   ..6   ..."
+        );
+        board.solve_with_config(&SolverConfig {
+            forcing_chain_depth: 1,
+            ..Default::default()
+        });
+
+        assert_eq!(
+            board.to_num_vec(),
+            vec![
+                1, 4, 7,   9, 6, 8,   2, 5, 3,
+                9, 2, 6,   1, 5, 3,   7, 8, 4,
+                8, 3, 5,   7, 4, 2,   9, 1, 6,
+
+                5, 9, 3,   2, 8, 1,   6, 4, 7,
+                4, 7, 8,   6, 3, 9,   1, 2, 5,
+                6, 1, 2,   5, 7, 4,   8, 3, 9,
+
+                3, 8, 9,   4, 2, 7,   5, 6, 1,
+                2, 6, 1,   3, 9, 5,   4, 7, 8,
+                7, 5, 4,   8, 1, 6,   3, 9, 2,
+            ]
+        );
+    }
+
+    #[test]
+    fn solve_backtracking() {
+        // https://sudoku.zeit.de/sudoku-sehr-schwer 26.10.2019
+        let mut board = board_from_string(
+            "4..   8..   3..
+             59.   ..2   7..
+             3..   574   ...
+
+             9..   6..   28.
+             6..   ..5   1..
+             81.   4..   ...
+
+             ...   ..9   ..2
+             28.   ...   .16
+             .4.   ...   ..."
+        );
+        board.solve_with_config(&SolverConfig {
+            backtracking: true,
+            ..Default::default()
+        });
+
+        assert_eq!(
+            board.to_num_vec(),
+            vec![
+                4, 2, 7,    8, 9, 6,    3, 5, 1,
+                5, 9, 8,    3, 1, 2,    7, 6, 4,
+                3, 6, 1,    5, 7, 4,    9, 2, 8,
+
+                9, 7, 4,    6, 3, 1,    2, 8, 5,
+                6, 3, 2,    9, 8, 5,    1, 4, 7,
+                8, 1, 5,    4, 2, 7,    6, 9, 3,
+
+                7, 5, 6,    1, 4, 9,    8, 3, 2,
+                2, 8, 9,    7, 5, 3,    4, 1, 6,
+                1, 4, 3,    2, 6, 8,    5, 7, 9,
+            ]
+        );
+    }
+
+    #[test]
+    fn solve_backtracking_empty() {
+        let mut board: Board = Board::new();
+        let result = board.solve_with_config(&SolverConfig {
+            backtracking: true,
+            ..Default::default()
+        });
+
+        assert_eq!(result, SolveResult::Solved);
+    }
+
+    #[test]
+    fn solve_dlx() {
+        // https://sudoku.zeit.de/sudoku-sehr-schwer 26.10.2019
+        let mut board = board_from_string(
+            "4..   8..   3..
+             59.   ..2   7..
+             3..   574   ...
+
+             9..   6..   28.
+             6..   ..5   1..
+             81.   4..   ...
+
+             ...   ..9   ..2
+             28.   ...   .16
+             .4.   ...   ..."
+        );
+
+        assert_eq!(board.count_solutions(2), 1);
+        assert_eq!(board.solve_dlx(), SolveResult::Solved);
+        assert_eq!(
+            board.to_num_vec(),
+            vec![
+                4, 2, 7,    8, 9, 6,    3, 5, 1,
+                5, 9, 8,    3, 1, 2,    7, 6, 4,
+                3, 6, 1,    5, 7, 4,    9, 2, 8,
+
+                9, 7, 4,    6, 3, 1,    2, 8, 5,
+                6, 3, 2,    9, 8, 5,    1, 4, 7,
+                8, 1, 5,    4, 2, 7,    6, 9, 3,
+
+                7, 5, 6,    1, 4, 9,    8, 3, 2,
+                2, 8, 9,    7, 5, 3,    4, 1, 6,
+                1, 4, 3,    2, 6, 8,    5, 7, 9,
+            ]
+        );
+    }
+
+    #[test]
+    fn solve_fast() {
+        let mut board: Board = concat!(
+            "4..8..3..59...27..3..574...9..6..28.6....51..81.4.....",
+            ".....9..228.....16.4......."
+        ).parse().unwrap();
+        let mut expected = board.clone();
+        expected.solve_dlx();
+
+        board.record_steps(true);
+        assert_eq!(board.solve_fast(), SolveResult::Solved);
+        assert_eq!(board.to_num_vec(), expected.to_num_vec());
+        let steps = board.steps().clone().unwrap();
+        assert_eq!(steps[0].reason, Reason::BitBoard);
+
+        let mut board: Board = format!("11{}", ".".repeat(79)).parse().unwrap();
+        assert_eq!(board.solve_fast(), SolveResult::Contradiction);
+
+        // the diagonals need the exact cover solver
+        let mut board = Board::<9>::new();
+        board.diagonals().collect::<Vec<_>>().into_iter()
+            .for_each(|unit| board.add_unit(unit));
+        board.record_steps(true);
+        assert_eq!(board.solve_fast(), SolveResult::Solved);
+        assert!(board.is_valid());
+        let steps = board.steps().clone().unwrap();
+        assert_eq!(steps[0].reason, Reason::ExactCover);
+    }
+
+    #[test]
+    fn other_sizes() {
+        let mut board: Board<4> = "1... ..2. .3.. ...4".parse().unwrap();
+        assert_eq!(board.box_size(), (2, 2));
+        assert_eq!(board.solve(), SolveResult::Solved);
+        assert!(board.is_solved());
+
+        let mut board = Board::<6>::new();
+        assert_eq!(board.box_size(), (2, 3));
+        board.set((0, 0), 6);
+        assert_eq!(board.candidates((1, 2)).as_deref(), Some(&[1, 2, 3, 4, 5][..]));
+        assert_eq!(board.candidate_count((2, 2)), 6);
+        assert_eq!(board.solve_dlx(), SolveResult::Solved);
+        assert!(board.is_solved());
+
+        let mut board = Board::<6>::with_boxes(3, 2);
+        board.set((0, 0), 6);
+        assert_eq!(board.candidate_count((2, 1)), 5);
+        assert_eq!(board.candidate_count((1, 2)), 6);
+
+        let mut board = Board::<16>::new();
+        assert_eq!(board.solve_dlx(), SolveResult::Solved);
+        assert!(board.is_solved());
+        assert_ne!(board, Board::new());
+    }
+
+    #[test]
+    #[should_panic]
+    fn boxes_must_fit() {
+        Board::<6>::with_boxes(2, 2);
+    }
+
+    #[test]
+    fn count_solutions() {
+        assert_eq!(Board::<9>::new().count_solutions(10), 10);
+
+        let board = board_from_string(&format!("11{}", ".".repeat(79)));
+        assert_eq!(board.count_solutions(10), 0);
+    }
+
+    #[test]
+    fn count_solutions_parallel() {
+        assert_eq!(Board::<9>::new().count_solutions_parallel(1000), 1000);
+        assert_eq!(Board::<4>::new().count_solutions_parallel(usize::MAX), 288);
+
+        let mut board = Board::<4>::new();
+        board.diagonals().collect::<Vec<_>>().into_iter()
+            .for_each(|unit| board.add_unit(unit));
+        let count = board.count_solutions(usize::MAX);
+        assert_eq!(board.count_solutions_parallel(usize::MAX), count);
+
+        let board = board_from_string(&format!("11{}", ".".repeat(79)));
+        assert_eq!(board.count_solutions_parallel(10), 0);
+    }
+
+    #[test]
+    fn valid_and_solved() {
+        let mut board: Board = Board::new();
+        assert!(board.is_valid());
+        assert!(!board.is_solved());
+
+        board.solve_dlx();
+        assert!(board.is_valid());
+        assert!(board.is_solved());
+
+        // same row, same column and same square
+        for (a, b) in [(0, 8), (0, 72), (30, 50)] {
+            let mut cells = vec!['.'; 81];
+            cells[a] = '5';
+            cells[b] = '5';
+
+            let board = board_from_string(&cells.iter().collect::<String>());
+            assert!(!board.is_valid());
+            assert!(!board.is_solved());
+        }
+    }
+
+    #[test]
+    fn candidates() {
+        let board = board_from_string(&format!("1.3{}", ".".repeat(78)));
+
+        assert_eq!(board.candidates((0, 0)), None);
+        assert_eq!(board.candidate_count((0, 0)), 0);
+        assert_eq!(
+            board.candidates((0, 1)).as_deref(),
+            Some(&[2, 4, 5, 6, 7, 8, 9][..])
+        );
+        assert_eq!(board.candidate_count((0, 1)), 7);
+        assert_eq!(board.candidate_count((8, 8)), 9);
+    }
+
+    #[test]
+    fn snapshot_restore() {
+        let mut board = board_from_string(&format!("1{}", ".".repeat(80)));
+        board.record_steps(true);
+        let snapshot = board.snapshot();
+
+        board.set((0, 1), 1);
+        assert!(board.contradiction().is_some());
+        board.set((1, 1), 2);
+        assert_eq!(board.steps().as_ref().map(Vec::len), Some(2));
+
+        board.restore(&snapshot);
+        assert_eq!(board.contradiction(), None);
+        assert_eq!(board.steps().as_ref().map(Vec::len), Some(0));
+        assert_eq!(board.to_string(), format!("1{}", ".".repeat(80)));
+        assert_eq!(board.candidate_count((1, 1)), 8);
+    }
+
+    #[test]
+    fn record_snapshots() {
+        let mut board = board_from_string(&format!("1{}", ".".repeat(80)));
+        board.record_snapshots(true);
+        board.eliminate(1, 5, Reason::Set);
+        board.set((0, 1), 2);
+        let snapshot = board.snapshot();
+        board.set((0, 2), 3);
+
+        let snapshots = board.snapshots().unwrap();
+        assert_eq!(snapshots.len(), board.steps().as_ref().map_or(0, Vec::len));
+        assert_eq!(snapshots[0].mask(1), 0b1_1110_1110);
+        assert_eq!(snapshots[0].value(0), Some(1));
+        assert_eq!(snapshots[1].field(1), Field::Value(2));
+        assert_eq!(
+            snapshots[0].field(1).candidates(),
+            Some(&[2, 3, 4, 6, 7, 8, 9][..])
+        );
+        assert_eq!(snapshots[1].mask(2), 0b1_1111_1100);
+        assert_eq!(snapshots[2].value(2), Some(3));
+
+        board.restore(&snapshot);
+        assert_eq!(board.snapshots().map(<[_]>::len), Some(2));
+        board.record_steps(true);
+        assert_eq!(board.snapshots().map(<[_]>::len), Some(0));
+        board.record_steps(false);
+        assert_eq!(board.snapshots(), None);
+    }
+
+    #[test]
+    fn changed_since() {
+        let mut board = board_from_string(&format!("1{}", ".".repeat(80)));
+        assert_eq!(board.changed_since(0).count(), 81);
+
+        let since = board.generation();
+        board.set((8, 8), 2);
+        // the cell and its peers, which lose the candidate 2
+        let changed = board.changed_since(since).collect::<Vec<_>>();
+        assert_eq!(changed.len(), 21);
+        assert_eq!(changed.last(), Some(&80));
+
+        let since = board.generation();
+        assert!(!board.eliminate(0, 3, Reason::Set));
+        assert!(board.eliminate(40, 3, Reason::Set));
+        assert_eq!(board.changed_since(since).collect::<Vec<_>>(), vec![40]);
+
+        let snapshot = board.snapshot();
+        let since = board.generation();
+        board.restore(&snapshot);
+        assert_eq!(board.changed_since(since).count(), 81);
+    }
+
+    #[test]
+    fn givens() {
+        let mut board = board_from_string(&format!("12{}", ".".repeat(79)));
+        board.set((0, 2), 3);
+
+        assert!(board.is_given((0, 0)));
+        assert!(board.is_given((0, 1)));
+        assert!(!board.is_given((0, 2)));
+        assert!(!board.is_given((0, 3)));
+        assert_eq!(board.puzzle().to_string(), format!("12{}", ".".repeat(79)));
+
+        let snapshot = board.snapshot();
+        board.set((0, 0), 4);
+        assert!(!board.is_given((0, 0)));
+        board.restore(&snapshot);
+        assert!(board.is_given((0, 0)));
+    }
+
+    #[test]
+    fn clone_eq_hash() {
+        use std::collections::HashSet;
+
+        let mut board = board_from_string(&format!("1{}", ".".repeat(80)));
+        let mut copy = board.clone();
+        assert_eq!(board, copy);
+
+        copy.record_steps(true);
+        copy.set((0, 1), 2);
+        assert_ne!(board, copy);
+
+        board.set((0, 1), 2);
+        assert_eq!(board, copy);
+
+        // the lazily computed tables don't take part in hashing
+        #[allow(clippy::mutable_key_type)]
+        let set: HashSet<_> = vec![board, copy, Board::new()].into_iter().collect();
+        assert_eq!(set.len(), 2);
+    }
+
+    #[test]
+    fn try_set() {
+        let mut board = board_from_string(&format!("1{}", ".".repeat(80)));
+
+        assert_eq!(
+            board.try_set((2, 2), 1),
+            Err(Error::Conflict(ConflictError { cell: (0, 0), value: 1 }))
+        );
+        assert_eq!(board.candidate_count((2, 2)), 8);
+        assert_eq!(
+            board.try_set((8, 0), 1).unwrap_err().to_string(),
+            "1 is already placed at (1, 1)"
+        );
+
+        assert_eq!(board.try_set((2, 3), 1), Ok(()));
+        assert!(matches!(board.field((2, 3)), Field::Value(1)));
+        assert!(board.try_set((2, 4), 1).is_err());
+        assert_eq!(board.try_set((2, 5), 10), Err(Error::InvalidValue(10)));
+        assert_eq!(board.try_set((0, 9), 2), Err(Error::InvalidCell((0, 9))));
+        assert_eq!(board.try_set((9, 0), 2), Err(Error::InvalidCell((9, 0))));
+        assert_eq!(board.candidate_count((1, 0)), 8);
+    }
+
+    #[test]
+    fn fill() {
+        let mut board: Board = Board::new();
+        assert_eq!(
+            board.fill(std::iter::repeat_n(None, 82)),
+            Err(Error::TooManyCells(82))
+        );
+        assert_eq!(
+            board.fill(vec![None, Some(2)].into_iter()),
+            Err(Error::TooFewCells(2))
+        );
+        let empty = || std::iter::repeat_n(None, 79);
+        let data = vec![Some(1), Some(0)].into_iter().chain(empty());
+        assert_eq!(board.fill(data), Err(Error::InvalidValue(0)));
+        assert_eq!(board, Board::new());
+
+        let data = vec![None, Some(2)].into_iter().chain(empty());
+        assert_eq!(board.fill(data), Ok(()));
+        assert!(matches!(board.field((0, 1)), Field::Value(2)));
+        assert!(board.is_given((0, 1)));
+    }
+
+    #[test]
+    fn apply_steps() {
+        let puzzle = board_from_string(
+            "..4..5.2..52.3684..16.82...2...5.4......\
+             1.73.641.....8...8....712......47.....1.9"
+        );
+        let mut board = puzzle.clone();
+        board.record_steps(true);
+        assert_eq!(board.solve(), SolveResult::Solved);
+        let steps = board.steps().clone().unwrap();
+
+        let mut replay = puzzle.clone();
+        assert_eq!(replay.apply_steps(&steps), Ok(()));
+        assert_eq!(replay, board);
+
+        let mut replay = puzzle.clone();
+        assert_eq!(replay.apply_steps(&steps[..3]), Ok(()));
+        assert_eq!(replay.apply_steps(&steps[2..]), Err(Error::InvalidStep(0)));
+
+        let mut replay = puzzle;
+        let step = Step {
+            cell: (0, 0),
+            value: 1,
+            action: Action::Place,
+            reason: Reason::Set,
+        };
+        assert_eq!(replay.apply_steps(&[step]), Err(Error::InvalidStep(0)));
+    }
+
+    #[test]
+    fn contradiction_empty_cell() {
+        let board = board_from_string(&format!(
+            "12345678.
+             ........9{}", ".".repeat(63)
+        ));
+
+        assert_eq!(board.status(), SolveResult::Contradiction);
+        assert_eq!(
+            board.contradiction(),
+            Some(Contradiction { cell: (0, 8), cause: Some(((1, 8), 9)) })
+        );
+    }
+
+    #[test]
+    fn contradiction_same_value() {
+        let mut board = board_from_string(
+            &format!("1..   ...   ...   2{}", ".".repeat(71))
+        );
+        assert_eq!(board.contradiction(), None);
+
+        board.set((1, 1), 2);
+        assert_eq!(
+            board.contradiction(),
+            Some(Contradiction { cell: (1, 0), cause: Some(((1, 1), 2)) })
+        );
+        assert_eq!(board.solve(), SolveResult::Contradiction);
+    }
+
+    #[test]
+    fn inline_candidates() {
+        let mut opts = (1..=9).collect::<Candidates>();
+        opts.retain(|v| v % 3 != 0);
+        assert_eq!(*opts, [1, 2, 4, 5, 7, 8]);
+        assert_eq!(opts.into_iter().sum::<u8>(), 27);
+        let copy = opts.iter().copied().collect();
+        assert_eq!(Field::Options(opts), Field::Options(copy));
+        // the cells are in the board as value and candidate mask each
+        let cells = 81 * (std::mem::size_of::<u8>() + std::mem::size_of::<u64>());
+        assert!(std::mem::size_of::<Board>() > cells);
+        assert!(std::mem::size_of::<Board>() < 81 * std::mem::size_of::<Field>());
+
+        #[cfg(feature = "serde")]
+        assert!(serde_json::from_str::<Candidates>(&format!("{:?}", [1; 36])).is_err());
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn serde_round_trip() {
+        let mut board = board_from_string(&format!("12{}", ".".repeat(79)));
+        board.record_steps(true);
+        board.solve();
+
+        let json = serde_json::to_string(&board).unwrap();
+        let copy: Board = serde_json::from_str(&json).unwrap();
+        assert_eq!(copy, board);
+        assert_eq!(copy.steps(), board.steps());
+        assert_eq!(serde_json::to_string(&copy).unwrap(), json);
+
+        assert!(json.contains(r#"{"Options":[3,4,5,6,7,8,9]}"#));
+        assert!(serde_json::from_str::<Board>(
+            r#"{"data":[{"Value":1}],"steps":null,"contradiction":null}"#
+        ).is_err());
+    }
+
     #[test]
     fn solve_by_neighbourhood() {
         let mut board = board_from_string(
@@ -439,9 +2275,13 @@ mod tests {
 
              ...   2..   ...
              ...   ...   ...
+             ...   ...   ...
+
+             ...   ...   ...
+             ...   ...   ...
              ...   ...   ..."
         );
-        board.solve();
+        assert_eq!(board.solve(), SolveResult::Stuck);
 
         // because you know 2 must be on L1C1 or L1C2 it can not be on L1C4
         // and hence, must be on L3C4
@@ -501,4 +2341,51 @@ mod tests {
             ]
         );
     }
+
+    #[test]
+    fn solutions() {
+        let board: Board<4> = "1... ..1. .1.. ...1".parse().unwrap();
+        let solutions = board.solutions(10);
+
+        assert_eq!(solutions.len(), board.count_solutions(10));
+        assert!(solutions.len() > 1);
+        assert!(solutions.iter().all(Board::is_solved));
+        assert_ne!(solutions[0], solutions[1]);
+        assert_eq!(board.solutions(1).len(), 1);
+    }
+
+    #[test]
+    fn conflicts() {
+        let mut board = Board::<9>::new();
+        assert!(board.conflicts().is_empty());
+
+        board.fill((0..81).map(|i| match i {
+            0 | 10 | 72 => Some(5),
+            40 => Some(1),
+            _ => None,
+        })).unwrap();
+        assert_eq!(board.conflicts(), vec![(0, 0), (1, 1), (8, 0)]);
+    }
+
+    #[test]
+    fn rate() {
+        let board = board_from_string(
+            "..4   ..5  .2.
+             .52   .36  84.
+             .16   .82  ...
+
+             2..   .5.  4..
+             ...   .1.  73.
+             641   ...  ..8
+
+             ...   8..  ..7
+             12.   ...  ..4
+             7..   ...  1.9"
+        );
+        assert!(board.rate().is_some());
+        assert!(board.rate() <= Some(Difficulty::Medium));
+
+        let board: Board = format!("11{}", ".".repeat(79)).parse().unwrap();
+        assert_eq!(board.rate(), None);
+    }
 }
@@ -0,0 +1,108 @@
+use crate::error::Error;
+
+/// The characters of a puzzle that stand for the values 1 to `N` instead of
+/// the digits, like the letters of Wordoku
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Symbols(Vec<char>);
+
+impl Symbols {
+    /// The symbols for the values from 1 on, one character each; there must
+    /// be `size` different letters or digits
+    pub fn new(symbols: &str, size: usize) -> Result<Self, Error> {
+        let chars = symbols.chars().collect::<Vec<_>>();
+        let valid = chars.len() == size
+            && chars.iter().all(|c| c.is_alphanumeric())
+            && chars.iter().enumerate().all(|(i, c)| !chars[..i].contains(c));
+
+        if valid {
+            Ok(Symbols(chars))
+        } else {
+            Err(Error::InvalidSymbols(size))
+        }
+    }
+
+    /// The symbol of `value`
+    pub fn symbol(&self, value: u8) -> char {
+        self.0[value as usize - 1]
+    }
+
+    /// The value of the symbol `c`
+    pub fn value(&self, c: char) -> Option<u8> {
+        self.0.iter().position(|&s| s == c).map(|i| i as u8 + 1)
+    }
+
+    /// Translates the input `text` to the digits and letters of the values
+    /// that the parser reads; the other characters that would be values
+    /// become empty cells
+    pub fn decode(&self, text: &str) -> String {
+        text.chars()
+            .map(|c| match self.value(c) {
+                Some(val) => value_char(val),
+                None if self.is_value_char(c) => '.',
+                None => c,
+            })
+            .collect()
+    }
+
+    // Whether `c` stands for one of the values in the usual notation
+    fn is_value_char(&self, c: char) -> bool {
+        matches!(c.to_digit(36), Some(val) if val >= 1 && val as usize <= self.0.len())
+    }
+}
+
+// The digit or upper-case letter of `val`
+fn value_char(val: u8) -> char {
+    std::char::from_digit(u32::from(val), 36).map_or('?', |c| c.to_ascii_uppercase())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::sudoku::Board;
+
+    #[test]
+    fn new() {
+        assert!(Symbols::new("ABCDEFGHI", 9).is_ok());
+        assert_eq!(Symbols::new("ABCD", 9), Err(Error::InvalidSymbols(9)));
+        assert_eq!(Symbols::new("ABCA", 4), Err(Error::InvalidSymbols(4)));
+        assert_eq!(Symbols::new("AB.D", 4), Err(Error::InvalidSymbols(4)));
+        assert_eq!(Symbols::new("!@#$", 4), Err(Error::InvalidSymbols(4)));
+    }
+
+    #[test]
+    fn translate() {
+        let symbols = Symbols::new("WORD", 4).unwrap();
+        assert_eq!(symbols.symbol(3), 'R');
+        assert_eq!(symbols.value('D'), Some(4));
+        assert_eq!(symbols.decode("W.R1\nDx.."), "1.3.\n4x..");
+
+        let board: Board<4> = symbols.decode("WORDRDWO........").parse().unwrap();
+        assert_eq!(board.to_string(), "12343412........");
+        assert_eq!(board.with_symbols(Some(&symbols)).to_string(), "WORDRDWO........");
+        assert_eq!(
+            format!("{:#}", board.with_symbols(Some(&symbols))),
+            "WO   RD\nRD   WO\n\n..   ..\n..   .."
+        );
+        assert_eq!(
+            board.pretty().labels(true).symbols(Some(&symbols)).to_string(),
+            "    1 2  3 4\n\
+             \x20 ┌─────┬─────┐\n\
+             1 │ W O │ R D │\n\
+             2 │ R D │ W O │\n\
+             \x20 ├─────┼─────┤\n\
+             3 │ . . │ . . │\n\
+             4 │ . . │ . . │\n\
+             \x20 └─────┴─────┘"
+        );
+    }
+
+    #[test]
+    fn digits() {
+        // the digits in another order
+        let symbols = Symbols::new("4321", 4).unwrap();
+        assert_eq!(symbols.decode("41.2"), "14.3");
+
+        let board: Board<4> = "14.3............".parse().unwrap();
+        assert!(board.with_symbols(Some(&symbols)).to_string().starts_with("41.2"));
+    }
+}
@@ -0,0 +1,199 @@
+use std::{
+    fmt,
+    sync::{Arc, Mutex},
+};
+
+use crate::sudoku::Board;
+use crate::unit::Unit;
+
+// The cells of every unit a board can have, for its boxes or regions
+//
+// The units lie one after another, `N` cells each: the rows, the columns, the
+// boxes, the disjoint groups, the two diagonals and the windows.
+pub(crate) struct UnitTable {
+    cells: Vec<usize>,
+}
+
+impl UnitTable {
+    pub fn new<const N: usize>(board: &Board<N>) -> Self {
+        let (rows, cols) = board.box_size();
+        let windows = board.windows().count();
+        let mut cells = Vec::with_capacity((4 * N + 2 + windows) * N);
+
+        for r in 0..N {
+            cells.extend((0..N).map(|i| r * N + i));
+        }
+
+        for c in 0..N {
+            cells.extend((0..N).map(|i| i * N + c));
+        }
+
+        match board.regions() {
+            Some(regions) => {
+                for b in 0..N {
+                    cells.extend((0..N * N).filter(|&idx| regions[idx] == b));
+                }
+            }
+            None => {
+                for b in 0..N {
+                    let (row, col) = (b / (N / cols) * rows, b % (N / cols) * cols);
+                    cells.extend((0..N).map(|i| (row + i / cols) * N + col + i % cols));
+                }
+            }
+        }
+
+        for g in 0..N {
+            cells.extend((0..N).map(|i| {
+                let (row, col) = (i / (N / cols) * rows, i % (N / cols) * cols);
+                (row + g / cols) * N + col + g % cols
+            }));
+        }
+
+        cells.extend((0..N).map(|i| i * N + i));
+        cells.extend((0..N).map(|i| i * N + N - 1 - i));
+
+        let per_row = (N - 1) / (cols + 1);
+        for w in 0..windows {
+            let row = 1 + w / per_row * (rows + 1);
+            let col = 1 + w % per_row * (cols + 1);
+            cells.extend((0..N).map(|i| (row + i / cols) * N + col + i % cols));
+        }
+
+        UnitTable { cells }
+    }
+
+    // The table of the boards with `N` rows and the boxes of `board`, but
+    // without regions
+    pub fn shared<const N: usize>(board: &Board<N>) -> Arc<Self> {
+        static TABLES: Tables<UnitTable> = Mutex::new(Vec::new());
+        shared(&TABLES, (N, board.box_size().0), || UnitTable::new(board))
+    }
+
+    // The indices of the cells of `unit`
+    pub fn cells<const N: usize>(&self, unit: Unit) -> &[usize] {
+        let slot = match unit {
+            Unit::Row(r) => r,
+            Unit::Col(c) => N + c,
+            Unit::Box(b) => 2 * N + b,
+            Unit::Group(g) => 3 * N + g,
+            Unit::Diagonal(d) => 4 * N + d.min(1),
+            Unit::Window(w) => 4 * N + 2 + w,
+        };
+
+        &self.cells[slot * N..(slot + 1) * N]
+    }
+}
+
+impl fmt::Debug for UnitTable {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_struct("UnitTable").finish_non_exhaustive()
+    }
+}
+
+// The peers of every cell by all constraints of a board, without duplicates,
+// and the units of the constraints
+pub(crate) struct PeerTable {
+    // the peers of the cell `idx` are `peers[start[idx]..start[idx + 1]]`
+    start: Vec<usize>,
+    peers: Vec<usize>,
+    units: Vec<Unit>,
+}
+
+impl PeerTable {
+    pub fn new<const N: usize>(board: &Board<N>) -> Self {
+        let mut start = Vec::with_capacity(N * N + 1);
+        let mut peers = Vec::with_capacity(N * N * 3 * N);
+
+        for idx in 0..N * N {
+            start.push(peers.len());
+            // the peers of `idx` as bit set; N is at most 35
+            let mut seen = [0u64; 35 * 35 / 64 + 1];
+
+            for constraint in board.constraints() {
+                for (row, col) in constraint.peers(board, (idx / N, idx % N)) {
+                    let other = row * N + col;
+                    if seen[other / 64] & 1 << (other % 64) == 0 {
+                        seen[other / 64] |= 1 << (other % 64);
+                        peers.push(other);
+                    }
+                }
+            }
+        }
+        start.push(peers.len());
+
+        let units = board.constraints()
+            .flat_map(|constraint| constraint.units(board))
+            .collect();
+
+        PeerTable { start, peers, units }
+    }
+
+    // The table of the boards with `N` rows and the boxes of `board`, but
+    // without variants
+    pub fn shared<const N: usize>(board: &Board<N>) -> Arc<Self> {
+        static TABLES: Tables<PeerTable> = Mutex::new(Vec::new());
+        shared(&TABLES, (N, board.box_size().0), || PeerTable::new(board))
+    }
+
+    // The indices of the peers of the cell `idx`
+    pub fn peers(&self, idx: usize) -> &[usize] {
+        &self.peers[self.start[idx]..self.start[idx + 1]]
+    }
+
+    // The units of all constraints
+    pub fn units(&self) -> &[Unit] {
+        &self.units
+    }
+}
+
+impl fmt::Debug for PeerTable {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_struct("PeerTable").finish_non_exhaustive()
+    }
+}
+
+// The tables by the number of rows and the rows of the boxes
+type Tables<T> = Mutex<Vec<((usize, usize), Arc<T>)>>;
+
+// The table for `key`, made by `make` the first time
+fn shared<T>(
+    tables: &Tables<T>, key: (usize, usize), make: impl FnOnce() -> T
+) -> Arc<T> {
+    let mut tables = tables.lock().unwrap_or_else(|err| err.into_inner());
+    if let Some((_, table)) = tables.iter().find(|(k, _)| *k == key) {
+        return Arc::clone(table);
+    }
+
+    let table = Arc::new(make());
+    tables.push((key, Arc::clone(&table)));
+    table
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn units() {
+        let board = Board::<6>::new();
+        let table = UnitTable::new(&board);
+        assert_eq!(table.cells::<6>(Unit::Col(5)), [5, 11, 17, 23, 29, 35]);
+        assert_eq!(table.cells::<6>(Unit::Box(3)), [15, 16, 17, 21, 22, 23]);
+        assert_eq!(table.cells::<6>(Unit::Diagonal(1)), [5, 10, 15, 20, 25, 30]);
+    }
+
+    #[test]
+    fn shared_peers() {
+        let table = PeerTable::shared(&Board::<9>::new());
+        assert!(Arc::ptr_eq(&table, &PeerTable::shared(&Board::<9>::new())));
+        assert_eq!(table.peers(80).len(), 20);
+
+        let wide = PeerTable::shared(&Board::<6>::with_boxes(3, 2));
+        assert!(!Arc::ptr_eq(&wide, &PeerTable::shared(&Board::<6>::new())));
+        assert!(wide.peers(0).contains(&13));
+        assert_eq!(wide.units().len(), 18);
+
+        let units = UnitTable::shared(&Board::<9>::new());
+        assert!(Arc::ptr_eq(&units, &UnitTable::shared(&Board::<9>::new())));
+    }
+}
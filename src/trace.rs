@@ -0,0 +1,174 @@
+use std::borrow::Cow;
+
+use crate::step::{Action, CellName, Reason, Step};
+use crate::sudoku::Board;
+use crate::unit::Unit;
+
+/// The version of the [`Trace`] schema; it changes only if a field of a trace
+/// changes its meaning or goes away, new fields and techniques keep it
+pub const TRACE_VERSION: u32 = 1;
+
+/// The recorded steps of a board as JSON document for tools, like
+/// `{"version": 1, "size": 9, "puzzle": "...", "steps": [{"action": "place",
+/// "cell": "r1c3", "value": 4, "technique": "hidden-single", "unit": {"kind":
+/// "box", "index": 1}}]}`
+///
+/// Unlike [`Step`], the schema stays the same while the solver evolves: the
+/// techniques have fixed identifiers and cells and units are counted from 1.
+#[derive(Clone, Debug, PartialEq, Eq, serde::Serialize, serde::Deserialize)]
+pub struct Trace {
+    /// [`TRACE_VERSION`] of the writer
+    pub version: u32,
+    /// The number of rows and columns
+    pub size: usize,
+    /// The clues as line with `.` for the other cells
+    pub puzzle: String,
+    pub steps: Vec<TraceStep>,
+}
+
+/// A placement or elimination of a [`Trace`]
+#[derive(Clone, Debug, PartialEq, Eq, serde::Serialize, serde::Deserialize)]
+pub struct TraceStep {
+    /// `place` or `eliminate`
+    pub action: Cow<'static, str>,
+    /// The cell like `r4c7`
+    pub cell: String,
+    pub value: u8,
+    /// The technique like `sole-candidate`, see [`technique_id`]
+    pub technique: Cow<'static, str>,
+    /// The name of a custom technique
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub name: Option<String>,
+    /// The unit of a hidden single or pointing candidates
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub unit: Option<TraceUnit>,
+}
+
+/// A unit of a [`TraceStep`]
+#[derive(Clone, Debug, PartialEq, Eq, serde::Serialize, serde::Deserialize)]
+pub struct TraceUnit {
+    /// `row`, `column`, `box`, `diagonal`, `window` or `group`
+    pub kind: Cow<'static, str>,
+    /// The number of the unit counted from 1 like in [`Unit`]'s display; the
+    /// main diagonal is 1 and the anti-diagonal 2
+    pub index: usize,
+}
+
+/// The identifier of the technique behind `reason` in a [`Trace`]: `given`,
+/// `set`, `sole-candidate`, `hidden-single`, `pointing`, `remote-pair`,
+/// `cage-sum`, `thermometer`, `arrow-sum`, `forcing-chain`, `assumption`,
+/// `guess`, `exact-cover`, `bit-board`, `overlap` or `custom`
+pub fn technique_id(reason: &Reason) -> &'static str {
+    match reason {
+        Reason::Given => "given",
+        Reason::Set => "set",
+        Reason::SoleCandidate => "sole-candidate",
+        Reason::HiddenSingle(_) => "hidden-single",
+        Reason::Pointing(_) => "pointing",
+        Reason::RemotePair => "remote-pair",
+        Reason::CageSum => "cage-sum",
+        Reason::Thermometer => "thermometer",
+        Reason::ArrowSum => "arrow-sum",
+        Reason::ForcingChain => "forcing-chain",
+        Reason::Assumption => "assumption",
+        Reason::Guess => "guess",
+        Reason::ExactCover => "exact-cover",
+        Reason::BitBoard => "bit-board",
+        Reason::Overlap => "overlap",
+        Reason::Custom(_) => "custom",
+    }
+}
+
+impl From<&Step> for TraceStep {
+    fn from(step: &Step) -> Self {
+        let action = match step.action {
+            Action::Place => "place",
+            Action::Eliminate => "eliminate",
+        };
+        let unit = match step.reason {
+            Reason::HiddenSingle(unit) | Reason::Pointing(unit) => Some(unit.into()),
+            _ => None,
+        };
+        let name = match &step.reason {
+            Reason::Custom(name) => Some(name.to_string()),
+            _ => None,
+        };
+
+        TraceStep {
+            action: action.into(),
+            cell: CellName(step.cell).to_string(),
+            value: step.value,
+            technique: technique_id(&step.reason).into(),
+            name,
+            unit,
+        }
+    }
+}
+
+impl From<Unit> for TraceUnit {
+    fn from(unit: Unit) -> Self {
+        let (kind, index) = match unit {
+            Unit::Row(r) => ("row", r),
+            Unit::Col(c) => ("column", c),
+            Unit::Box(b) => ("box", b),
+            Unit::Diagonal(d) => ("diagonal", d),
+            Unit::Window(w) => ("window", w),
+            Unit::Group(g) => ("group", g),
+        };
+
+        TraceUnit { kind: kind.into(), index: index + 1 }
+    }
+}
+
+impl<const N: usize> Board<N> {
+    /// The recorded [`steps`](Self::steps) as [`Trace`], empty if the board
+    /// doesn't record them
+    pub fn trace(&self) -> Trace {
+        Trace {
+            version: TRACE_VERSION,
+            size: N,
+            puzzle: self.puzzle().to_string(),
+            steps: self.steps()
+                .as_deref()
+                .unwrap_or(&[])
+                .iter()
+                .map(TraceStep::from)
+                .collect(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn trace() {
+        let mut board: Board<4> = "1.....2.....3...".parse().unwrap();
+        board.record_steps(true);
+        board.solve();
+
+        let trace = board.trace();
+        assert_eq!((trace.version, trace.size), (TRACE_VERSION, 4));
+        assert_eq!(trace.puzzle, "1.....2.....3...");
+        assert_eq!(trace.steps.len(), board.steps().as_ref().unwrap().len());
+        assert!(trace.steps.iter().all(|step| step.action == "place"
+            || step.action == "eliminate"));
+
+        let step = Step {
+            cell: (3, 6),
+            value: 5,
+            action: Action::Place,
+            reason: Reason::HiddenSingle(Unit::Box(5)),
+        };
+        assert_eq!(
+            serde_json::to_string(&TraceStep::from(&step)).unwrap(),
+            r#"{"action":"place","cell":"r4c7","value":5,"technique":"hidden-single","#
+                .to_owned()
+                + r#""unit":{"kind":"box","index":6}}"#
+        );
+
+        let json = serde_json::to_string(&trace).unwrap();
+        assert_eq!(serde_json::from_str::<Trace>(&json).unwrap(), trace);
+    }
+}
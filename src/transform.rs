@@ -0,0 +1,558 @@
+use std::cmp::Ordering;
+
+use crate::error::Error;
+use crate::sudoku::{Board, Field};
+
+// The largest size of boards with a canonical form
+const MAX_CANONICAL: usize = 9;
+
+/// A change of a board that keeps a valid solution valid: turning, mirroring,
+/// permuting bands, stacks, rows or columns and relabeling the values
+///
+/// The operations apply one after the other to the result of the ones before,
+/// e.g. `Transform::new(&board).rotate(1).relabel(&values)`, and
+/// [`Board::transform`] applies all of them at once. Bands are the rows of
+/// boxes and stacks the columns of boxes.
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+pub struct Transform {
+    size: usize,
+    // the boxes of the result
+    box_rows: usize,
+    box_cols: usize,
+    // the cell of the original board for each cell of the result
+    cells: Vec<usize>,
+    // the value of the result for each value from 1
+    values: Vec<u8>,
+}
+
+impl Transform {
+    /// The transformation that changes nothing on boards like `board`
+    pub fn new<const N: usize>(board: &Board<N>) -> Self {
+        let (box_rows, box_cols) = board.box_size();
+        Transform {
+            size: N,
+            box_rows,
+            box_cols,
+            cells: (0..N * N).collect(),
+            values: (1..=N as u8).collect(),
+        }
+    }
+
+    /// The size of the boxes of the result as `(rows, columns)`
+    pub fn box_size(&self) -> (usize, usize) {
+        (self.box_rows, self.box_cols)
+    }
+
+    /// The position in the result of the cell at `pos` of the original board
+    pub fn cell(&self, pos: (usize, usize)) -> (usize, usize) {
+        let old = pos.0 * self.size + pos.1;
+        let idx = self.cells.iter().position(|&c| c == old).expect("a cell");
+        (idx / self.size, idx % self.size)
+    }
+
+    /// The value in the result of `val` of the original board
+    pub fn value(&self, val: u8) -> u8 {
+        self.values[val as usize - 1]
+    }
+
+    /// Turns the board by `quarter_turns` quarters clockwise; odd turns swap
+    /// the rows and columns of the boxes
+    pub fn rotate(self, quarter_turns: usize) -> Self {
+        (0..quarter_turns % 4).fold(self, |t, _| {
+            let n = t.size;
+            t.swap_boxes().map_cells(|(row, col)| (n - 1 - col, row))
+        })
+    }
+
+    /// Mirrors the board at the diagonal from the top left
+    pub fn transpose(self) -> Self {
+        self.swap_boxes().map_cells(|(row, col)| (col, row))
+    }
+
+    /// Swaps the left and right half of the board
+    pub fn mirror(self) -> Self {
+        let n = self.size;
+        self.map_cells(|(row, col)| (row, n - 1 - col))
+    }
+
+    /// Puts the band `bands[i]` in the place of band `i`
+    ///
+    /// # Panics
+    ///
+    /// Panics if `bands` is no permutation of the bands.
+    pub fn permute_bands(self, bands: &[usize]) -> Self {
+        let rows = self.box_rows;
+        check_permutation(bands, self.size / rows);
+        let bands = bands.to_vec();
+        self.map_cells(|(row, col)| (bands[row / rows] * rows + row % rows, col))
+    }
+
+    /// Puts the stack `stacks[i]` in the place of stack `i`
+    ///
+    /// # Panics
+    ///
+    /// Panics if `stacks` is no permutation of the stacks.
+    pub fn permute_stacks(self, stacks: &[usize]) -> Self {
+        let cols = self.box_cols;
+        check_permutation(stacks, self.size / cols);
+        let stacks = stacks.to_vec();
+        self.map_cells(|(row, col)| (row, stacks[col / cols] * cols + col % cols))
+    }
+
+    /// Puts the row `rows[i]` of `band` in the place of its row `i`
+    ///
+    /// # Panics
+    ///
+    /// Panics if `rows` is no permutation of the rows of a band.
+    pub fn permute_rows(self, band: usize, rows: &[usize]) -> Self {
+        let height = self.box_rows;
+        check_permutation(rows, height);
+        let rows = rows.to_vec();
+        self.map_cells(|(row, col)| match row / height {
+            b if b == band => (b * height + rows[row % height], col),
+            _ => (row, col),
+        })
+    }
+
+    /// Puts the column `cols[i]` of `stack` in the place of its column `i`
+    ///
+    /// # Panics
+    ///
+    /// Panics if `cols` is no permutation of the columns of a stack.
+    pub fn permute_cols(self, stack: usize, cols: &[usize]) -> Self {
+        let width = self.box_cols;
+        check_permutation(cols, width);
+        let cols = cols.to_vec();
+        self.map_cells(|(row, col)| match col / width {
+            s if s == stack => (row, s * width + cols[col % width]),
+            _ => (row, col),
+        })
+    }
+
+    /// Replaces every value `v` with `values[v - 1]`
+    ///
+    /// # Panics
+    ///
+    /// Panics if `values` is no permutation of the values.
+    pub fn relabel(mut self, values: &[u8]) -> Self {
+        let indices = values.iter().map(|&v| (v as usize).wrapping_sub(1));
+        check_permutation(&indices.collect::<Vec<_>>(), self.size);
+        self.values = self.values.iter().map(|&v| values[v as usize - 1]).collect();
+        self
+    }
+
+    fn swap_boxes(mut self) -> Self {
+        std::mem::swap(&mut self.box_rows, &mut self.box_cols);
+        self
+    }
+
+    // Takes for each cell of the result the cell `source((row, col))` of the
+    // result so far
+    fn map_cells(mut self, source: impl Fn((usize, usize)) -> (usize, usize)) -> Self {
+        let n = self.size;
+        self.cells = (0..n * n)
+            .map(|idx| {
+                let (row, col) = source((idx / n, idx % n));
+                self.cells[row * n + col]
+            })
+            .collect();
+        self
+    }
+}
+
+fn check_permutation(items: &[usize], len: usize) {
+    let mut seen = vec![false; len];
+    let valid = items.len() == len
+        && items.iter().all(|&i| i < len && !std::mem::replace(&mut seen[i], true));
+    assert!(valid, "{:?} is no permutation of 0 to {}", items, len.saturating_sub(1));
+}
+
+impl<const N: usize> Board<N> {
+    /// A new board with the values, candidates and givens moved and relabeled
+    /// by `transform`; the variants and steps are left out, as most changes
+    /// break them
+    ///
+    /// # Panics
+    ///
+    /// Panics if `transform` is for boards of another size.
+    pub fn transform(&self, transform: &Transform) -> Self {
+        assert!(transform.size == N, "Transform for another size than {}", N);
+
+        let (rows, cols) = transform.box_size();
+        let mut board = Self::with_boxes(rows, cols);
+        for (idx, &old) in transform.cells.iter().enumerate() {
+            let fld = match &self.field_at(old) {
+                Field::Value(v) => Field::Value(transform.value(*v)),
+                Field::Options(opts) => {
+                    let mut opts = opts.iter()
+                        .map(|&v| transform.value(v))
+                        .collect::<Vec<_>>();
+                    opts.sort_unstable();
+                    Field::Options(opts.into_iter().collect())
+                }
+            };
+            board.put_field(idx, fld, self.is_given((old / N, old % N)));
+        }
+
+        board
+    }
+
+    /// The board turned by `quarter_turns` quarters clockwise
+    pub fn rotate(&self, quarter_turns: usize) -> Self {
+        self.transform(&Transform::new(self).rotate(quarter_turns))
+    }
+
+    /// The board mirrored at the diagonal from the top left
+    pub fn transpose(&self) -> Self {
+        self.transform(&Transform::new(self).transpose())
+    }
+
+    /// The board with the left and right half swapped
+    pub fn mirror(&self) -> Self {
+        self.transform(&Transform::new(self).mirror())
+    }
+
+    /// The board with every value `v` replaced by `values[v - 1]`
+    pub fn relabel(&self, values: &[u8]) -> Self {
+        self.transform(&Transform::new(self).relabel(values))
+    }
+}
+
+impl<const N: usize> Board<N> {
+    /// The least equivalent board in the order of [`Board::to_string`] with
+    /// empty cells first: of all boards that the transformations of
+    /// [`Transform`] make of this one, the one whose values, read row by row
+    /// with 0 for empty cells, come first
+    ///
+    /// Equivalent puzzles have the same canonical form, hence it serves to
+    /// find duplicates in collections. The work grows quickly with the size;
+    /// it takes a moment for 9x9 boards and fails with [`Error::TooLarge`]
+    /// for larger ones.
+    pub fn canonical_form(&self) -> Result<Self, Error> {
+        Ok(self.transform(&self.canonical_transform()?))
+    }
+
+    /// A 64-bit hash of the values of the
+    /// [`canonical_form`](Self::canonical_form), the same for equivalent
+    /// puzzles in every version and on every platform
+    ///
+    /// It finds duplicates in collections without keeping the boards and
+    /// serves as seed of [`Rng::new`](crate::Rng::new) to derive the same
+    /// numbers from the same puzzle. Boards without canonical form, larger
+    /// than 9x9, hash their own values and only equal boards get the same
+    /// fingerprint.
+    pub fn fingerprint(&self) -> u64 {
+        // FNV-1a of the size and the values with 0 for empty cells
+        let values = self.canonical_form()
+            .unwrap_or_else(|_| self.clone())
+            .fields()
+            .map(|fld| match fld {
+                Field::Value(v) => v,
+                Field::Options(_) => 0,
+            })
+            .collect::<Vec<_>>();
+
+        std::iter::once(N as u8)
+            .chain(values)
+            .fold(0xcbf2_9ce4_8422_2325, |hash, byte| {
+                (hash ^ u64::from(byte)).wrapping_mul(0x0100_0000_01b3)
+            })
+    }
+
+    /// The transformation that turns this board into its
+    /// [`canonical_form`](Self::canonical_form)
+    pub fn canonical_transform(&self) -> Result<Transform, Error> {
+        // the orders of the rows and columns alone run into millions for 16x16
+        if N > MAX_CANONICAL {
+            return Err(Error::TooLarge(N));
+        }
+
+        let (rows, cols) = self.box_size();
+        let values = self.fields()
+            .map(|fld| match fld {
+                Field::Value(v) => v,
+                Field::Options(_) => 0,
+            })
+            .collect::<Vec<_>>();
+
+        let row_orders = orders(N, rows);
+        let col_orders = orders(N, cols);
+        // transposing keeps the boxes only if they are square
+        let flips: &[bool] = if rows == cols { &[false, true] } else { &[false] };
+
+        let source = |flip: bool| {
+            move |row: usize, col: usize| match flip {
+                false => row * N + col,
+                true => col * N + row,
+            }
+        };
+        let labels = |flip, rows: &[usize], cols: &[usize], least: &mut [u8]| {
+            least_labels(&values, source(flip), rows, cols, least)
+        };
+
+        // only the first rows that come first can start the least board
+        let mut first = vec![u8::MAX; N];
+        for &flip in flips {
+            for row in 0..N {
+                for col_order in &col_orders {
+                    labels(flip, &[row], col_order, &mut first);
+                }
+            }
+        }
+
+        let mut best = first.clone();
+        best.resize(N * N, u8::MAX);
+        let mut choice = (false, 0, 0);
+        for &flip in flips {
+            for (c, col_order) in col_orders.iter().enumerate() {
+                let mut row0 = first.clone();
+                let starts = (0..N)
+                    .filter(|&row| {
+                        labels(flip, &[row], col_order, &mut row0) == Ordering::Equal
+                    })
+                    .collect::<Vec<_>>();
+
+                for (r, row_order) in row_orders.iter().enumerate() {
+                    if !starts.contains(&row_order[0]) {
+                        continue;
+                    }
+
+                    if labels(flip, row_order, col_order, &mut best) == Ordering::Less {
+                        choice = (flip, r, c);
+                    }
+                }
+            }
+        }
+
+        let (flip, r, c) = choice;
+        let (row_order, col_order) = (&row_orders[r], &col_orders[c]);
+        let cells = (0..N * N)
+            .map(|idx| {
+                let (row, col) = (row_order[idx / N], col_order[idx % N]);
+                if flip { col * N + row } else { row * N + col }
+            })
+            .collect::<Vec<_>>();
+
+        // number the values in the order they turn up, the missing ones last
+        let mut labels = vec![0; N];
+        let mut next = 1;
+        let order = cells.iter().map(|&idx| values[idx]).chain(1..=N as u8);
+        for val in order.filter(|&val| val != 0) {
+            if labels[val as usize - 1] == 0 {
+                labels[val as usize - 1] = next;
+                next += 1;
+            }
+        }
+
+        Ok(Transform {
+            size: N,
+            box_rows: if flip { cols } else { rows },
+            box_cols: if flip { rows } else { cols },
+            cells,
+            values: labels,
+        })
+    }
+}
+
+// Compares the values of the cells `cell(row, col)` for `rows` and `cols`, with
+// the values numbered in the order they turn up, to `least` and writes them
+// over it if they come first
+fn least_labels(
+    values: &[u8], cell: impl Fn(usize, usize) -> usize, rows: &[usize],
+    cols: &[usize], least: &mut [u8],
+) -> Ordering {
+    let mut labels = [0; 36];
+    let mut next = 1;
+    let mut order = Ordering::Equal;
+    for (idx, least) in least.iter_mut().enumerate() {
+        let label = match values[cell(rows[idx / cols.len()], cols[idx % cols.len()])] {
+            0 => 0,
+            val if labels[val as usize] != 0 => labels[val as usize],
+            val => {
+                labels[val as usize] = next;
+                next += 1;
+                next - 1
+            }
+        };
+
+        if order == Ordering::Equal {
+            order = label.cmp(least);
+        }
+        match order {
+            Ordering::Greater => break,
+            Ordering::Less => *least = label,
+            Ordering::Equal => (),
+        }
+    }
+
+    order
+}
+
+// All orders of the `size` lines that keep the blocks of `block` lines
+// together: the blocks in any order and the lines of each block, too
+fn orders(size: usize, block: usize) -> Vec<Vec<usize>> {
+    let mut ret = Vec::new();
+    for blocks in permutations(size / block) {
+        let mut orders = vec![Vec::new()];
+        for &b in &blocks {
+            orders = orders.into_iter()
+                .flat_map(|order| {
+                    permutations(block).into_iter().map(move |lines| {
+                        let mut order = order.clone();
+                        order.extend(lines.iter().map(|line| b * block + line));
+                        order
+                    })
+                })
+                .collect();
+        }
+        ret.extend(orders);
+    }
+
+    ret
+}
+
+// All orders of the numbers `0..n`
+fn permutations(n: usize) -> Vec<Vec<usize>> {
+    if n == 0 {
+        return vec![Vec::new()];
+    }
+
+    permutations(n - 1)
+        .into_iter()
+        .flat_map(|perm| {
+            (0..n).map(move |i| {
+                let mut perm = perm.clone();
+                perm.insert(i, n - 1);
+                perm
+            })
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const PUZZLE: &str = concat!(
+        "..4..5.2..52.3684..16.82...2...5.4......",
+        "1.73.641.....8...8....712......47.....1.9",
+    );
+
+    #[test]
+    fn rotate() {
+        let board: Board = PUZZLE.parse().unwrap();
+        let turned = board.rotate(1);
+        // the left column from the bottom becomes the top row
+        let row = (0..9).map(|col| turned.field_at(col)).collect::<Vec<_>>();
+        let col = (0..9).rev().map(|row| board.field_at(row * 9));
+        let col = col.collect::<Vec<_>>();
+        assert_eq!(row, col);
+
+        assert_eq!(board.rotate(4), board);
+        assert_eq!(board.rotate(2), board.rotate(1).rotate(1));
+        assert_eq!(board.rotate(1).rotate(3), board);
+        assert_eq!(board.transpose().transpose(), board);
+        assert_eq!(board.mirror().mirror(), board);
+        assert!(board.rotate(1).is_given((0, 1)));
+
+        // boxes of 2x3 turn into 3x2
+        let board = Board::<6>::new();
+        assert_eq!(board.rotate(1).box_size(), (3, 2));
+        assert_eq!(board.transpose().box_size(), (3, 2));
+        assert_eq!(board.rotate(2).box_size(), (2, 3));
+    }
+
+    #[test]
+    fn solution() {
+        let board: Board = PUZZLE.parse().unwrap();
+        let mut solution = board.clone();
+        solution.solve();
+
+        let transform = Transform::new(&board)
+            .rotate(1)
+            .permute_bands(&[2, 0, 1])
+            .permute_stacks(&[1, 2, 0])
+            .permute_rows(1, &[2, 1, 0])
+            .permute_cols(0, &[1, 0, 2])
+            .mirror()
+            .relabel(&[9, 8, 7, 6, 5, 4, 3, 2, 1]);
+
+        let mut other = board.transform(&transform);
+        assert_eq!(other.count_solutions(2), 1);
+        other.solve();
+        assert_eq!(other, solution.transform(&transform));
+        assert!(other.is_valid());
+
+        // the mapping of cells and values
+        let pos = transform.cell((0, 2));
+        let value = Field::Value(transform.value(4));
+        assert_eq!(other.field_at(pos.0 * 9 + pos.1), value);
+    }
+
+    #[test]
+    fn permute() {
+        let board: Board = PUZZLE.parse().unwrap();
+        let transform = Transform::new(&board).permute_bands(&[1, 0, 2]);
+        assert_eq!(transform.cell((0, 0)), (3, 0));
+        assert_eq!(board.transform(&transform).to_string()[27..54], PUZZLE[..27]);
+
+        let transform = Transform::new(&board).permute_rows(2, &[0, 2, 1]);
+        assert_eq!(transform.cell((7, 4)), (8, 4));
+        assert_eq!(transform.cell((1, 4)), (1, 4));
+
+        let relabeled = board.relabel(&[2, 1, 3, 4, 5, 6, 7, 8, 9]);
+        assert_eq!(relabeled.to_string()[..9], *"..4..5.1.");
+    }
+
+    #[test]
+    fn canonical_form() {
+        assert_eq!(orders(4, 2).len(), 8);
+        assert_eq!(permutations(3).len(), 6);
+
+        let board: Board<4> = "1.....3..2......".parse().unwrap();
+        let canonical = board.canonical_form().unwrap();
+        assert_eq!(canonical.to_string(), ".......1..2..3..");
+        assert_eq!(board.rotate(1).mirror().canonical_form(), Ok(canonical.clone()));
+        assert_eq!(canonical.canonical_form(), Ok(canonical));
+
+        let board: Board = PUZZLE.parse().unwrap();
+        let transform = Transform::new(&board)
+            .transpose()
+            .permute_bands(&[1, 2, 0])
+            .permute_cols(2, &[2, 0, 1])
+            .relabel(&[3, 1, 2, 5, 4, 9, 8, 7, 6]);
+        let canonical = board.canonical_form().unwrap();
+        assert_eq!(board.transform(&transform).canonical_form(), Ok(canonical.clone()));
+        assert!(canonical.to_string() < board.to_string().replace('.', "0"));
+        assert_eq!(canonical.count_solutions(2), 1);
+
+        let board = Board::<16>::new();
+        assert_eq!(board.canonical_form(), Err(Error::TooLarge(16)));
+    }
+
+    #[test]
+    fn fingerprint() {
+        let board: Board = PUZZLE.parse().unwrap();
+        let fingerprint = board.fingerprint();
+        let other = board.rotate(1).relabel(&[2, 1, 3, 4, 5, 6, 7, 8, 9]);
+        assert_eq!(other.fingerprint(), fingerprint);
+        let fewer: Board = PUZZLE.replacen('4', ".", 1).parse().unwrap();
+        assert_ne!(fewer.fingerprint(), fingerprint);
+
+        // the hash must not change between versions
+        assert_eq!(Board::<4>::new().fingerprint(), 0x129d_3d1e_1e96_bdd3);
+
+        let mut board = Board::<16>::new();
+        let empty = board.fingerprint();
+        board.set((0, 0), 1);
+        assert_ne!(board.fingerprint(), empty);
+        assert_eq!(board.clone().fingerprint(), board.fingerprint());
+    }
+
+    #[test]
+    #[should_panic]
+    fn invalid_permutation() {
+        let board = Board::<9>::new();
+        let _ = Transform::new(&board).permute_bands(&[0, 0, 1]);
+    }
+}
@@ -0,0 +1,286 @@
+use std::{
+    io::{self, Write},
+    thread,
+    time::Duration,
+};
+
+use crossterm::{
+    cursor,
+    event::{self, Event, KeyCode, KeyEvent, KeyEventKind},
+    execute, queue,
+    style::{Attribute, Color, Print, SetAttribute, SetForegroundColor},
+    terminal,
+};
+use sudoku_solver::{
+    Action, Board, Event as SolveEvent, Field, Reason, Snapshot, Solver, Step,
+};
+
+const HELP: &str = "arrows move, 1-9 enter, 0 clear, p pencil marks, h hint, \
+                    s solve, u undo, q quit";
+
+/// What the solver animation shows
+enum Frame {
+    Place(Step),
+    Restore(Box<Board>),
+}
+
+struct Game {
+    board: Board,
+    cursor: (usize, usize),
+    pencil: bool,
+    /// The pencil marks of the player for every cell
+    marks: Vec<Vec<u8>>,
+    history: Vec<(Snapshot, Vec<Vec<u8>>)>,
+    message: String,
+}
+
+/// Lets the player solve `board` in the terminal
+pub fn play(board: Board) -> io::Result<()> {
+    let mut game = Game {
+        board,
+        cursor: (0, 0),
+        pencil: false,
+        marks: vec![Vec::new(); 81],
+        history: Vec::new(),
+        message: HELP.to_string(),
+    };
+
+    let mut out = io::stdout();
+    terminal::enable_raw_mode()?;
+    execute!(out, terminal::EnterAlternateScreen, cursor::Hide)?;
+
+    let result = game.run(&mut out);
+
+    execute!(out, cursor::Show, terminal::LeaveAlternateScreen)?;
+    terminal::disable_raw_mode()?;
+
+    result
+}
+
+impl Game {
+    fn run(&mut self, out: &mut impl Write) -> io::Result<()> {
+        loop {
+            self.draw(out)?;
+
+            let key = match event::read()? {
+                Event::Key(KeyEvent { code, kind: KeyEventKind::Press, .. }) => code,
+                _ => continue,
+            };
+
+            let (row, col) = self.cursor;
+            match key {
+                KeyCode::Char('q') | KeyCode::Esc => return Ok(()),
+                KeyCode::Up => self.cursor.0 = (row + 8) % 9,
+                KeyCode::Down => self.cursor.0 = (row + 1) % 9,
+                KeyCode::Left => self.cursor.1 = (col + 8) % 9,
+                KeyCode::Right => self.cursor.1 = (col + 1) % 9,
+                KeyCode::Char(c @ '1'..='9') => self.enter(c as u8 - b'0'),
+                KeyCode::Char('0' | ' ') | KeyCode::Backspace | KeyCode::Delete => {
+                    self.clear()
+                }
+                KeyCode::Char('p') => {
+                    self.pencil = !self.pencil;
+                    self.message = if self.pencil {
+                        "Digits toggle pencil marks".to_string()
+                    } else {
+                        "Digits fill the cell".to_string()
+                    };
+                }
+                KeyCode::Char('h') => self.hint(),
+                KeyCode::Char('s') => self.animate(out)?,
+                KeyCode::Char('u') => self.undo(),
+                _ => self.message = HELP.to_string(),
+            }
+        }
+    }
+
+    fn idx(&self) -> usize {
+        self.cursor.0 * 9 + self.cursor.1
+    }
+
+    fn save(&mut self) {
+        self.history.push((self.board.snapshot(), self.marks.clone()));
+    }
+
+    fn undo(&mut self) {
+        match self.history.pop() {
+            Some((snapshot, marks)) => {
+                self.board.restore(&snapshot);
+                self.marks = marks;
+                self.message = "Undone".to_string();
+            }
+            None => self.message = "Nothing to undo".to_string(),
+        }
+    }
+
+    fn enter(&mut self, val: u8) {
+        if self.board.is_given(self.cursor) {
+            self.message = "The cell is part of the puzzle".to_string();
+            return;
+        }
+
+        let idx = self.idx();
+        self.save();
+        if self.pencil {
+            let marks = &mut self.marks[idx];
+            match marks.iter().position(|&v| v == val) {
+                Some(i) => drop(marks.remove(i)),
+                None => {
+                    marks.push(val);
+                    marks.sort_unstable();
+                }
+            }
+            return;
+        }
+
+        self.replace(idx, Some(val));
+        self.message = if self.board.is_solved() {
+            "Solved!".to_string()
+        } else {
+            String::new()
+        };
+    }
+
+    fn clear(&mut self) {
+        if self.board.is_given(self.cursor) {
+            self.message = "The cell is part of the puzzle".to_string();
+        } else if let Field::Value(_) = self.board.field(self.cursor) {
+            self.save();
+            self.replace(self.idx(), None);
+        }
+    }
+
+    // The board can't take back a value, hence it gets rebuilt from the
+    // puzzle and the other values of the player
+    fn replace(&mut self, idx: usize, val: Option<u8>) {
+        let mut board = self.board.puzzle();
+        for (i, fld) in self.board.fields().enumerate() {
+            let entry = match fld {
+                _ if i == idx => val,
+                Field::Value(v) if !self.board.is_given((i / 9, i % 9)) => Some(v),
+                _ => None,
+            };
+
+            if let Some(v) = entry {
+                board.set_idx(i, v, Reason::Set);
+            }
+        }
+
+        self.board = board;
+    }
+
+    fn hint(&mut self) {
+        self.message = match self.board.next_hint() {
+            Some(hint) => hint.explain(),
+            None => "No hint found".to_string(),
+        };
+    }
+
+    // Shows the solver filling the board step by step; any key skips to the end
+    fn animate(&mut self, out: &mut impl Write) -> io::Result<()> {
+        let mut frames = Vec::new();
+        let mut solver = Solver::default();
+        solver.backtracking(true);
+        solver.solve_with(&mut self.board.clone(), |event| match event {
+            SolveEvent::Step(step) if step.action == Action::Place => {
+                frames.push(Frame::Place(step.clone()));
+            }
+            SolveEvent::Step(_) => (),
+            SolveEvent::Restore(board) => {
+                frames.push(Frame::Restore(Box::new(board.clone())))
+            }
+        });
+
+        self.save();
+        let mut skip = false;
+        for frame in frames {
+            match frame {
+                Frame::Place(step) => {
+                    self.message = step.to_string();
+                    let idx = step.cell.0 * 9 + step.cell.1;
+                    self.board.set_idx(idx, step.value, step.reason);
+                }
+                Frame::Restore(board) => self.board = *board,
+            }
+
+            if !skip {
+                self.draw(out)?;
+                skip = event::poll(Duration::from_millis(0))?;
+                if skip {
+                    event::read()?;
+                } else {
+                    thread::sleep(Duration::from_millis(40));
+                }
+            }
+        }
+
+        self.message = if self.board.is_solved() {
+            "Solved by the solver; u takes it back".to_string()
+        } else {
+            "The puzzle has no solution".to_string()
+        };
+
+        Ok(())
+    }
+
+    fn draw(&self, out: &mut impl Write) -> io::Result<()> {
+        queue!(
+            out, terminal::Clear(terminal::ClearType::All), cursor::MoveTo(0, 0)
+        )?;
+
+        let conflicts = self.board.conflicts();
+        for row in 0..9 {
+            if row % 3 == 0 {
+                queue!(out, Print(" +-------+-------+-------+\r\n"))?;
+            }
+
+            for col in 0..9 {
+                if col % 3 == 0 {
+                    queue!(out, Print(" |"))?;
+                }
+                queue!(out, Print(' '))?;
+
+                let pos = (row, col);
+                if pos == self.cursor {
+                    queue!(out, SetAttribute(Attribute::Reverse))?;
+                }
+
+                match self.board.field(pos) {
+                    Field::Value(v) => {
+                        let color = if conflicts.contains(&pos) {
+                            Color::Red
+                        } else if self.board.is_given(pos) {
+                            Color::Reset
+                        } else {
+                            Color::Blue
+                        };
+                        queue!(out, SetForegroundColor(color), Print(v))?;
+                    }
+                    Field::Options(_) if !self.marks[row * 9 + col].is_empty() => {
+                        queue!(out, SetForegroundColor(Color::DarkGrey), Print('*'))?;
+                    }
+                    Field::Options(_) => {
+                        queue!(out, SetForegroundColor(Color::DarkGrey), Print('.'))?;
+                    }
+                }
+
+                queue!(out, SetForegroundColor(Color::Reset))?;
+                queue!(out, SetAttribute(Attribute::Reset))?;
+            }
+
+            queue!(out, Print(" |\r\n"))?;
+        }
+        queue!(out, Print(" +-------+-------+-------+\r\n\r\n"))?;
+
+        let marks = &self.marks[self.idx()];
+        if !marks.is_empty() {
+            let marks = marks.iter().map(u8::to_string).collect::<Vec<_>>();
+            queue!(out, Print(format!(" Pencil marks: {}\r\n", marks.join(" "))))?;
+        }
+
+        let mode = if self.pencil { " [pencil]" } else { "" };
+        queue!(out, Print(format!(" {}{}\r\n", self.message, mode)))?;
+
+        out.flush()
+    }
+}
@@ -0,0 +1,196 @@
+use std::{fmt, sync::Arc};
+
+use crate::sudoku::{Board, Field};
+
+/// A row, column, box or another set of cells that must hold every value once,
+/// each numbered from 0
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum Unit {
+    Row(usize),
+    Col(usize),
+    /// The boxes are numbered row by row; irregular regions take their place
+    Box(usize),
+    /// The main diagonal 0 from the top left and the anti-diagonal 1 from the
+    /// top right
+    Diagonal(usize),
+    /// The windows of Windoku between the boxes, numbered row by row
+    Window(usize),
+    /// The cells at the same position within each box, numbered by that
+    /// position row by row
+    Group(usize),
+}
+
+impl fmt::Display for Unit {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Unit::Row(r) => write!(f, "row {}", r + 1),
+            Unit::Col(c) => write!(f, "column {}", c + 1),
+            Unit::Box(b) => write!(f, "box {}", b + 1),
+            Unit::Diagonal(0) => write!(f, "main diagonal"),
+            Unit::Diagonal(_) => write!(f, "anti-diagonal"),
+            Unit::Window(w) => write!(f, "window {}", w + 1),
+            Unit::Group(g) => write!(f, "disjoint group {}", g + 1),
+        }
+    }
+}
+
+impl<const N: usize> Board<N> {
+    /// The rows from top to bottom
+    pub fn rows(&self) -> impl Iterator<Item = Unit> {
+        (0..N).map(Unit::Row)
+    }
+
+    /// The columns from left to right
+    pub fn cols(&self) -> impl Iterator<Item = Unit> {
+        (0..N).map(Unit::Col)
+    }
+
+    /// The boxes row by row
+    pub fn boxes(&self) -> impl Iterator<Item = Unit> {
+        (0..N).map(Unit::Box)
+    }
+
+    /// The main diagonal and the anti-diagonal
+    pub fn diagonals(&self) -> impl Iterator<Item = Unit> {
+        (0..2).map(Unit::Diagonal)
+    }
+
+    /// The windows of Windoku: boxes one cell apart from each other and from
+    /// the edges, e.g. four of them on a 9x9 board
+    pub fn windows(&self) -> impl Iterator<Item = Unit> {
+        let (rows, cols) = self.box_size();
+        (0..(N - 1) / (rows + 1) * ((N - 1) / (cols + 1))).map(Unit::Window)
+    }
+
+    /// The disjoint groups: the cells at the same position within each box
+    pub fn groups(&self) -> impl Iterator<Item = Unit> {
+        (0..N).map(Unit::Group)
+    }
+
+    /// The units of all constraints: the rows, columns and boxes followed by
+    /// the units added with [`Board::add_unit`]
+    pub fn units(&self) -> impl Iterator<Item = Unit> {
+        let table = Arc::clone(self.peer_table());
+        (0..table.units().len()).map(move |i| table.units()[i])
+    }
+
+    /// Index of the `i`-th cell of `unit`
+    pub fn unit_cell(&self, unit: Unit, i: usize) -> usize {
+        self.unit_table().cells::<N>(unit)[i]
+    }
+
+    /// Indices of the cells of `unit`
+    pub fn unit_cells(&self, unit: Unit) -> impl Iterator<Item = usize> + '_ {
+        self.unit_table().cells::<N>(unit).iter().copied()
+    }
+
+    /// The cells of `unit`
+    pub fn unit(&self, unit: Unit) -> impl Iterator<Item = Field> + '_ {
+        self.unit_cells(unit).map(move |idx| self.field_at(idx))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn cells() {
+        let board = Board::<9>::new();
+        assert_eq!(
+            board.unit_cells(Unit::Row(2)).collect::<Vec<_>>(),
+            (18..27).collect::<Vec<_>>()
+        );
+        assert_eq!(
+            board.unit_cells(Unit::Col(1)).collect::<Vec<_>>(),
+            vec![1, 10, 19, 28, 37, 46, 55, 64, 73]
+        );
+        assert_eq!(
+            board.unit_cells(Unit::Box(5)).collect::<Vec<_>>(),
+            vec![33, 34, 35, 42, 43, 44, 51, 52, 53]
+        );
+
+        // 2x3 boxes
+        let board = Board::<6>::new();
+        assert_eq!(
+            board.unit_cells(Unit::Box(3)).collect::<Vec<_>>(),
+            vec![15, 16, 17, 21, 22, 23]
+        );
+    }
+
+    #[test]
+    fn units() {
+        let board = Board::<9>::new();
+        assert_eq!(board.units().count(), 27);
+
+        // every cell is in exactly one row, column and box
+        let mut count = [0; 81];
+        board.units()
+            .flat_map(|unit| board.unit_cells(unit))
+            .for_each(|idx| count[idx] += 1);
+        assert!(count.iter().all(|&n| n == 3));
+    }
+
+    #[test]
+    fn unit_fields() {
+        let board: Board = format!("..7{}", ".".repeat(78)).parse().unwrap();
+        assert!(matches!(board.unit(Unit::Box(0)).nth(2), Some(Field::Value(7))));
+        assert_eq!(
+            board.unit(Unit::Col(2)).filter(|f| matches!(f, Field::Value(_))).count(),
+            1
+        );
+    }
+
+    #[test]
+    fn diagonals() {
+        let mut board = Board::<9>::new();
+        assert_eq!(
+            board.unit_cells(Unit::Diagonal(1)).collect::<Vec<_>>(),
+            vec![8, 16, 24, 32, 40, 48, 56, 64, 72]
+        );
+
+        board.add_unit(Unit::Diagonal(0));
+        assert_eq!(board.units().count(), 28);
+        assert_eq!(board.units().last(), Some(Unit::Diagonal(0)));
+    }
+
+    #[test]
+    fn windows() {
+        let board = Board::<9>::new();
+        assert_eq!(board.windows().count(), 4);
+        assert_eq!(
+            board.unit_cells(Unit::Window(3)).collect::<Vec<_>>(),
+            vec![50, 51, 52, 59, 60, 61, 68, 69, 70]
+        );
+
+        assert_eq!(Board::<4>::new().windows().count(), 1);
+        assert_eq!(Board::<16>::new().windows().count(), 9);
+    }
+
+    #[test]
+    fn groups() {
+        let board = Board::<9>::new();
+        assert_eq!(board.groups().count(), 9);
+        assert_eq!(
+            board.unit_cells(Unit::Group(4)).collect::<Vec<_>>(),
+            vec![10, 13, 16, 37, 40, 43, 64, 67, 70]
+        );
+
+        // 2x3 boxes
+        assert_eq!(
+            Board::<6>::new().unit_cells(Unit::Group(5)).collect::<Vec<_>>(),
+            vec![8, 11, 20, 23, 32, 35]
+        );
+    }
+
+    #[test]
+    fn display() {
+        assert_eq!(Unit::Row(0).to_string(), "row 1");
+        assert_eq!(Unit::Col(8).to_string(), "column 9");
+        assert_eq!(Unit::Box(4).to_string(), "box 5");
+        assert_eq!(Unit::Diagonal(1).to_string(), "anti-diagonal");
+        assert_eq!(Unit::Window(0).to_string(), "window 1");
+        assert_eq!(Unit::Group(2).to_string(), "disjoint group 3");
+    }
+}
@@ -0,0 +1,56 @@
+use wasm_bindgen::prelude::*;
+
+use crate::error::Error;
+use crate::strategy::SolverConfig;
+use crate::sudoku::{Board, SolveResult};
+
+// Solves `puzzle` with guessing as last resort
+fn solve_board(puzzle: &str, steps: bool) -> Result<Board, Error> {
+    let mut board: Board = puzzle.parse()?;
+    if !board.is_valid() {
+        return Err(Error::Unsolvable);
+    }
+
+    board.record_steps(steps);
+    let config = SolverConfig { backtracking: true, ..Default::default() };
+
+    match board.solve_with_config(&config) {
+        SolveResult::Contradiction => Err(Error::Unsolvable),
+        _ => Ok(board),
+    }
+}
+
+/// Solves the puzzle and returns the solution as line of 81 digits
+#[wasm_bindgen]
+pub fn solve(puzzle: &str) -> Result<String, JsError> {
+    Ok(solve_board(puzzle, false)?.to_string())
+}
+
+/// Solves the puzzle and returns the steps as JSON array
+#[wasm_bindgen]
+pub fn solve_steps(puzzle: &str) -> Result<String, JsError> {
+    let board = solve_board(puzzle, true)?;
+    Ok(serde_json::to_string(board.steps().as_deref().unwrap_or(&[]))?)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::parse::ParseError;
+
+    #[test]
+    fn solve_puzzle() {
+        let board = solve_board(&format!("12{}", ".".repeat(79)), true).unwrap();
+        assert!(board.is_solved());
+        assert!(board.steps().as_ref().unwrap().len() >= 79);
+
+        assert_eq!(
+            solve_board("123", false),
+            Err(Error::Parse(ParseError::TooFewCells(3)))
+        );
+        assert_eq!(
+            solve_board(&format!("11{}", ".".repeat(79)), false),
+            Err(Error::Unsolvable)
+        );
+    }
+}
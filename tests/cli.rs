@@ -0,0 +1,50 @@
+#![cfg(feature = "cli")]
+
+use std::io::Write;
+use std::process::{Command, Stdio};
+
+// The output of the command line tool with `args` for the puzzle `input` on
+// stdin
+fn run(args: &[&str], input: &str) -> String {
+    let mut child = Command::new(env!("CARGO_BIN_EXE_sudoku-solver"))
+        .args(args)
+        .arg("-")
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .spawn()
+        .unwrap();
+    child.stdin.take().unwrap().write_all(input.as_bytes()).unwrap();
+
+    let output = child.wait_with_output().unwrap();
+    String::from_utf8(output.stdout).unwrap()
+}
+
+#[test]
+fn symbols_csv_marks() {
+    let args = ["solve", "--symbols", "WORD", "--output", "csv", "--marks"];
+    let output = run(&args, "WO..............");
+    let mut lines = output.lines();
+    assert_eq!(lines.next(), Some("row,column,value,candidates"));
+    assert_eq!(lines.next(), Some("1,1,W,"));
+    assert_eq!(lines.next(), Some("1,2,O,"));
+    assert_eq!(lines.next(), Some("1,3,,RD"));
+}
+
+#[test]
+fn symbols_labels() {
+    let args = ["solve", "--symbols", "WORD", "--output", "pretty", "--labels"];
+    let output = run(&args, "WORDRDWO........");
+    let mut lines = output.lines().skip_while(|line| !line.contains('1'));
+    assert_eq!(lines.next(), Some("      1 2  3 4"));
+    assert_eq!(lines.next(), Some("    ┌─────┬─────┐"));
+    assert_eq!(lines.next(), Some("  1 │ W O │ R D │"));
+    assert_eq!(lines.next(), Some("  2 │ R D │ W O │"));
+}
+
+#[test]
+fn iteration_limit() {
+    let args = ["solve", "--backtracking", "--max-iterations", "3"];
+    let output = run(&args, &format!("1{}", ".".repeat(80)));
+    assert!(output.contains("\nIteration limit reached, partial solution:\n"));
+    assert!(!output.contains("Stuck"));
+}